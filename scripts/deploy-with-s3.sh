@@ -28,6 +28,9 @@ fi
 
 echo "✅ Backend canister deployed successfully"
 
+# Certificates are signed with the local replica's threshold ECDSA key
+dfx canister call backend set_signing_key_name '("dfx_test_key")' --network local
+
 # Deploy frontend canister
 dfx deploy frontend --network local
 
@@ -0,0 +1,186 @@
+// Archive canister for the OriginStamp NFT transaction log
+//
+// The backend canister spawns archives and moves its oldest ICRC-3 blocks here
+// once its local log grows past a threshold. An archive holds a contiguous
+// range of blocks starting at `first_index` and serves them through
+// `icrc3_get_blocks`, which the backend advertises as the callback for
+// archived ranges.
+
+use candid::{CandidType, Nat, Principal};
+use ic_cdk::api::caller;
+use ic_cdk::export_candid;
+use serde::Deserialize;
+use std::cell::RefCell;
+
+// Most blocks returned by a single icrc3_get_blocks call
+const MAX_BLOCKS_PER_RESPONSE: u64 = 1000;
+
+// Generic ICRC-3 value; blocks are stored exactly as the backend produced them
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum Value {
+    Blob(Vec<u8>),
+    Text(String),
+    Nat(Nat),
+    Int(candid::Int),
+    Array(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ArchiveInitArgs {
+    // The canister allowed to append blocks
+    pub ledger_id: Principal,
+    // Index of the first block this archive will hold
+    pub first_index: u64,
+    pub max_blocks: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetBlocksRequest {
+    pub start: Nat,
+    pub length: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct BlockWithId {
+    pub id: Nat,
+    pub block: Value,
+}
+
+candid::define_function!(pub GetBlocksCallback : (Vec<GetBlocksRequest>) -> (GetBlocksResult) query);
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ArchivedBlocks {
+    pub args: Vec<GetBlocksRequest>,
+    pub callback: GetBlocksCallback,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetBlocksResult {
+    pub log_length: Nat,
+    pub blocks: Vec<BlockWithId>,
+    pub archived_blocks: Vec<ArchivedBlocks>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+struct ArchiveState {
+    config: ArchiveInitArgs,
+    blocks: Vec<Value>,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<ArchiveState>> = const { RefCell::new(None) };
+}
+
+#[ic_cdk::init]
+fn init(args: ArchiveInitArgs) {
+    STATE.with(|state| {
+        *state.borrow_mut() = Some(ArchiveState {
+            config: args,
+            blocks: Vec::new(),
+        })
+    });
+}
+
+// Archived history must survive upgrades, so the state is kept in stable memory across them
+#[ic_cdk::pre_upgrade]
+fn pre_upgrade() {
+    let state = STATE.with(|state| state.borrow_mut().take());
+    ic_cdk::storage::stable_save((state,)).expect("failed to save archive state");
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    let (state,): (Option<ArchiveState>,) =
+        ic_cdk::storage::stable_restore().expect("failed to restore archive state");
+    STATE.with(|state_cell| *state_cell.borrow_mut() = state);
+}
+
+// Append the next blocks of the log (ledger only). `first_index` is the index of
+// the first block in `blocks` and must continue the archive; a chunk that was
+// already stored (a retried append) is accepted without storing it again.
+#[ic_cdk::update]
+pub fn append_blocks(first_index: u64, blocks: Vec<Value>) -> Result<(), String> {
+    with_state(|state| {
+        if caller() != state.config.ledger_id {
+            return Err("Only the ledger canister can append blocks".to_string());
+        }
+        let next_index = state.config.first_index + state.blocks.len() as u64;
+        if first_index != next_index {
+            let stored = first_index >= state.config.first_index
+                && first_index.saturating_add(blocks.len() as u64) <= next_index;
+            return if stored {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Blocks must continue the archive at index {next_index}, got {first_index}"
+                ))
+            };
+        }
+        if (state.blocks.len() + blocks.len()) as u64 > state.config.max_blocks {
+            return Err(format!(
+                "Archive full: at most {} blocks",
+                state.config.max_blocks
+            ));
+        }
+        state.blocks.extend(blocks);
+        Ok(())
+    })
+}
+
+// Number of blocks this archive can still accept
+#[ic_cdk::query]
+pub fn remaining_capacity() -> u64 {
+    with_state(|state| {
+        state
+            .config
+            .max_blocks
+            .saturating_sub(state.blocks.len() as u64)
+    })
+}
+
+// Blocks in the requested ranges that this archive holds, in request order
+#[ic_cdk::query]
+pub fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    with_state(|state| {
+        let first_index = state.config.first_index;
+        let end_index = first_index + state.blocks.len() as u64;
+        let mut budget = MAX_BLOCKS_PER_RESPONSE;
+        let mut found = Vec::new();
+
+        for range in args {
+            let Ok(start) = u64::try_from(&range.start.0) else {
+                continue;
+            };
+            let length = u64::try_from(&range.length.0)
+                .unwrap_or(u64::MAX)
+                .min(budget);
+            // Clamp the requested range to the blocks held here; a range outside
+            // of them yields nothing
+            let end = start.saturating_add(length).min(end_index);
+            let start = start.max(first_index);
+            for id in start..end {
+                found.push(BlockWithId {
+                    id: Nat::from(id),
+                    block: state.blocks[(id - first_index) as usize].clone(),
+                });
+            }
+            budget -= end.saturating_sub(start);
+        }
+
+        GetBlocksResult {
+            log_length: Nat::from(end_index),
+            blocks: found,
+            archived_blocks: Vec::new(),
+        }
+    })
+}
+
+fn with_state<R>(f: impl FnOnce(&mut ArchiveState) -> R) -> R {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        f(state.as_mut().expect("archive not initialized"))
+    })
+}
+
+export_candid!();
@@ -1,6 +1,7 @@
 // Main entry point for the backend canister
 
 // Required imports
+use candid::Nat;
 use ic_cdk::export_candid;
 
 // Module declarations
@@ -14,9 +15,23 @@ pub use modules::nft::mint_certificate_nft;
 
 // Re-export all types needed for Candid interface
 pub use types::{
-    Account, Certificate, CollectionMetadata, CreateCertificateRequest, LoginResult,
-    NFTGenerationResult, PhysicalArtSession, S3Config, Token, TokenMetadata, TransferRequest,
-    TransferResponse, UploadFileData, VerificationResult,
+    Account, ApprovalInfo, ApproveCollectionArg, ApproveCollectionError, ApproveCollectionResult,
+    ApproveTokenArg, ApproveTokenError, ApproveTokenResult, ArchiveConfig, ArchiveFailure,
+    ArchiveInfo, ArchivedBlocks, BatchVerificationEntry, BlockWithId, BurnArg, BurnError,
+    BurnResult, Certificate, CertificateDocument, CertificateDocumentFormat, CertificatePublicKey,
+    CertificateQrCode, CertificateSearchHit, CertificateSearchQuery, CertificateSearchResult,
+    CertificateStatus, CertificateTemplate, CertificateType, CertificateTypeDetails,
+    CertifiedCertificate, CollectionApproval, CollectionMetadata, CollectionMetadataChange,
+    CreateCertificateRequest, CredentialVerificationResult, DataCertificate, GetArchivesArgs,
+    GetBlocksCallback, GetBlocksRequest, GetBlocksResult, HttpRequest, HttpResponse, IsApprovedArg,
+    LoginResult, NFTGenerationResult, PhotoEvidence, PhysicalArtSession, ProvenanceManifest,
+    QrCodeFormat, QrCodeOptions, QrErrorCorrection, RevocationEntry, RevocationReason,
+    RevokeCollectionApprovalArg, RevokeCollectionApprovalError, RevokeCollectionApprovalResult,
+    RevokeTokenApprovalArg, RevokeTokenApprovalError, RevokeTokenApprovalResponse, S3Config,
+    SupportedBlockType, SupportedStandard, Token, TokenApproval, TokenMetadata, TransferArg,
+    TransferError, TransferFromArg, TransferFromResult, TransferPolicy, TransferResult,
+    UploadFileData, Value, VerificationBundle, VerificationChannel, VerificationEvent,
+    VerificationResult, VerificationStats,
 };
 
 // Dashboard metrics structure
@@ -25,22 +40,30 @@ pub struct DashboardMetrics {
     pub total_users: usize,
     pub total_sessions: usize,
     pub total_certificates: usize,
+    pub total_verifications: u64,
+    pub verifications_last_30_days: u64,
 }
 
 // Re-export all public functions from modules for easier access
 pub use modules::certificates::*;
+pub use modules::http::*;
 pub use modules::nft::*;
 pub use modules::physical_art::*;
 pub use modules::s3::*;
+pub use modules::signing::*;
 pub use modules::users::*;
 
 // Get dashboard metrics
 #[ic_cdk::query]
 pub fn get_dashboard_metrics() -> DashboardMetrics {
+    let (total_verifications, verifications_last_30_days) =
+        modules::certificates::verification_volume(30);
     DashboardMetrics {
         total_users: get_user_count(),
         total_sessions: get_session_count(),
         total_certificates: get_certificate_count(),
+        total_verifications,
+        verifications_last_30_days,
     }
 }
 
@@ -49,6 +72,12 @@ pub fn get_dashboard_metrics() -> DashboardMetrics {
 fn init() {
     // Initialize demo coupons for development/testing
     let _ = modules::certificates::initialize_demo_coupons();
+
+    // Publish the (empty) certificate tree so absence proofs work from the start
+    modules::certification::update_certified_data();
+
+    // Serve the collection document at /collection.json before the first mint
+    modules::nft::publish_collection_document();
 }
 
 // Export the Candid interface
@@ -0,0 +1,196 @@
+use super::{authorize_certificate_management, get_certificate_by_id, verify_certificate};
+use crate::types::{
+    DailyVerificationCount, VerificationChannel, VerificationCounts, VerificationEvent,
+    VerificationResult, VerificationStats,
+};
+use crate::utils::to_hex;
+use candid::Principal;
+use ic_cdk::api::{caller, time};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+// =============================================================================
+// Verification event log
+// =============================================================================
+//
+// Queries cannot write state, so only verifications made through
+// `record_certificate_verification` (the web app, QR landing page and API
+// clients that opt in) are logged. Callers are stored as a salted hash so repeat
+// checks can be told apart without revealing who made them.
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+// Most recent events kept per certificate
+const MAX_EVENTS_PER_CERTIFICATE: usize = 500;
+// Daily counters older than this are dropped
+const RETENTION_DAYS: u64 = 366;
+const DEFAULT_EVENT_LIMIT: u32 = 50;
+const DEFAULT_STATS_DAYS: u64 = 30;
+
+#[derive(Default)]
+struct VerificationLog {
+    events: VecDeque<VerificationEvent>,
+    daily: BTreeMap<u64, VerificationCounts>,
+    total: VerificationCounts,
+}
+
+thread_local! {
+    static VERIFICATION_LOGS: RefCell<HashMap<String, VerificationLog>> = RefCell::new(HashMap::new());
+    // Canister-wide daily volume, used by the dashboard
+    static DAILY_VERIFICATIONS: RefCell<BTreeMap<u64, VerificationCounts>> = const { RefCell::new(BTreeMap::new()) };
+    static TOTAL_VERIFICATIONS: RefCell<u64> = const { RefCell::new(0) };
+    // Secret salt for caller hashes, drawn from raw_rand on first use
+    static CALLER_SALT: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+// Verify a certificate and record the check in its verification log
+#[ic_cdk::update]
+pub async fn record_certificate_verification(
+    certificate_id: String,
+    channel: VerificationChannel,
+) -> Result<VerificationResult, String> {
+    let verifier = caller();
+    let result = verify_certificate(certificate_id.clone())?;
+    let caller_hash = anonymize_caller(verifier).await?;
+
+    let now = time();
+    let event = VerificationEvent {
+        certificate_id: certificate_id.clone(),
+        timestamp: now,
+        caller_hash,
+        channel,
+        valid: result.valid,
+    };
+    let day_start = now - now % NANOS_PER_DAY;
+    let cutoff = day_start.saturating_sub(RETENTION_DAYS * NANOS_PER_DAY);
+
+    VERIFICATION_LOGS.with(|logs| {
+        let mut logs = logs.borrow_mut();
+        let log = logs.entry(certificate_id).or_default();
+        log.total.add(channel);
+        log.daily.entry(day_start).or_default().add(channel);
+        log.daily = log.daily.split_off(&cutoff);
+        log.events.push_back(event);
+        if log.events.len() > MAX_EVENTS_PER_CERTIFICATE {
+            log.events.pop_front();
+        }
+    });
+    DAILY_VERIFICATIONS.with(|daily| {
+        let mut daily = daily.borrow_mut();
+        daily.entry(day_start).or_default().add(channel);
+        *daily = daily.split_off(&cutoff);
+    });
+    TOTAL_VERIFICATIONS.with(|total| *total.borrow_mut() += 1);
+
+    Ok(result)
+}
+
+// Most recent verification events for a certificate, newest first (owner, moderator or admin)
+#[ic_cdk::query]
+pub fn get_certificate_verification_events(
+    certificate_id: String,
+    limit: Option<u32>,
+) -> Result<Vec<VerificationEvent>, String> {
+    authorize_log_access(&certificate_id)?;
+
+    let limit = limit.unwrap_or(DEFAULT_EVENT_LIMIT) as usize;
+    Ok(VERIFICATION_LOGS.with(|logs| {
+        logs.borrow()
+            .get(&certificate_id)
+            .map(|log| log.events.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }))
+}
+
+// Verification counts per channel, in total and per UTC day since `since`
+// (default: the last 30 days)
+#[ic_cdk::query]
+pub fn get_certificate_verification_stats(
+    certificate_id: String,
+    since: Option<u64>,
+) -> Result<VerificationStats, String> {
+    authorize_log_access(&certificate_id)?;
+
+    let now = time();
+    let since = since.unwrap_or_else(|| now.saturating_sub(DEFAULT_STATS_DAYS * NANOS_PER_DAY));
+    let since_day = since - since % NANOS_PER_DAY;
+
+    Ok(VERIFICATION_LOGS.with(|logs| {
+        let logs = logs.borrow();
+        let log = logs.get(&certificate_id);
+        VerificationStats {
+            certificate_id: certificate_id.clone(),
+            total: log.map(|log| log.total.clone()).unwrap_or_default(),
+            last_verified_at: log
+                .and_then(|log| log.events.back())
+                .map(|event| event.timestamp),
+            daily: log
+                .map(|log| {
+                    log.daily
+                        .range(since_day..)
+                        .map(|(day_start, counts)| DailyVerificationCount {
+                            day_start: *day_start,
+                            counts: counts.clone(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }))
+}
+
+// Canister-wide verification volume: (all time, last `days` days)
+pub(crate) fn verification_volume(days: u64) -> (u64, u64) {
+    let now = time();
+    let since = now.saturating_sub(days * NANOS_PER_DAY);
+    let since_day = since - since % NANOS_PER_DAY;
+
+    let recent = DAILY_VERIFICATIONS.with(|daily| {
+        daily
+            .borrow()
+            .range(since_day..)
+            .map(|(_, counts)| counts.total)
+            .sum()
+    });
+    (TOTAL_VERIFICATIONS.with(|total| *total.borrow()), recent)
+}
+
+fn authorize_log_access(certificate_id: &str) -> Result<(), String> {
+    let certificate = get_certificate_by_id(certificate_id.to_string())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+    authorize_certificate_management(&certificate).map(|_| ())
+}
+
+// First 8 bytes of SHA-256(salt || principal); None for anonymous callers
+async fn anonymize_caller(principal: Principal) -> Result<Option<String>, String> {
+    if principal == Principal::anonymous() {
+        return Ok(None);
+    }
+
+    let salt = match CALLER_SALT.with(|salt| salt.borrow().clone()) {
+        Some(salt) => salt,
+        None => {
+            let (random,) = ic_cdk::api::management_canister::main::raw_rand()
+                .await
+                .map_err(|(_, e)| format!("Failed to generate salt: {e}"))?;
+            // Another call may have initialized the salt while this one awaited
+            CALLER_SALT.with(|salt| salt.borrow_mut().get_or_insert(random).clone())
+        }
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(&salt);
+    hasher.update(principal.as_slice());
+    Ok(Some(to_hex(&hasher.finalize()[..8])))
+}
+
+impl VerificationCounts {
+    fn add(&mut self, channel: VerificationChannel) {
+        match channel {
+            VerificationChannel::Qr => self.qr += 1,
+            VerificationChannel::Api => self.api += 1,
+            VerificationChannel::Web => self.web += 1,
+        }
+        self.total += 1;
+    }
+}
@@ -0,0 +1,144 @@
+use super::pages::evidence_timeline;
+use super::{certificate_signing_payload, get_certificate_by_id, get_certificate_revocation};
+use crate::modules::{nft, physical_art, signing};
+use crate::types::{Certificate, CertificatePublicKey, PhotoEvidence, VerificationBundle};
+use crate::utils::{canonical_json, to_hex};
+use ic_cdk::api::time;
+use originstamp_verifier::stringify_large_integers;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// Offline verification bundles (format: originstamp-verification-bundle v2)
+// =============================================================================
+//
+// The bundle carries everything needed to check a certificate without network
+// access. `bundle_signature` covers the canonical JSON (JCS) of the rest of the
+// bundle, so the evidence Merkle root, process log and NFT snapshot are attested
+// by the same key as the certificate. Integers above 2^53 (nanosecond timestamps)
+// are carried as strings so the canonical form keeps every digit, and only photos
+// with an owner-recorded hash become Merkle leaves. The format is documented in
+// docs/05-backend/07-verification-bundle.md and checked by src/verifier.
+
+const BUNDLE_FORMAT: &str = "originstamp-verification-bundle";
+const BUNDLE_VERSION: u32 = 2;
+const SIGNATURE_ALGORITHM: &str = "ecdsa-secp256k1-sha256";
+
+thread_local! {
+    static BUNDLE_CACHE: RefCell<HashMap<String, ([u8; 32], VerificationBundle)>> = RefCell::new(HashMap::new());
+}
+
+// Export a signed, self-contained verification bundle for a certificate
+#[ic_cdk::update]
+pub async fn export_verification_bundle(
+    certificate_id: String,
+) -> Result<VerificationBundle, String> {
+    let certificate = get_certificate_by_id(certificate_id.clone())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+
+    // Signed with the certificate's own key so one pinned key checks both signatures
+    let key = signing::signing_key(&certificate.signing_key_id)
+        .ok_or_else(|| "Certificate signing key is not registered".to_string())?;
+    let mut bundle = unsigned_bundle(&certificate, &key);
+    let content_digest: [u8; 32] = Sha256::digest(canonical_json(&bundle)).into();
+    if let Some(cached) = BUNDLE_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .get(&certificate_id)
+            .filter(|(digest, _)| *digest == content_digest)
+            .map(|(_, bundle)| bundle.clone())
+    }) {
+        return Ok(cached);
+    }
+
+    bundle["generated_at"] = json!(time());
+    stringify_large_integers(&mut bundle);
+    let signature = signing::sign_message(&key, canonical_json(&bundle).as_bytes()).await?;
+    bundle["bundle_signature"] = json!({
+        "algorithm": SIGNATURE_ALGORITHM,
+        "key_id": key.key_id,
+        "value": to_hex(&signature),
+    });
+
+    let exported = VerificationBundle {
+        certificate_id: certificate_id.clone(),
+        file_name: format!("{certificate_id}.osvb.json"),
+        content_type: "application/json".to_string(),
+        data: serde_json::to_vec_pretty(&bundle)
+            .map_err(|e| format!("Failed to serialize bundle: {e}"))?,
+    };
+    BUNDLE_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .insert(certificate_id, (content_digest, exported.clone()))
+    });
+    Ok(exported)
+}
+
+// Bundle without generated_at and bundle_signature
+fn unsigned_bundle(certificate: &Certificate, public_key: &CertificatePublicKey) -> Value {
+    let evidence = physical_art::get_session_evidence(certificate.session_id.clone());
+    let leaves: Vec<[u8; 32]> = evidence.iter().filter_map(evidence_leaf).collect();
+
+    let revocation = get_certificate_revocation(certificate.certificate_id.clone());
+    let process_log: Vec<Value> = evidence_timeline(certificate, &revocation)
+        .into_iter()
+        .map(|event| json!({ "timestamp": event.timestamp, "event": event.description }))
+        .collect();
+
+    let nft_snapshot = certificate
+        .nft_id
+        .as_ref()
+        .and_then(|token_id| token_id.parse::<u64>().ok())
+        .and_then(nft::get_token_details)
+        .map(|token| {
+            json!({
+                "token_id": token.id,
+                "token_standard": certificate.token_standard,
+                "owner": token.owner.owner.to_text(),
+                "subaccount": token.owner.subaccount.as_deref().map(to_hex),
+            })
+        });
+
+    let mut bundle = json!({
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "canister_id": ic_cdk::api::id().to_text(),
+        "issuer_did": signing::issuer_did(),
+        "certificate": certificate,
+        "signing_payload": certificate_signing_payload(certificate),
+        "public_key": {
+            "algorithm": public_key.algorithm,
+            "key_id": public_key.key_id,
+            "key_name": public_key.key_name,
+            "derivation_path": public_key.derivation_path,
+            "sec1": public_key.public_key,
+        },
+        "evidence": {
+            "photos": evidence,
+            "merkle": {
+                "algorithm": "sha256",
+                "leaves": leaves.iter().map(|leaf| to_hex(leaf)).collect::<Vec<_>>(),
+                "root": to_hex(&originstamp_verifier::merkle_root(&leaves)),
+            },
+        },
+        "process_log": process_log,
+        "revocation": revocation,
+        "nft": nft_snapshot,
+    });
+    stringify_large_integers(&mut bundle);
+    bundle
+}
+
+// Leaf and Merkle hashing are shared with the offline verifier crate. Photos
+// without a recorded hash have nothing to attest and are left out.
+fn evidence_leaf(photo: &PhotoEvidence) -> Option<[u8; 32]> {
+    let content_hash = photo.content_hash.as_deref()?;
+    Some(originstamp_verifier::evidence_leaf(
+        photo.uploaded_at,
+        content_hash,
+        &photo.photo_url,
+    ))
+}
@@ -0,0 +1,222 @@
+use super::{sanitize_string, CERTIFICATES};
+use crate::modules::physical_art;
+use crate::types::{Certificate, CertificateStatus, CertificateType, CertificateTypeDetails};
+
+// =============================================================================
+// Certificate types: type-specific validation and required evidence
+// =============================================================================
+
+// Largest edition a limited-edition certificate can belong to
+const MAX_EDITION_SIZE: u32 = 10_000;
+
+impl CertificateType {
+    pub const ALL: [CertificateType; 5] = [
+        CertificateType::Standard,
+        CertificateType::LimitedEdition,
+        CertificateType::Commission,
+        CertificateType::Restoration,
+        CertificateType::Digital,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CertificateType::Standard => "standard",
+            CertificateType::LimitedEdition => "limited_edition",
+            CertificateType::Commission => "commission",
+            CertificateType::Restoration => "restoration",
+            CertificateType::Digital => "digital",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CertificateType::Standard => "Standard",
+            CertificateType::LimitedEdition => "Limited Edition",
+            CertificateType::Commission => "Commission",
+            CertificateType::Restoration => "Restoration",
+            CertificateType::Digital => "Digital",
+        }
+    }
+
+    // Process photos required before a certificate of this type can be issued
+    fn min_photos(&self) -> usize {
+        match self {
+            // Before and after the restoration work
+            CertificateType::Restoration => 2,
+            _ => 1,
+        }
+    }
+
+    // Digital works can be hashed exactly, so every photo must carry a content hash
+    fn requires_photo_hashes(&self) -> bool {
+        *self == CertificateType::Digital
+    }
+}
+
+impl CertificateTypeDetails {
+    pub fn certificate_type(&self) -> CertificateType {
+        match self {
+            CertificateTypeDetails::Standard => CertificateType::Standard,
+            CertificateTypeDetails::LimitedEdition { .. } => CertificateType::LimitedEdition,
+            CertificateTypeDetails::Commission { .. } => CertificateType::Commission,
+            CertificateTypeDetails::Restoration { .. } => CertificateType::Restoration,
+            CertificateTypeDetails::Digital { .. } => CertificateType::Digital,
+        }
+    }
+
+    // Short human-readable summary for pages and printable documents
+    pub fn summary(&self) -> Option<String> {
+        match self {
+            CertificateTypeDetails::Standard => None,
+            CertificateTypeDetails::LimitedEdition {
+                edition_number,
+                edition_size,
+            } => Some(format!("Edition {edition_number} of {edition_size}")),
+            CertificateTypeDetails::Commission {
+                commissioned_by, ..
+            } => Some(format!("Commissioned by {commissioned_by}")),
+            CertificateTypeDetails::Restoration {
+                original_artist,
+                original_year,
+                ..
+            } => Some(match original_year {
+                Some(year) => format!("Restoration of a work by {original_artist} ({year})"),
+                None => format!("Restoration of a work by {original_artist}"),
+            }),
+            CertificateTypeDetails::Digital { master_file_hash } => {
+                Some(format!("Master file SHA-256 {}", &master_file_hash[..16]))
+            }
+        }
+    }
+}
+
+// Validate and sanitize type-specific issuance data
+pub(super) fn validate_type_details(
+    details: &CertificateTypeDetails,
+    username: &str,
+    art_title: &str,
+) -> Result<CertificateTypeDetails, String> {
+    match details {
+        CertificateTypeDetails::Standard => Ok(CertificateTypeDetails::Standard),
+        CertificateTypeDetails::LimitedEdition {
+            edition_number,
+            edition_size,
+        } => {
+            if *edition_size == 0 || *edition_size > MAX_EDITION_SIZE {
+                return Err(format!(
+                    "Invalid edition_size: must be between 1-{MAX_EDITION_SIZE}"
+                ));
+            }
+            if *edition_number == 0 || edition_number > edition_size {
+                return Err(format!(
+                    "Invalid edition_number: must be between 1-{edition_size}"
+                ));
+            }
+            check_edition_available(username, art_title, *edition_number, *edition_size)?;
+            Ok(details.clone())
+        }
+        CertificateTypeDetails::Commission {
+            commissioned_by,
+            commissioned_at,
+        } => Ok(CertificateTypeDetails::Commission {
+            commissioned_by: sanitize_string(commissioned_by, 100)?,
+            commissioned_at: *commissioned_at,
+        }),
+        CertificateTypeDetails::Restoration {
+            original_artist,
+            original_year,
+            scope,
+        } => Ok(CertificateTypeDetails::Restoration {
+            original_artist: sanitize_string(original_artist, 100)?,
+            original_year: *original_year,
+            scope: sanitize_string(scope, 500)?,
+        }),
+        CertificateTypeDetails::Digital { master_file_hash } => {
+            let master_file_hash = master_file_hash.to_ascii_lowercase();
+            if master_file_hash.len() != 64
+                || !master_file_hash.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err("Invalid master_file_hash: expected 64 hex characters".to_string());
+            }
+            Ok(CertificateTypeDetails::Digital { master_file_hash })
+        }
+    }
+}
+
+// Check the session carries the evidence the certificate type requires
+pub(super) fn check_required_evidence(
+    certificate_type: CertificateType,
+    session_id: &str,
+) -> Result<(), String> {
+    let evidence = physical_art::get_session_evidence(session_id.to_string());
+
+    let min_photos = certificate_type.min_photos();
+    if evidence.len() < min_photos {
+        return Err(format!(
+            "{} certificates require at least {min_photos} process photos",
+            certificate_type.display_name()
+        ));
+    }
+    if certificate_type.requires_photo_hashes()
+        && evidence.iter().any(|photo| photo.content_hash.is_none())
+    {
+        return Err(format!(
+            "{} certificates require a recorded content hash for every photo",
+            certificate_type.display_name()
+        ));
+    }
+    Ok(())
+}
+
+// Edition availability is checked before signing, but another session can claim
+// the same edition while the signature is awaited; check again right before storing
+pub(super) fn recheck_edition_available(certificate: &Certificate) -> Result<(), String> {
+    match certificate.type_details {
+        CertificateTypeDetails::LimitedEdition {
+            edition_number,
+            edition_size,
+        } => check_edition_available(
+            &certificate.username,
+            &certificate.art_title,
+            edition_number,
+            edition_size,
+        ),
+        _ => Ok(()),
+    }
+}
+
+// Each edition number can be certified once per work, and all editions share a size
+fn check_edition_available(
+    username: &str,
+    art_title: &str,
+    edition_number: u32,
+    edition_size: u32,
+) -> Result<(), String> {
+    CERTIFICATES.with(|certificates| {
+        for certificate in certificates.borrow().values() {
+            if certificate.username != username
+                || !certificate.art_title.eq_ignore_ascii_case(art_title)
+                || certificate.certificate_status == CertificateStatus::Revoked
+            {
+                continue;
+            }
+            if let CertificateTypeDetails::LimitedEdition {
+                edition_number: existing_number,
+                edition_size: existing_size,
+            } = certificate.type_details
+            {
+                if existing_size != edition_size {
+                    return Err(format!(
+                        "Edition size must match the existing editions of this work ({existing_size})"
+                    ));
+                }
+                if existing_number == edition_number {
+                    return Err(format!(
+                        "Edition {edition_number} of this work is already certified"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,266 @@
+use super::{build_verification_result, get_certificate_by_id};
+use crate::modules::{certification, physical_art, signing};
+use crate::types::{
+    Certificate, CertificateStatus, CertificateTypeDetails, CredentialVerificationResult,
+};
+use crate::utils::{canonical_json, format_rfc3339};
+use ic_cdk::api::time;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature, VerifyingKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// W3C Verifiable Credentials (VC Data Model 2.0)
+// =============================================================================
+//
+// The canister can only sign with threshold secp256k1, which no W3C cryptosuite
+// covers (ecdsa-jcs-2019 is P-256/P-384 only), so proofs use a custom suite,
+// "originstamp-secp256k1-jcs-2025":
+//   - hashData = SHA-256(JCS(proof config)) || SHA-256(JCS(document)), as in ecdsa-jcs-2019;
+//     timestamps are RFC 3339 strings so every JSON number stays below 2^53
+//   - proofValue = base58btc multibase of the 64-byte r || s ECDSA signature over
+//     SHA-256(hashData)
+//   - the key is a did:web Multikey (compressed secp256k1, multicodec 0xe7); the
+//     verificationMethod fragment is the signing key id
+// Generic Data Integrity verifiers will not recognise the suite; use
+// verify_certificate_vc or follow the steps above.
+
+const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+const PROOF_TYPE: &str = "DataIntegrityProof";
+const CRYPTOSUITE: &str = "originstamp-secp256k1-jcs-2025";
+const PROOF_PURPOSE: &str = "assertionMethod";
+
+thread_local! {
+    static CREDENTIAL_CACHE: RefCell<HashMap<String, ([u8; 32], String)>> = RefCell::new(HashMap::new());
+}
+
+// Export a certificate as a signed VC 2.0 JSON-LD document
+#[ic_cdk::update]
+pub async fn export_certificate_vc(certificate_id: String) -> Result<String, String> {
+    let certificate = get_certificate_by_id(certificate_id.clone())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+
+    // Each signature costs cycles, so only re-sign when the certificate changed
+    let certificate_hash: [u8; 32] =
+        Sha256::digest(certification::encode_certificate(&certificate)).into();
+    if let Some(cached) = CREDENTIAL_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .get(&certificate_id)
+            .filter(|(hash, _)| *hash == certificate_hash)
+            .map(|(_, credential)| credential.clone())
+    }) {
+        return Ok(cached);
+    }
+
+    let key = signing::active_signing_key()?;
+    let mut credential = unsigned_credential(&certificate);
+    let mut proof = json!({
+        "@context": credential["@context"].clone(),
+        "type": PROOF_TYPE,
+        "cryptosuite": CRYPTOSUITE,
+        "created": format_rfc3339(time()),
+        "verificationMethod": signing::verification_method_id(&key.key_id),
+        "proofPurpose": PROOF_PURPOSE,
+    });
+
+    let signature = signing::sign_message(&key, &hash_data(&proof, &credential)).await?;
+    proof["proofValue"] = json!(format!("z{}", bs58::encode(signature).into_string()));
+    credential["proof"] = proof;
+
+    let serialized = serde_json::to_string_pretty(&credential)
+        .map_err(|e| format!("Failed to serialize credential: {e}"))?;
+    CREDENTIAL_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .insert(certificate_id, (certificate_hash, serialized.clone()))
+    });
+    Ok(serialized)
+}
+
+// Validate a credential produced by export_certificate_vc
+#[ic_cdk::query]
+pub fn verify_certificate_vc(json: String) -> Result<CredentialVerificationResult, String> {
+    let mut credential: Value =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid JSON: {e}"))?;
+    let credential_object = credential
+        .as_object_mut()
+        .ok_or_else(|| "Credential must be a JSON object".to_string())?;
+    let mut proof = credential_object
+        .remove("proof")
+        .filter(Value::is_object)
+        .ok_or_else(|| "Credential has no proof".to_string())?;
+
+    let mut errors = Vec::new();
+
+    if credential["@context"].get(0).and_then(Value::as_str) != Some(CREDENTIALS_V2_CONTEXT) {
+        errors.push("First @context entry must be the VC 2.0 context".to_string());
+    }
+    let is_credential = credential["type"]
+        .as_array()
+        .is_some_and(|types| types.iter().any(|t| t == "VerifiableCredential"));
+    if !is_credential {
+        errors.push("Type must include VerifiableCredential".to_string());
+    }
+    if credential["issuer"]["id"].as_str() != Some(signing::issuer_did().as_str()) {
+        errors.push("Credential was not issued by this canister".to_string());
+    }
+    // Any key this canister has signed with, including retired ones
+    let key_id = proof["verificationMethod"]
+        .as_str()
+        .and_then(signing::key_id_from_verification_method)
+        .map(str::to_string)
+        .unwrap_or_default();
+    if proof["type"] != PROOF_TYPE
+        || proof["cryptosuite"] != CRYPTOSUITE
+        || proof["proofPurpose"] != PROOF_PURPOSE
+        || signing::signing_key(&key_id).is_none()
+    {
+        errors.push(
+            "Unsupported proof type, cryptosuite, purpose or verification method".to_string(),
+        );
+    }
+
+    let proof_value = proof
+        .as_object_mut()
+        .and_then(|proof| proof.remove("proofValue"))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let signature_valid =
+        match verify_proof_value(&key_id, &proof_value, &hash_data(&proof, &credential)) {
+            Ok(valid) => valid,
+            Err(error) => {
+                errors.push(error);
+                false
+            }
+        };
+    if !signature_valid && errors.is_empty() {
+        errors.push("Proof signature does not match the credential".to_string());
+    }
+
+    // The signed content must still describe the current certificate state
+    let certificate_id = credential["credentialSubject"]["certificateId"]
+        .as_str()
+        .map(str::to_string);
+    let mut status = None;
+    match certificate_id.clone().and_then(get_certificate_by_id) {
+        Some(certificate) => {
+            let verification = build_verification_result(&certificate);
+            if credential["credentialSubject"]["verificationHash"]
+                != certificate.verification_hash.as_str()
+            {
+                errors.push("Credential does not match the current certificate".to_string());
+            }
+            if verification.status != CertificateStatus::Active {
+                errors.push(format!("Certificate is {}", verification.status.label()));
+            }
+            status = Some(verification.status);
+        }
+        None => errors.push("Certificate not found".to_string()),
+    }
+
+    Ok(CredentialVerificationResult {
+        valid: signature_valid && errors.is_empty(),
+        signature_valid,
+        certificate_id,
+        status,
+        errors,
+    })
+}
+
+fn unsigned_credential(certificate: &Certificate) -> Value {
+    let evidence_photos = physical_art::get_session_details(certificate.session_id.clone())
+        .map(|session| session.uploaded_photos.len())
+        .unwrap_or_default();
+
+    json!({
+        "@context": [CREDENTIALS_V2_CONTEXT],
+        "id": certificate.verification_url,
+        "type": ["VerifiableCredential", "ArtworkAuthenticityCredential"],
+        "name": "Certificate of Authenticity",
+        "issuer": {
+            "id": signing::issuer_did(),
+            "name": certificate.issuer,
+        },
+        "validFrom": format_rfc3339(certificate.issue_date),
+        "validUntil": format_rfc3339(certificate.expiry_date),
+        "credentialSubject": {
+            "type": "Artwork",
+            "certificateId": certificate.certificate_id,
+            "certificateType": certificate.certificate_type.label(),
+            "typeDetails": credential_type_details(&certificate.type_details),
+            "version": certificate.version,
+            "supersedes": certificate.supersedes,
+            "title": certificate.art_title,
+            "description": certificate.description,
+            "creator": certificate.username,
+            "process": {
+                "creationDuration": certificate.metadata.creation_duration,
+                "totalActions": certificate.metadata.total_actions,
+                "fileFormat": certificate.metadata.file_format,
+                "fileSize": certificate.metadata.file_size,
+                "creationTools": certificate.metadata.creation_tools,
+                "evidencePhotos": evidence_photos,
+            },
+            "scores": {
+                "verificationScore": certificate.verification_score,
+                "authenticityRating": certificate.authenticity_rating,
+                "provenanceScore": certificate.provenance_score,
+                "communityTrust": certificate.community_trust,
+            },
+            "verificationHash": certificate.verification_hash,
+            "certificateSignature": certificate.signature,
+            "nft": certificate.nft_id.as_ref().map(|token_id| json!({
+                "tokenId": token_id,
+                "tokenUri": certificate.token_uri,
+                "tokenStandard": certificate.token_standard,
+            })),
+        },
+    })
+}
+
+// Nanosecond timestamps exceed 2^53, which JCS numbers cannot carry exactly,
+// so they are emitted as RFC 3339 strings like validFrom/validUntil
+fn credential_type_details(type_details: &CertificateTypeDetails) -> Value {
+    let mut value = json!(type_details);
+    if let CertificateTypeDetails::Commission {
+        commissioned_at: Some(commissioned_at),
+        ..
+    } = type_details
+    {
+        value["Commission"]["commissioned_at"] = json!(format_rfc3339(*commissioned_at));
+    }
+    value
+}
+
+// hashData: SHA-256(canonical proof config) || SHA-256(canonical document)
+fn hash_data(proof_config: &Value, credential: &Value) -> Vec<u8> {
+    let mut data = Sha256::digest(canonical_json(proof_config)).to_vec();
+    data.extend_from_slice(&Sha256::digest(canonical_json(credential)));
+    data
+}
+
+fn verify_proof_value(key_id: &str, proof_value: &str, hash_data: &[u8]) -> Result<bool, String> {
+    let encoded = proof_value
+        .strip_prefix('z')
+        .ok_or_else(|| "proofValue must be base58btc multibase".to_string())?;
+    let signature_bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|_| "proofValue is not valid base58".to_string())?;
+    let signature =
+        Signature::from_slice(&signature_bytes).map_err(|_| "Malformed signature".to_string())?;
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    let public_key = signing::public_key_bytes(key_id)
+        .ok_or_else(|| "Unknown verification method".to_string())?;
+    let verifying_key =
+        VerifyingKey::from_sec1_bytes(&public_key).map_err(|_| "Invalid issuer key".to_string())?;
+
+    // sign_message signs SHA-256(message), so verify against the same prehash
+    Ok(verifying_key
+        .verify_prehash(&Sha256::digest(hash_data), &signature)
+        .is_ok())
+}
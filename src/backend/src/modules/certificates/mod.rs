@@ -1,12 +1,43 @@
 use crate::types::{
-    Certificate, CertificateMetadata, CreateCertificateRequest, NFTGenerationResult,
+    BatchVerificationEntry, Certificate, CertificateMetadata, CertificateNftLink,
+    CertificateStatus, CertificateType, CertificateTypeDetails, CertifiedCertificate,
+    CreateCertificateRequest, NFTGenerationResult, RevocationEntry, RevocationReason,
+    ScoreBreakdown, VerificationResult,
 };
+use crate::utils::to_hex;
 use candid::{CandidType, Deserialize};
 use ic_cdk::api::{caller, time};
 use sha2::{Digest, Sha256};
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+mod analytics;
+mod bundle;
+mod certificate_type;
+mod credentials;
+mod pages;
+mod printable;
+mod provenance;
+mod qr;
+mod rendering;
+mod search;
+
+pub(crate) use analytics::verification_volume;
+pub use analytics::{
+    get_certificate_verification_events, get_certificate_verification_stats,
+    record_certificate_verification,
+};
+pub use bundle::export_verification_bundle;
+pub use credentials::{export_certificate_vc, verify_certificate_vc};
+pub use printable::{
+    get_certificate_document, get_certificate_template, list_certificate_templates,
+    set_certificate_template,
+};
+pub use provenance::export_certificate_provenance;
+pub use qr::get_certificate_qr;
+pub use rendering::render_pending_certificate_assets;
+pub use search::search_certificates;
+
 // Role-based access control
 #[derive(CandidType, Deserialize, Clone, PartialEq, Eq)]
 pub enum UserRole {
@@ -51,6 +82,78 @@ pub fn initialize_admin() {
     });
 }
 
+// Assign a role to a user (admin only)
+#[ic_cdk::update]
+pub fn set_user_role(username: String, role: UserRole) -> Result<bool, String> {
+    if caller_role() != Some(UserRole::Admin) {
+        return Err("Only admins can assign user roles".to_string());
+    }
+
+    if username.is_empty() || username.len() > 50 {
+        return Err("Invalid username: must be between 1-50 characters".to_string());
+    }
+
+    let is_staff = matches!(role, UserRole::Moderator | UserRole::Admin);
+    USER_PERMISSIONS.with(|permissions| {
+        permissions.borrow_mut().insert(
+            username.clone(),
+            UserPermissions {
+                username,
+                can_create_certificates: true,
+                can_generate_nfts: role != UserRole::User,
+                can_modify_certificates: is_staff,
+                can_delete_certificates: role == UserRole::Admin,
+                role,
+            },
+        );
+    });
+
+    Ok(true)
+}
+
+pub(crate) fn require_admin() -> Result<(), String> {
+    if caller_role() == Some(UserRole::Admin) {
+        Ok(())
+    } else {
+        Err("Only admins can perform this action".to_string())
+    }
+}
+
+// Role of the calling principal; controllers are always treated as admins
+fn caller_role() -> Option<UserRole> {
+    if ic_cdk::api::is_controller(&caller()) {
+        return Some(UserRole::Admin);
+    }
+
+    let username = crate::modules::users::caller_username()?;
+    USER_PERMISSIONS.with(|permissions| {
+        permissions
+            .borrow()
+            .get(&username)
+            .map(|user_perm| user_perm.role.clone())
+    })
+}
+
+// Certificate owners, moderators and admins may manage a certificate.
+// Returns the identity to record as the acting party.
+fn authorize_certificate_management(certificate: &Certificate) -> Result<String, String> {
+    if let Some(username) = crate::modules::users::caller_username() {
+        if username == certificate.username {
+            return Ok(username);
+        }
+    }
+
+    match caller_role() {
+        Some(UserRole::Moderator) | Some(UserRole::Admin) => {
+            Ok(crate::modules::users::caller_username().unwrap_or_else(|| caller().to_string()))
+        }
+        _ => Err(
+            "Only the certificate owner, moderators or admins can manage this certificate"
+                .to_string(),
+        ),
+    }
+}
+
 // Authentication and authorization functions
 fn authenticate_user() -> Result<String, String> {
     let caller_principal = caller();
@@ -154,6 +257,125 @@ fn authorize_certificate_creation(username: &str) -> Result<(), String> {
 
 thread_local! {
     static CERTIFICATES: RefCell<HashMap<String, Certificate>> = RefCell::new(HashMap::new());
+    static REVOCATIONS: RefCell<Vec<RevocationEntry>> = const { RefCell::new(Vec::new()) };
+    // Issuer name recorded on new certificates; None uses DEFAULT_ISSUER
+    static ISSUER_NAME: RefCell<Option<String>> = const { RefCell::new(None) };
+    // Secondary indexes for lookups by verification hash and NFT token id
+    static CERTIFICATE_IDS_BY_HASH: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    static CERTIFICATE_IDS_BY_TOKEN: RefCell<HashMap<u64, String>> = RefCell::new(HashMap::new());
+    // Every version issued from a session, to re-render pages when its evidence changes
+    static CERTIFICATE_IDS_BY_SESSION: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+// All certificate writes go through these helpers so the certified tree stays in sync
+fn insert_certificate(certificate: Certificate) {
+    crate::modules::certification::certify_certificate(&certificate);
+    publish_certificate_assets(&certificate);
+    index_certificate(&certificate);
+    CERTIFICATE_IDS_BY_SESSION.with(|index| {
+        index
+            .borrow_mut()
+            .entry(certificate.session_id.clone())
+            .or_default()
+            .push(certificate.certificate_id.clone())
+    });
+    CERTIFICATES.with(|certificates| {
+        certificates
+            .borrow_mut()
+            .insert(certificate.certificate_id.clone(), certificate);
+    });
+}
+
+fn modify_certificate<R>(
+    certificate_id: &str,
+    update: impl FnOnce(&mut Certificate) -> R,
+) -> Option<R> {
+    let (result, previous, certificate) = CERTIFICATES.with(|certificates| {
+        let mut certificates = certificates.borrow_mut();
+        let certificate = certificates.get_mut(certificate_id)?;
+        let previous = certificate.clone();
+        let result = update(certificate);
+        crate::modules::certification::certify_certificate(certificate);
+        Some((result, previous, certificate.clone()))
+    })?;
+    unindex_lookup_keys(&previous);
+    index_certificate(&certificate);
+    publish_certificate_assets(&certificate);
+    Some(result)
+}
+
+fn index_certificate(certificate: &Certificate) {
+    search::index_certificate(certificate);
+    CERTIFICATE_IDS_BY_HASH.with(|index| {
+        index.borrow_mut().insert(
+            normalize_verification_hash(&certificate.verification_hash),
+            certificate.certificate_id.clone(),
+        )
+    });
+    // A token belongs to the latest version of its certificate only
+    if let Some(token_id) = certificate
+        .nft_id
+        .as_ref()
+        .filter(|_| certificate.superseded_by.is_none())
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        CERTIFICATE_IDS_BY_TOKEN.with(|index| {
+            index
+                .borrow_mut()
+                .insert(token_id, certificate.certificate_id.clone())
+        });
+    }
+}
+
+// Drop the hash and token lookups of a certificate's previous state; index_certificate
+// re-adds the ones that still apply
+fn unindex_lookup_keys(previous: &Certificate) {
+    let certificate_id = &previous.certificate_id;
+    CERTIFICATE_IDS_BY_HASH.with(|index| {
+        let mut index = index.borrow_mut();
+        let hash = normalize_verification_hash(&previous.verification_hash);
+        if index.get(&hash) == Some(certificate_id) {
+            index.remove(&hash);
+        }
+    });
+    if let Some(token_id) = previous
+        .nft_id
+        .as_ref()
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        CERTIFICATE_IDS_BY_TOKEN.with(|index| {
+            let mut index = index.borrow_mut();
+            if index.get(&token_id) == Some(certificate_id) {
+                index.remove(&token_id);
+            }
+        });
+    }
+}
+
+// Hashes are stored as "0x" + lowercase hex; accept either case and a missing prefix
+fn normalize_verification_hash(hash: &str) -> String {
+    let hash = hash.trim();
+    let hex = hash
+        .strip_prefix("0x")
+        .or_else(|| hash.strip_prefix("0X"))
+        .unwrap_or(hash);
+    format!("0x{}", hex.to_ascii_lowercase())
+}
+
+// Queue the verification pages of a session's certificates, whose evidence
+// sections list the session photos, for re-rendering
+pub fn republish_session_pages(session_id: &str) {
+    let certificate_ids = CERTIFICATE_IDS_BY_SESSION
+        .with(|index| index.borrow().get(session_id).cloned())
+        .unwrap_or_default();
+    rendering::queue_certificate_renders(certificate_ids);
+}
+
+// Withdraw a stale provenance manifest now and queue the pages, printable
+// documents and QR code derived from a certificate for rendering
+fn publish_certificate_assets(certificate: &Certificate) {
+    provenance::unpublish_stale_manifest(certificate);
+    rendering::queue_certificate_renders([certificate.certificate_id.clone()]);
 }
 
 // Reentrancy protection and rate limiting
@@ -258,6 +480,15 @@ fn validate_and_sanitize_input(
         .map(|tool| sanitize_string(tool, 50))
         .collect::<Result<Vec<String>, String>>()?;
 
+    let type_details = certificate_type::validate_type_details(
+        request
+            .type_details
+            .as_ref()
+            .unwrap_or(&CertificateTypeDetails::Standard),
+        &request.username,
+        &sanitized_art_title,
+    )?;
+
     Ok(CreateCertificateRequest {
         session_id: request.session_id.clone(),
         username: request.username.clone(),
@@ -268,6 +499,7 @@ fn validate_and_sanitize_input(
         file_format: request.file_format.clone(),
         creation_tools: sanitized_tools,
         file_sizes: request.file_sizes.clone(),
+        type_details: Some(type_details),
     })
 }
 
@@ -399,9 +631,119 @@ impl SubscriptionTier {
 // Blockchain transaction details shown
 // Verification status indicators active
 
+const DEFAULT_ISSUER: &str = "OriginStamp";
+
+// Issuer name recorded on newly issued certificates
+#[ic_cdk::query]
+pub fn get_certificate_issuer() -> String {
+    ISSUER_NAME
+        .with(|issuer| issuer.borrow().clone())
+        .unwrap_or_else(|| DEFAULT_ISSUER.to_string())
+}
+
+// Configure the issuer name for this deployment (admin only). Issued certificates
+// keep the issuer they were signed with.
+#[ic_cdk::update]
+pub fn set_certificate_issuer(issuer: String) -> Result<bool, String> {
+    require_admin()?;
+    let issuer = sanitize_string(issuer.trim(), 100)?;
+    ISSUER_NAME.with(|name| *name.borrow_mut() = Some(issuer));
+    Ok(true)
+}
+
+// Certificate validity window
+const DEFAULT_VALIDITY_YEARS: u64 = 10;
+const MAX_VALIDITY_YEARS: u32 = 25;
+// Upper bound on verify_certificates so a batch fits in a single query's instruction limit
+const MAX_VERIFICATION_BATCH: usize = 200;
+const NANOS_PER_YEAR: u64 = 365 * 24 * 60 * 60 * 1_000_000_000;
+
+fn new_certificate_id(session_id: &str) -> String {
+    format!(
+        "CERT-{}-{}",
+        session_id.to_uppercase(),
+        generate_secure_random_id()
+    )
+}
+
+// Canonical bytes covered by a certificate's signature: the issuance data only,
+// so later status changes (revocation, NFT linking) keep the signature valid.
+// Serialized as compact JSON with fields in declaration order.
+#[derive(serde::Serialize)]
+struct CertificateSigningPayload<'a> {
+    certificate_id: &'a str,
+    session_id: &'a str,
+    username: &'a str,
+    art_title: &'a str,
+    description: &'a str,
+    issue_date: u64,
+    expiry_date: u64,
+    certificate_type: &'a CertificateType,
+    type_details: &'a CertificateTypeDetails,
+    verification_score: u32,
+    authenticity_rating: u32,
+    provenance_score: u32,
+    community_trust: u32,
+    issuer: &'a str,
+    version: u32,
+    supersedes: Option<&'a str>,
+}
+
+pub fn certificate_signing_payload(certificate: &Certificate) -> String {
+    let payload = CertificateSigningPayload {
+        certificate_id: &certificate.certificate_id,
+        session_id: &certificate.session_id,
+        username: &certificate.username,
+        art_title: &certificate.art_title,
+        description: &certificate.description,
+        issue_date: certificate.issue_date,
+        expiry_date: certificate.expiry_date,
+        certificate_type: &certificate.certificate_type,
+        type_details: &certificate.type_details,
+        verification_score: certificate.verification_score,
+        authenticity_rating: certificate.authenticity_rating,
+        provenance_score: certificate.provenance_score,
+        community_trust: certificate.community_trust,
+        issuer: &certificate.issuer,
+        version: certificate.version,
+        supersedes: certificate.supersedes.as_deref(),
+    };
+    serde_json::to_string(&payload).unwrap_or_default()
+}
+
+// Hash and sign the certificate's canonical payload with the canister's threshold key
+async fn seal_certificate(certificate: &mut Certificate) -> Result<(), String> {
+    let payload = certificate_signing_payload(certificate);
+    let key = crate::modules::signing::active_signing_key()?;
+    let signature = crate::modules::signing::sign_message(&key, payload.as_bytes()).await?;
+
+    certificate.verification_hash = format!("0x{}", to_hex(&Sha256::digest(payload.as_bytes())));
+
+    let mut tx_hasher = Sha256::new();
+    tx_hasher.update(payload.as_bytes());
+    tx_hasher.update(&signature);
+    certificate.blockchain_tx = format!("0x{}", to_hex(&tx_hasher.finalize()));
+    certificate.signature = to_hex(&signature);
+    certificate.signing_key_id = key.key_id;
+
+    Ok(())
+}
+
+// (verification_score, authenticity_rating, provenance_score, community_trust)
+fn evidence_scores(photo_count: u32) -> (u32, u32, u32, u32) {
+    (
+        85u32.saturating_add((photo_count.saturating_mul(2)).min(15)),
+        90u32.saturating_add(photo_count.min(10)),
+        88u32.saturating_add(photo_count.min(12)),
+        82u32.saturating_add(photo_count.min(18)),
+    )
+}
+
 // Certificate generation
 #[ic_cdk::update]
-pub fn generate_certificate(request: CreateCertificateRequest) -> Result<Certificate, String> {
+pub async fn generate_certificate(
+    request: CreateCertificateRequest,
+) -> Result<Certificate, String> {
     // 1. Authentication
     authenticate_user()?;
 
@@ -497,50 +839,30 @@ pub fn generate_certificate(request: CreateCertificateRequest) -> Result<Certifi
         return Err("Photo count mismatch with uploaded photos".to_string());
     }
 
-    // 6. Generate certificate ID with
-    let certificate_id = format!(
-        "CERT-{}-{}",
-        sanitized_request.session_id.to_uppercase(),
-        generate_secure_random_id()
-    );
+    // Type-specific evidence (e.g. before/after photos for restorations)
+    let type_details = sanitized_request
+        .type_details
+        .clone()
+        .unwrap_or(CertificateTypeDetails::Standard);
+    if let Err(e) = certificate_type::check_required_evidence(
+        type_details.certificate_type(),
+        &sanitized_request.session_id,
+    ) {
+        release_reentrancy_certificate(&request.session_id);
+        return Err(e);
+    }
 
-    // 7. Generate verification hash with additional entropy
-    let caller_principal = caller();
-    let verification_data = format!(
-        "{}{}{}{}{}",
-        sanitized_request.session_id,
-        sanitized_request.username,
-        sanitized_request.art_title,
-        caller_principal,
-        time()
-    );
-    let mut hasher = Sha256::new();
-    hasher.update(verification_data.as_bytes());
-    let verification_hash = format!("0x{:x}", hasher.finalize());
-
-    // 8. Generate blockchain transaction hash
-    let tx_data = format!(
-        "{}{}{}{}",
-        certificate_id,
-        verification_hash,
-        caller_principal,
-        time()
-    );
-    let mut tx_hasher = Sha256::new();
-    tx_hasher.update(tx_data.as_bytes());
-    let blockchain_tx = format!("0x{:x}", tx_hasher.finalize());
+    // 6. Generate certificate ID with
+    let certificate_id = new_certificate_id(&sanitized_request.session_id);
 
     // 9. Calculate scores with bounds checking
-    let photo_count = session.uploaded_photos.len() as u32;
-    let verification_score = 85u32.saturating_add((photo_count.saturating_mul(2)).min(15));
-    let authenticity_rating = 90u32.saturating_add(photo_count.min(10));
-    let provenance_score = 88u32.saturating_add(photo_count.min(12));
-    let community_trust = 82u32.saturating_add(photo_count.min(18));
+    let (verification_score, authenticity_rating, provenance_score, community_trust) =
+        evidence_scores(session.uploaded_photos.len() as u32);
 
     let current_time = time();
-    let expiry_date = current_time.saturating_add(10 * 365 * 24 * 60 * 60 * 1_000_000_000); // 10 years
+    let expiry_date = current_time.saturating_add(DEFAULT_VALIDITY_YEARS * NANOS_PER_YEAR);
 
-    let certificate = Certificate {
+    let mut certificate = Certificate {
         certificate_id: certificate_id.clone(),
         session_id: sanitized_request.session_id,
         username: sanitized_request.username,
@@ -548,19 +870,23 @@ pub fn generate_certificate(request: CreateCertificateRequest) -> Result<Certifi
         description: sanitized_request.description,
         issue_date: current_time,
         expiry_date,
-        verification_hash,
-        blockchain_tx,
-        qr_code_data: format!("https://originstamp.ic0.app/verify/{certificate_id}"),
+        // Hashes and signature are filled in by seal_certificate
+        verification_hash: String::new(),
+        blockchain_tx: String::new(),
+        signature: String::new(),
+        signing_key_id: String::new(),
+        qr_code_data: format!("https://originstamp.ic0.app/verify/{certificate_id}?source=qr"),
         verification_url: format!("https://originstamp.ic0.app/verify/{certificate_id}"),
-        certificate_type: "standard".to_string(),
+        certificate_type: type_details.certificate_type(),
+        type_details,
         verification_score,
         authenticity_rating,
         provenance_score,
         community_trust,
-        certificate_status: "active".to_string(),
-        issuer: "OriginStamp".to_string(),
-        blockchain: "Internet Computer".to_string(),
-        token_standard: "ICP-721".to_string(),
+        certificate_status: CertificateStatus::Active,
+        issuer: get_certificate_issuer(),
+        blockchain: crate::modules::nft::BLOCKCHAIN.to_string(),
+        token_standard: crate::modules::nft::TOKEN_STANDARD.to_string(),
         metadata: CertificateMetadata {
             creation_duration: format!(
                 "{} hours {} minutes",
@@ -576,21 +902,56 @@ pub fn generate_certificate(request: CreateCertificateRequest) -> Result<Certifi
         nft_generated: false,
         nft_id: None,
         token_uri: None,
+        version: 1,
+        supersedes: None,
+        superseded_by: None,
     };
 
-    // 10. Store certificate with access control
-    CERTIFICATES.with(|certificates| {
-        certificates
-            .borrow_mut()
-            .insert(certificate_id.clone(), certificate.clone());
-    });
+    // 10. Sign certificate with the canister's threshold key
+    if let Err(e) = seal_certificate(&mut certificate).await {
+        release_reentrancy_certificate(&request.session_id);
+        return Err(e);
+    }
 
-    // 11. Release reentrancy protection
+    // 11. Store certificate with access control, unless its edition was claimed while signing
+    if let Err(e) = certificate_type::recheck_edition_available(&certificate) {
+        release_reentrancy_certificate(&request.session_id);
+        return Err(e);
+    }
+    insert_certificate(certificate.clone());
+
+    // 12. Release reentrancy protection
     release_reentrancy_certificate(&request.session_id);
 
     Ok(certificate)
 }
 
+// Get the exact bytes (as UTF-8 JSON) covered by a certificate's signature
+#[ic_cdk::query]
+pub fn get_certificate_signing_payload(certificate_id: String) -> Option<String> {
+    get_certificate_by_id(certificate_id)
+        .map(|certificate| certificate_signing_payload(&certificate))
+}
+
+// Get a certificate together with a certificate-tree witness and the IC data certificate,
+// so clients can verify the response against the IC root key
+#[ic_cdk::query]
+pub fn get_certificate_certified(certificate_id: String) -> Result<CertifiedCertificate, String> {
+    let data_certificate = ic_cdk::api::data_certificate()
+        .ok_or_else(|| "Certified responses are only available from query calls".to_string())?;
+
+    let certificate = get_certificate_by_id(certificate_id.clone());
+    Ok(CertifiedCertificate {
+        certificate_bytes: certificate
+            .as_ref()
+            .map(crate::modules::certification::encode_certificate)
+            .unwrap_or_default(),
+        certificate,
+        data_certificate,
+        witness: crate::modules::certification::certificate_witness(&certificate_id),
+    })
+}
+
 // Get certificate by ID
 #[ic_cdk::query]
 pub fn get_certificate_by_id(certificate_id: String) -> Option<Certificate> {
@@ -610,53 +971,388 @@ pub fn get_user_certificates(username: String) -> Vec<Certificate> {
 }
 
 // Verify certificate
-#[ic_cdk::update]
-pub fn verify_certificate(
-    certificate_id: String,
-) -> Result<crate::types::VerificationResult, String> {
-    let certificate =
-        CERTIFICATES.with(|certificates| certificates.borrow().get(&certificate_id).cloned());
+#[ic_cdk::query]
+pub fn verify_certificate(certificate_id: String) -> Result<VerificationResult, String> {
+    get_certificate_by_id(certificate_id)
+        .map(|certificate| build_verification_result(&certificate))
+        .ok_or_else(|| "Certificate not found".to_string())
+}
 
-    if certificate.is_none() {
-        return Err("Certificate not found".to_string());
+// Verify a batch of certificates in one call, one entry per requested ID in request order
+#[ic_cdk::query]
+pub fn verify_certificates(
+    certificate_ids: Vec<String>,
+) -> Result<Vec<BatchVerificationEntry>, String> {
+    if certificate_ids.len() > MAX_VERIFICATION_BATCH {
+        return Err(format!(
+            "Batch too large: at most {MAX_VERIFICATION_BATCH} certificates per call"
+        ));
     }
 
-    let certificate = certificate.unwrap();
+    Ok(certificate_ids
+        .into_iter()
+        .map(|certificate_id| BatchVerificationEntry {
+            result: verify_certificate(certificate_id.clone()),
+            certificate_id,
+        })
+        .collect())
+}
 
-    // Check if certificate is expired
-    if ic_cdk::api::time() > certificate.expiry_date {
-        return Ok(crate::types::VerificationResult {
-            valid: false,
-            score: 0,
-            details: format!(
-                "{{\"error\": \"Certificate expired\", \"expiry_date\": {}, \"current_time\": {}}}",
-                certificate.expiry_date,
-                ic_cdk::api::time()
-            ),
+// Verify a certificate by its verification hash (with or without the 0x prefix)
+#[ic_cdk::query]
+pub fn verify_certificate_by_hash(verification_hash: String) -> Result<VerificationResult, String> {
+    let certificate_id = CERTIFICATE_IDS_BY_HASH
+        .with(|index| {
+            index
+                .borrow()
+                .get(&normalize_verification_hash(&verification_hash))
+                .cloned()
+        })
+        .ok_or_else(|| "Certificate not found".to_string())?;
+    verify_certificate(certificate_id)
+}
+
+// Verify the certificate linked to an NFT
+#[ic_cdk::query]
+pub fn verify_certificate_by_token(token_id: u64) -> Result<VerificationResult, String> {
+    let certificate_id = CERTIFICATE_IDS_BY_TOKEN
+        .with(|index| index.borrow().get(&token_id).cloned())
+        .ok_or_else(|| "No certificate is linked to this token".to_string())?;
+    verify_certificate(certificate_id)
+}
+
+// Structured verification outcome for a certificate at the current time
+pub fn build_verification_result(certificate: &Certificate) -> VerificationResult {
+    let checked_at = time();
+
+    let status = if certificate.certificate_status == CertificateStatus::Active
+        && checked_at > certificate.expiry_date
+    {
+        CertificateStatus::Expired
+    } else {
+        certificate.certificate_status.clone()
+    };
+
+    let revocation = if status == CertificateStatus::Revoked {
+        get_certificate_revocation(certificate.certificate_id.clone())
+    } else {
+        None
+    };
+
+    let nft = certificate
+        .nft_id
+        .as_ref()
+        .map(|token_id| CertificateNftLink {
+            token_id: token_id.clone(),
+            token_uri: certificate.token_uri.clone(),
+            owner: token_id
+                .parse::<u64>()
+                .ok()
+                .and_then(crate::modules::nft::get_token_details)
+                .map(|token| token.owner),
         });
+
+    VerificationResult {
+        certificate_id: certificate.certificate_id.clone(),
+        valid: status == CertificateStatus::Active,
+        status,
+        issue_date: certificate.issue_date,
+        expiry_date: certificate.expiry_date,
+        issuer: certificate.issuer.clone(),
+        verification_hash: certificate.verification_hash.clone(),
+        blockchain_tx: certificate.blockchain_tx.clone(),
+        signature: certificate.signature.clone(),
+        signing_key_id: certificate.signing_key_id.clone(),
+        scores: ScoreBreakdown {
+            verification_score: certificate.verification_score,
+            authenticity_rating: certificate.authenticity_rating,
+            provenance_score: certificate.provenance_score,
+            community_trust: certificate.community_trust,
+        },
+        nft,
+        revocation,
+        superseded_by: certificate.superseded_by.clone(),
+        checked_at,
     }
+}
 
-    // Verify certificate is active
-    if certificate.certificate_status != "active" {
-        return Ok(crate::types::VerificationResult {
-            valid: false,
-            score: 0,
-            details: format!(
-                "{{\"error\": \"Certificate is not active\", \"status\": \"{}\"}}",
-                certificate.certificate_status
-            ),
+// Start the next version of a certificate; callers adjust evidence and expiry, then seal it.
+// The NFT link is carried over here and cleared on the predecessor when the version is stored.
+fn next_certificate_version(previous: &Certificate) -> Certificate {
+    let certificate_id = new_certificate_id(&previous.session_id);
+    let current_time = time();
+
+    Certificate {
+        certificate_id: certificate_id.clone(),
+        issue_date: current_time,
+        expiry_date: current_time.saturating_add(DEFAULT_VALIDITY_YEARS * NANOS_PER_YEAR),
+        verification_hash: String::new(),
+        blockchain_tx: String::new(),
+        signature: String::new(),
+        signing_key_id: String::new(),
+        qr_code_data: format!("https://originstamp.ic0.app/verify/{certificate_id}?source=qr"),
+        verification_url: format!("https://originstamp.ic0.app/verify/{certificate_id}"),
+        certificate_status: CertificateStatus::Active,
+        version: previous.version.saturating_add(1),
+        supersedes: Some(previous.certificate_id.clone()),
+        superseded_by: None,
+        ..previous.clone()
+    }
+}
+
+// Only current, non-revoked certificates can be renewed or reissued
+fn load_certificate_for_new_version(certificate_id: &str) -> Result<Certificate, String> {
+    let certificate = get_certificate_by_id(certificate_id.to_string())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+
+    if let Some(next_id) = &certificate.superseded_by {
+        return Err(format!("Certificate has been superseded by {next_id}"));
+    }
+    match certificate.certificate_status {
+        CertificateStatus::Active | CertificateStatus::Expired => {}
+        _ => {
+            return Err(format!(
+                "Certificate is {} and cannot be renewed or reissued",
+                certificate.certificate_status.label()
+            ))
+        }
+    }
+
+    authorize_certificate_management(&certificate)?;
+    Ok(certificate)
+}
+
+// Store a new certificate version and mark its predecessor as superseded.
+// Sealing awaits the signature, so the predecessor is checked again here: a
+// revocation or another new version that landed meanwhile aborts this one.
+fn store_certificate_version(certificate: Certificate) -> Result<(), String> {
+    if let Some(previous_id) = &certificate.supersedes {
+        load_certificate_for_new_version(previous_id)?;
+        modify_certificate(previous_id, |previous| {
+            previous.certificate_status = CertificateStatus::Superseded;
+            previous.superseded_by = Some(certificate.certificate_id.clone());
+            previous.nft_generated = false;
+            previous.nft_id = None;
+            previous.token_uri = None;
         });
     }
+    insert_certificate(certificate.clone());
+
+    // The NFT moves to the latest certificate version
+    if let Some(token_id) = certificate
+        .nft_id
+        .as_ref()
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        crate::modules::nft::relink_certificate_token(token_id, &certificate.certificate_id);
+    }
+    Ok(())
+}
+
+// Renew a certificate with a fresh validity window
+#[ic_cdk::update]
+pub async fn renew_certificate(
+    certificate_id: String,
+    validity_years: Option<u32>,
+) -> Result<Certificate, String> {
+    authenticate_user()?;
+
+    let validity_years = validity_years.unwrap_or(DEFAULT_VALIDITY_YEARS as u32);
+    if validity_years == 0 || validity_years > MAX_VALIDITY_YEARS {
+        return Err(format!(
+            "Invalid validity_years: must be between 1-{MAX_VALIDITY_YEARS}"
+        ));
+    }
+
+    let previous = load_certificate_for_new_version(&certificate_id)?;
+
+    let mut certificate = next_certificate_version(&previous);
+    certificate.expiry_date = certificate
+        .issue_date
+        .saturating_add(validity_years as u64 * NANOS_PER_YEAR);
+    seal_certificate(&mut certificate).await?;
+
+    store_certificate_version(certificate.clone())?;
+    Ok(certificate)
+}
+
+// Reissue a certificate from the current state of its session (e.g. after adding evidence)
+#[ic_cdk::update]
+pub async fn reissue_certificate(certificate_id: String) -> Result<Certificate, String> {
+    authenticate_user()?;
+
+    let previous = load_certificate_for_new_version(&certificate_id)?;
+
+    let session = crate::modules::physical_art::get_session_details(previous.session_id.clone())
+        .ok_or_else(|| "Session not found".to_string())?;
 
-    // Return verification result
-    Ok(crate::types::VerificationResult {
-        valid: true,
-        score: certificate.verification_score,
-        details: format!("{{\"verified\": true, \"timestamp\": {}, \"blockchain\": \"{}\", \"certificate_id\": \"{}\", \"verification_hash\": \"{}\", \"blockchain_tx\": \"{}\"}}", 
-            ic_cdk::api::time(), certificate.blockchain, certificate.certificate_id, certificate.verification_hash, certificate.blockchain_tx),
+    let photo_count = session.uploaded_photos.len() as u32;
+    if photo_count == 0 {
+        return Err("Session has no uploaded photos".to_string());
+    }
+
+    let subscription_limits = get_user_subscription(previous.username.clone())
+        .unwrap_or(SubscriptionTier::Free)
+        .get_limits();
+    if photo_count > subscription_limits.max_photos {
+        return Err(format!(
+            "Photo count {} exceeds subscription limit {}",
+            photo_count, subscription_limits.max_photos
+        ));
+    }
+
+    // The new evidence must still satisfy the certificate type, as at issuance
+    certificate_type::check_required_evidence(previous.certificate_type, &previous.session_id)?;
+
+    let mut certificate = next_certificate_version(&previous);
+    let (verification_score, authenticity_rating, provenance_score, community_trust) =
+        evidence_scores(photo_count);
+    certificate.verification_score = verification_score;
+    certificate.authenticity_rating = authenticity_rating;
+    certificate.provenance_score = provenance_score;
+    certificate.community_trust = community_trust;
+    certificate.metadata.total_actions = photo_count;
+    seal_certificate(&mut certificate).await?;
+
+    store_certificate_version(certificate.clone())?;
+    Ok(certificate)
+}
+
+// Get the full version lineage of a certificate, oldest first
+#[ic_cdk::query]
+pub fn get_certificate_history(certificate_id: String) -> Vec<Certificate> {
+    CERTIFICATES.with(|certificates| {
+        let certificates = certificates.borrow();
+
+        let mut root = match certificates.get(&certificate_id) {
+            Some(certificate) => certificate,
+            None => return Vec::new(),
+        };
+        while let Some(previous) = root
+            .supersedes
+            .as_ref()
+            .and_then(|previous_id| certificates.get(previous_id))
+        {
+            root = previous;
+        }
+
+        let mut history = vec![root.clone()];
+        let mut current = root;
+        while let Some(next) = current
+            .superseded_by
+            .as_ref()
+            .and_then(|next_id| certificates.get(next_id))
+        {
+            history.push(next.clone());
+            current = next;
+        }
+
+        history
     })
 }
 
+// Revoke a certificate (owner, moderator or admin)
+#[ic_cdk::update]
+pub fn revoke_certificate(
+    certificate_id: String,
+    reason: RevocationReason,
+) -> Result<RevocationEntry, String> {
+    authenticate_user()?;
+
+    if let RevocationReason::Other(details) = &reason {
+        sanitize_string(details, 500)?;
+    }
+
+    let certificate = get_certificate_by_id(certificate_id.clone())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+
+    if certificate.certificate_status == CertificateStatus::Revoked {
+        return Err("Certificate is already revoked".to_string());
+    }
+    // Only the current version is revocable; earlier versions already point to it
+    if let Some(next_id) = &certificate.superseded_by {
+        return Err(format!(
+            "Certificate has been superseded by {next_id}; revoke the current version"
+        ));
+    }
+
+    let revoked_by = authorize_certificate_management(&certificate)?;
+
+    let entry = RevocationEntry {
+        certificate_id: certificate_id.clone(),
+        reason,
+        revoked_by,
+        revoked_at: time(),
+    };
+
+    // Record the entry first so the re-rendered verification page includes it
+    REVOCATIONS.with(|revocations| revocations.borrow_mut().push(entry.clone()));
+
+    modify_certificate(&certificate_id, |cert| {
+        cert.certificate_status = CertificateStatus::Revoked;
+    });
+
+    // Reflect the revocation in the linked NFT metadata
+    if let Some(token_id) = certificate.nft_id.and_then(|id| id.parse::<u64>().ok()) {
+        crate::modules::nft::update_certificate_token_status(
+            token_id,
+            CertificateStatus::Revoked,
+            Some(entry.reason.label()),
+        );
+    }
+
+    Ok(entry)
+}
+
+// Get revocation list, optionally only entries revoked after a timestamp
+#[ic_cdk::query]
+pub fn get_revocation_list(since: Option<u64>) -> Vec<RevocationEntry> {
+    REVOCATIONS.with(|revocations| {
+        revocations
+            .borrow()
+            .iter()
+            .filter(|entry| since.is_none_or(|since| entry.revoked_at > since))
+            .cloned()
+            .collect()
+    })
+}
+
+// Get revocation details for a certificate
+#[ic_cdk::query]
+pub fn get_certificate_revocation(certificate_id: String) -> Option<RevocationEntry> {
+    REVOCATIONS.with(|revocations| {
+        revocations
+            .borrow()
+            .iter()
+            .find(|entry| entry.certificate_id == certificate_id)
+            .cloned()
+    })
+}
+
+impl CertificateStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CertificateStatus::Active => "active",
+            CertificateStatus::Suspended => "suspended",
+            CertificateStatus::Revoked => "revoked",
+            CertificateStatus::Expired => "expired",
+            CertificateStatus::Superseded => "superseded",
+        }
+    }
+}
+
+impl RevocationReason {
+    pub fn label(&self) -> String {
+        match self {
+            RevocationReason::Fraudulent => "fraudulent".to_string(),
+            RevocationReason::IssuedInError => "issued_in_error".to_string(),
+            RevocationReason::DisputedAuthorship => "disputed_authorship".to_string(),
+            RevocationReason::OwnerRequest => "owner_request".to_string(),
+            RevocationReason::Other(details) => details.clone(),
+        }
+    }
+}
+
 // NFT Generation moved to NFT Module
 // This function is replaced by NFT Module integration
 // Certificate only provides metadata, NFT Module handles minting
@@ -697,32 +1393,43 @@ pub fn get_certificate_for_nft_minting(_certificate_id: String) -> Option<Certif
     get_certificate_by_id(_certificate_id)
 }
 
-// New function: Update certificate after NFT minting
-#[ic_cdk::update]
-pub fn update_certificate_nft_info(
-    _certificate_id: String,
-    nft_id: String,
+// Link a freshly minted token to its certificate. Crate-internal: only the NFT
+// module's mint path may set the NFT fields, which minting and burning trust.
+pub(crate) fn link_certificate_nft(
+    certificate_id: &str,
+    token_id: u64,
     token_uri: String,
-) -> Result<bool, String> {
-    // This function updates certificate after NFT is minted
-    // Called by NFT Module after successful minting
-    // Links certificate with generated NFT
-
-    authenticate_user()?;
-
-    // Authorization check implemented
-    // Caller permission verified
+) -> Result<(), String> {
+    let linked = get_certificate_by_id(certificate_id.to_string())
+        .ok_or_else(|| "Certificate not found".to_string())?
+        .nft_id;
+    if let Some(existing) = linked {
+        return Err(format!("Certificate is already linked to token {existing}"));
+    }
+    modify_certificate(certificate_id, |cert| {
+        cert.nft_generated = true;
+        cert.nft_id = Some(token_id.to_string());
+        cert.token_uri = Some(token_uri);
+    })
+    .ok_or_else(|| "Certificate not found".to_string())
+}
 
-    CERTIFICATES.with(|certificates| {
-        if let Some(cert) = certificates.borrow_mut().get_mut(&_certificate_id) {
-            cert.nft_generated = true;
-            cert.nft_id = Some(nft_id);
-            cert.token_uri = Some(token_uri);
-            Ok(true)
-        } else {
-            Err("Certificate not found".to_string())
+// Detach a burned token from its certificate so the certificate can be minted again
+// (NFT module burn path only)
+pub(crate) fn unlink_certificate_nft(token_id: u64) {
+    let Some(certificate_id) =
+        CERTIFICATE_IDS_BY_TOKEN.with(|index| index.borrow_mut().remove(&token_id))
+    else {
+        return;
+    };
+    let token = token_id.to_string();
+    modify_certificate(&certificate_id, |cert| {
+        if cert.nft_id.as_deref() == Some(token.as_str()) {
+            cert.nft_generated = false;
+            cert.nft_id = None;
+            cert.token_uri = None;
         }
-    })
+    });
 }
 
 // Get total certificate count
@@ -0,0 +1,315 @@
+use super::{certificate_signing_payload, get_certificate_revocation};
+use crate::modules::http::{escape_html, publish_asset};
+use crate::types::{Certificate, CertificateStatus, RevocationEntry};
+use crate::utils::format_timestamp;
+use serde_json::json;
+
+// =============================================================================
+// Public verification pages served at /verify/{certificate_id}[.json]
+// =============================================================================
+//
+// Pages are rendered from stored state only (never from the current time), so
+// they can be certified at write time. Expiry is therefore decided by the
+// client: the HTML page compares `expiry_date` with the browser clock, and JSON
+// consumers compare it themselves (`status` is the stored status).
+
+pub(super) struct TimelineEvent {
+    pub timestamp: u64,
+    pub description: String,
+}
+
+// Re-render and certify the HTML and JSON verification pages for a certificate
+pub fn publish_certificate_pages(certificate: &Certificate) {
+    let revocation = get_certificate_revocation(certificate.certificate_id.clone());
+    let session = crate::modules::physical_art::get_session_details(certificate.session_id.clone());
+    let photos = session
+        .as_ref()
+        .map(|session| session.uploaded_photos.clone())
+        .unwrap_or_default();
+    let timeline = evidence_timeline(certificate, &revocation);
+
+    let document = verification_document(certificate, &photos, &timeline, &revocation);
+    publish_asset(
+        &format!("/verify/{}.json", certificate.certificate_id),
+        "application/json",
+        serde_json::to_vec_pretty(&document).unwrap_or_default(),
+    );
+
+    let page = render_verification_page(certificate, &photos, &timeline, &revocation);
+    publish_asset(
+        &format!("/verify/{}", certificate.certificate_id),
+        "text/html; charset=utf-8",
+        page.into_bytes(),
+    );
+}
+
+// Chronological process log: session, evidence uploads, issuance, NFT, revocation
+pub(super) fn evidence_timeline(
+    certificate: &Certificate,
+    revocation: &Option<RevocationEntry>,
+) -> Vec<TimelineEvent> {
+    let mut timeline = Vec::new();
+
+    if let Some(session) =
+        crate::modules::physical_art::get_session_details(certificate.session_id.clone())
+    {
+        timeline.push(TimelineEvent {
+            timestamp: session.created_at,
+            description: "Creation session started".to_string(),
+        });
+    }
+
+    let evidence =
+        crate::modules::physical_art::get_session_evidence(certificate.session_id.clone());
+    for (index, photo) in evidence.iter().enumerate() {
+        timeline.push(TimelineEvent {
+            timestamp: photo.uploaded_at,
+            description: format!("Progress photo {} uploaded", index + 1),
+        });
+    }
+
+    timeline.push(TimelineEvent {
+        timestamp: certificate.issue_date,
+        description: match &certificate.supersedes {
+            Some(previous_id) => format!(
+                "Certificate version {} issued, superseding {}",
+                certificate.version, previous_id
+            ),
+            None => "Certificate issued".to_string(),
+        },
+    });
+
+    if let Some(token) = certificate
+        .nft_id
+        .as_ref()
+        .and_then(|token_id| token_id.parse::<u64>().ok())
+        .and_then(crate::modules::nft::get_token_details)
+    {
+        timeline.push(TimelineEvent {
+            timestamp: token.created_at,
+            description: format!("Certificate NFT #{} minted", token.id),
+        });
+    }
+
+    if let Some(entry) = revocation {
+        timeline.push(TimelineEvent {
+            timestamp: entry.revoked_at,
+            description: format!("Certificate revoked ({})", entry.reason.label()),
+        });
+    }
+
+    timeline.sort_by_key(|event| event.timestamp);
+    timeline
+}
+
+fn verification_document(
+    certificate: &Certificate,
+    photos: &[String],
+    timeline: &[TimelineEvent],
+    revocation: &Option<RevocationEntry>,
+) -> serde_json::Value {
+    json!({
+        "certificate_id": certificate.certificate_id,
+        "version": certificate.version,
+        "status": certificate.certificate_status.label(),
+        "art_title": certificate.art_title,
+        "artist": certificate.username,
+        "description": certificate.description,
+        "certificate_type": certificate.certificate_type.label(),
+        "type_details": certificate.type_details,
+        "issuer": certificate.issuer,
+        "blockchain": certificate.blockchain,
+        "issue_date": certificate.issue_date,
+        "expiry_date": certificate.expiry_date,
+        "verification_hash": certificate.verification_hash,
+        "blockchain_tx": certificate.blockchain_tx,
+        "signature": certificate.signature,
+        "signing_payload": certificate_signing_payload(certificate),
+        "scores": {
+            "verification_score": certificate.verification_score,
+            "authenticity_rating": certificate.authenticity_rating,
+            "provenance_score": certificate.provenance_score,
+            "community_trust": certificate.community_trust,
+        },
+        "metadata": certificate.metadata,
+        "evidence": {
+            "photos": photos,
+            "timeline": timeline
+                .iter()
+                .map(|event| json!({ "timestamp": event.timestamp, "event": event.description }))
+                .collect::<Vec<_>>(),
+        },
+        "nft": certificate.nft_id.as_ref().map(|token_id| json!({
+            "token_id": token_id,
+            "token_uri": certificate.token_uri,
+        })),
+        "revocation": revocation.as_ref().map(|entry| json!({
+            "reason": entry.reason.label(),
+            "revoked_by": entry.revoked_by,
+            "revoked_at": entry.revoked_at,
+        })),
+        "supersedes": certificate.supersedes,
+        "superseded_by": certificate.superseded_by,
+    })
+}
+
+fn render_verification_page(
+    certificate: &Certificate,
+    photos: &[String],
+    timeline: &[TimelineEvent],
+    revocation: &Option<RevocationEntry>,
+) -> String {
+    let (status_class, status_text) = match certificate.certificate_status {
+        CertificateStatus::Active => (
+            "valid",
+            format!("Valid until {}", format_timestamp(certificate.expiry_date)),
+        ),
+        CertificateStatus::Revoked => (
+            "invalid",
+            match revocation {
+                Some(entry) => format!(
+                    "Revoked on {} ({})",
+                    format_timestamp(entry.revoked_at),
+                    escape_html(&entry.reason.label())
+                ),
+                None => "Revoked".to_string(),
+            },
+        ),
+        CertificateStatus::Superseded => (
+            "warning",
+            format!(
+                "Superseded by <a href=\"/verify/{0}\">{0}</a>",
+                escape_html(certificate.superseded_by.as_deref().unwrap_or_default())
+            ),
+        ),
+        ref status => ("warning", format!("Certificate {}", status.label())),
+    };
+
+    let scores = [
+        ("Verification score", certificate.verification_score),
+        ("Authenticity rating", certificate.authenticity_rating),
+        ("Provenance score", certificate.provenance_score),
+        ("Community trust", certificate.community_trust),
+    ]
+    .iter()
+    .map(|(label, value)| format!("<tr><th>{label}</th><td>{value}</td></tr>"))
+    .collect::<String>();
+
+    let timeline_items = timeline
+        .iter()
+        .map(|event| {
+            format!(
+                "<li><time>{}</time> {}</li>",
+                format_timestamp(event.timestamp),
+                escape_html(&event.description)
+            )
+        })
+        .collect::<String>();
+
+    let photo_items = photos
+        .iter()
+        .enumerate()
+        .map(|(index, url)| {
+            if url.starts_with("https://") || url.starts_with("http://") {
+                format!(
+                    "<li><a href=\"{0}\" rel=\"noopener\">Progress photo {1}</a></li>",
+                    escape_html(url),
+                    index + 1
+                )
+            } else {
+                format!("<li>{}</li>", escape_html(url))
+            }
+        })
+        .collect::<String>();
+
+    let nft_section = match &certificate.nft_id {
+        Some(token_id) => format!(
+            "<section><h2>NFT</h2><p>Token #{}{}</p></section>",
+            escape_html(token_id),
+            certificate
+                .token_uri
+                .as_ref()
+                .map(|uri| format!(" &middot; <a href=\"{0}\">{0}</a>", escape_html(uri)))
+                .unwrap_or_default()
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title} &middot; OriginStamp Verification</title>
+<style>
+body {{ font-family: system-ui, sans-serif; max-width: 720px; margin: 2rem auto; padding: 0 1rem; color: #1f2937; }}
+.status {{ padding: 0.75rem 1rem; border-radius: 0.5rem; font-weight: 600; }}
+.valid {{ background: #dcfce7; color: #166534; }}
+.invalid {{ background: #fee2e2; color: #991b1b; }}
+.warning {{ background: #fef3c7; color: #92400e; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ text-align: left; padding: 0.25rem 0.5rem; border-bottom: 1px solid #e5e7eb; }}
+code {{ word-break: break-all; font-size: 0.85em; }}
+time {{ color: #6b7280; margin-right: 0.5rem; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>by {artist} &middot; certificate <code>{certificate_id}</code> (version {version})</p>
+<p>{certificate_type}</p>
+<p class="status {status_class}" id="status">{status_text}</p>
+<section><h2>Scores</h2><table>{scores}</table></section>
+<section><h2>Evidence timeline</h2><ul>{timeline_items}</ul><ul>{photo_items}</ul></section>
+{nft_section}
+<section><h2>Cryptographic proof</h2>
+<p>Issued by {issuer} on {issue_date}</p>
+<p>Verification hash: <code>{verification_hash}</code></p>
+<p>Signature: <code>{signature}</code></p>
+<p><a href="/verify/{certificate_id}.json">Machine-readable JSON</a></p>
+</section>
+{expiry_script}
+</body>
+</html>
+"#,
+        title = escape_html(&certificate.art_title),
+        artist = escape_html(&certificate.username),
+        certificate_id = escape_html(&certificate.certificate_id),
+        version = certificate.version,
+        certificate_type = match certificate.type_details.summary() {
+            Some(summary) => format!(
+                "{} certificate &middot; {}",
+                certificate.certificate_type.display_name(),
+                escape_html(&summary)
+            ),
+            None => format!(
+                "{} certificate",
+                certificate.certificate_type.display_name()
+            ),
+        },
+        issuer = escape_html(&certificate.issuer),
+        issue_date = format_timestamp(certificate.issue_date),
+        verification_hash = escape_html(&certificate.verification_hash),
+        signature = escape_html(&certificate.signature),
+        expiry_script = expiry_script(certificate),
+    )
+}
+
+// The page is certified once, so an Active certificate that has since expired
+// is flagged by the browser
+fn expiry_script(certificate: &Certificate) -> String {
+    if certificate.certificate_status != CertificateStatus::Active {
+        return String::new();
+    }
+    format!(
+        r#"<script>
+if (Date.now() > {expires_at_ms}) {{
+  const status = document.getElementById("status");
+  status.className = "status invalid";
+  status.textContent = "Expired on {expiry_date}";
+}}
+</script>"#,
+        expires_at_ms = certificate.expiry_date / 1_000_000,
+        expiry_date = format_timestamp(certificate.expiry_date),
+    )
+}
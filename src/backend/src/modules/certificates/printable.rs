@@ -0,0 +1,608 @@
+use super::{
+    get_certificate_by_id, get_certificate_issuer, qr::encode_qr,
+    rendering::queue_certificate_renders, require_admin, sanitize_string, CERTIFICATES,
+};
+use crate::modules::http::{escape_html, publish_asset};
+use crate::types::{
+    Certificate, CertificateDocument, CertificateDocumentFormat, CertificateStatus,
+    CertificateTemplate, CertificateType, QrErrorCorrection,
+};
+use crate::utils::{format_timestamp, from_hex, to_hex};
+use qrcode::{Color, QrCode};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// Printable certificates of authenticity (SVG and PDF)
+// =============================================================================
+//
+// Both formats are drawn from the same element list on an A4 landscape page
+// (842 x 595 pt, y growing downwards, text positioned by its baseline).
+
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const TEXT_COLOR: Rgb = Rgb(0x1f, 0x29, 0x37);
+const MUTED_COLOR: Rgb = Rgb(0x6b, 0x72, 0x80);
+const ALERT_COLOR: Rgb = Rgb(0xb9, 0x1c, 0x1c);
+const QR_X: f32 = 640.0;
+const QR_Y: f32 = 200.0;
+const QR_SIZE: f32 = 150.0;
+
+thread_local! {
+    static TEMPLATES: RefCell<HashMap<CertificateType, CertificateTemplate>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Clone, Copy)]
+struct Rgb(u8, u8, u8);
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+enum Element {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: Option<Rgb>,
+        stroke: Option<(Rgb, f32)>,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        font: Font,
+        color: Rgb,
+        content: String,
+    },
+}
+
+fn default_template(certificate_type: CertificateType) -> CertificateTemplate {
+    let heading = match certificate_type {
+        CertificateType::Standard => "Certificate of Authenticity",
+        CertificateType::LimitedEdition => "Limited Edition Certificate",
+        CertificateType::Commission => "Certificate of Commissioned Work",
+        CertificateType::Restoration => "Certificate of Restoration",
+        CertificateType::Digital => "Digital Certificate of Authenticity",
+    };
+    CertificateTemplate {
+        certificate_type,
+        heading: heading.to_string(),
+        accent_color: "#1E3A8A".to_string(),
+        background_color: "#FFFDF7".to_string(),
+        footer_text: format!(
+            "Issued on the Internet Computer by {}. Scan the QR code to verify this certificate.",
+            get_certificate_issuer()
+        ),
+        show_scores: true,
+    }
+}
+
+// Get the template used for a certificate type (default if none is configured)
+#[ic_cdk::query]
+pub fn get_certificate_template(certificate_type: CertificateType) -> CertificateTemplate {
+    TEMPLATES
+        .with(|templates| templates.borrow().get(&certificate_type).cloned())
+        .unwrap_or_else(|| default_template(certificate_type))
+}
+
+// Templates for every certificate type, configured or default
+#[ic_cdk::query]
+pub fn list_certificate_templates() -> Vec<CertificateTemplate> {
+    CertificateType::ALL
+        .into_iter()
+        .map(get_certificate_template)
+        .collect()
+}
+
+// Configure the printable template for a certificate type (admin only)
+#[ic_cdk::update]
+pub fn set_certificate_template(template: CertificateTemplate) -> Result<bool, String> {
+    require_admin()?;
+
+    parse_color(&template.accent_color).ok_or("Invalid accent color: expected #RRGGBB")?;
+    parse_color(&template.background_color).ok_or("Invalid background color: expected #RRGGBB")?;
+
+    let template = CertificateTemplate {
+        heading: sanitize_string(&template.heading, 80)?,
+        footer_text: if template.footer_text.is_empty() {
+            String::new()
+        } else {
+            sanitize_string(&template.footer_text, 200)?
+        },
+        ..template
+    };
+
+    let certificate_type = template.certificate_type;
+    TEMPLATES.with(|templates| templates.borrow_mut().insert(certificate_type, template));
+
+    // Re-render published documents in bounded batches, so the update stays
+    // cheap however many certificates use the template
+    let affected: Vec<String> = CERTIFICATES.with(|certificates| {
+        certificates
+            .borrow()
+            .values()
+            .filter(|cert| cert.certificate_type == certificate_type)
+            .map(|cert| cert.certificate_id.clone())
+            .collect()
+    });
+    queue_certificate_renders(affected);
+
+    Ok(true)
+}
+
+// Render a printable certificate as SVG or PDF
+#[ic_cdk::query]
+pub fn get_certificate_document(
+    certificate_id: String,
+    format: CertificateDocumentFormat,
+) -> Result<CertificateDocument, String> {
+    let certificate =
+        get_certificate_by_id(certificate_id).ok_or_else(|| "Certificate not found".to_string())?;
+    render_certificate_document(&certificate, format)
+}
+
+// Publish both renderings at /certificate/{certificate_id}.svg and .pdf
+pub fn publish_certificate_documents(certificate: &Certificate) {
+    for format in [
+        CertificateDocumentFormat::Svg,
+        CertificateDocumentFormat::Pdf,
+    ] {
+        if let Ok(document) = render_certificate_document(certificate, format) {
+            publish_asset(
+                &format!("/certificate/{}", document.file_name),
+                &document.content_type,
+                document.data,
+            );
+        }
+    }
+}
+
+fn render_certificate_document(
+    certificate: &Certificate,
+    format: CertificateDocumentFormat,
+) -> Result<CertificateDocument, String> {
+    let template = get_certificate_template(certificate.certificate_type);
+    let qr = encode_qr(&certificate.qr_code_data, QrErrorCorrection::Quartile)?;
+    let elements = layout(certificate, &template, &qr);
+
+    let (content_type, extension, data) = match format {
+        CertificateDocumentFormat::Svg => ("image/svg+xml", "svg", render_svg(&elements)),
+        CertificateDocumentFormat::Pdf => {
+            ("application/pdf", "pdf", render_pdf(&elements, certificate))
+        }
+    };
+
+    Ok(CertificateDocument {
+        certificate_id: certificate.certificate_id.clone(),
+        format,
+        content_type: content_type.to_string(),
+        file_name: format!("{}.{}", certificate.certificate_id, extension),
+        data,
+    })
+}
+
+// Short, human-comparable digest of the certificate signature
+fn signature_fingerprint(signature: &str) -> String {
+    let Some(bytes) = from_hex(signature).filter(|bytes| !bytes.is_empty()) else {
+        return "unsigned".to_string();
+    };
+    let digest = to_hex(&Sha256::digest(bytes)[..16]).to_uppercase();
+    digest
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_color(hex: &str) -> Option<Rgb> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 {
+        return None;
+    }
+    let bytes = from_hex(digits)?;
+    Some(Rgb(bytes[0], bytes[1], bytes[2]))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(max_chars - 3).collect();
+        truncated.push_str("...");
+        truncated
+    }
+}
+
+fn text(x: f32, y: f32, size: f32, font: Font, color: Rgb, content: impl Into<String>) -> Element {
+    Element::Text {
+        x,
+        y,
+        size,
+        font,
+        color,
+        content: content.into(),
+    }
+}
+
+fn layout(certificate: &Certificate, template: &CertificateTemplate, qr: &QrCode) -> Vec<Element> {
+    let accent = parse_color(&template.accent_color).unwrap_or(Rgb(0x1e, 0x3a, 0x8a));
+    let background = parse_color(&template.background_color).unwrap_or(Rgb(0xff, 0xff, 0xff));
+    let date = |timestamp: u64| format_timestamp(timestamp)[..10].to_string();
+
+    let mut elements = vec![
+        Element::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: PAGE_WIDTH,
+            height: PAGE_HEIGHT,
+            fill: Some(background),
+            stroke: None,
+        },
+        Element::Rect {
+            x: 24.0,
+            y: 24.0,
+            width: PAGE_WIDTH - 48.0,
+            height: PAGE_HEIGHT - 48.0,
+            fill: None,
+            stroke: Some((accent, 3.0)),
+        },
+        Element::Rect {
+            x: 32.0,
+            y: 32.0,
+            width: PAGE_WIDTH - 64.0,
+            height: PAGE_HEIGHT - 64.0,
+            fill: None,
+            stroke: Some((accent, 0.75)),
+        },
+        text(
+            70.0,
+            95.0,
+            30.0,
+            Font::Bold,
+            accent,
+            truncate(&template.heading, 45),
+        ),
+        text(
+            70.0,
+            145.0,
+            22.0,
+            Font::Bold,
+            TEXT_COLOR,
+            truncate(&certificate.art_title, 50),
+        ),
+        text(
+            70.0,
+            172.0,
+            14.0,
+            Font::Regular,
+            TEXT_COLOR,
+            format!("by {}", truncate(&certificate.username, 50)),
+        ),
+    ];
+
+    let version = match &certificate.supersedes {
+        Some(previous_id) => format!("{} (supersedes {})", certificate.version, previous_id),
+        None => certificate.version.to_string(),
+    };
+    let mut details = vec![
+        ("Certificate ID", certificate.certificate_id.clone()),
+        (
+            "Certificate type",
+            certificate.certificate_type.display_name().to_string(),
+        ),
+        ("Issued by", certificate.issuer.clone()),
+        ("Issue date", date(certificate.issue_date)),
+        ("Valid until", date(certificate.expiry_date)),
+        ("Version", version),
+    ];
+    if let Some(summary) = certificate.type_details.summary() {
+        details.insert(2, ("Details", summary));
+    }
+    let mut y = 220.0;
+    for (label, value) in details {
+        elements.push(text(70.0, y, 11.0, Font::Bold, TEXT_COLOR, label));
+        elements.push(text(
+            180.0,
+            y,
+            11.0,
+            Font::Regular,
+            TEXT_COLOR,
+            truncate(&value, 40),
+        ));
+        y += 22.0;
+    }
+    if certificate.certificate_status != CertificateStatus::Active {
+        elements.push(text(
+            70.0,
+            y,
+            11.0,
+            Font::Bold,
+            ALERT_COLOR,
+            format!("Status: {}", certificate.certificate_status.label()),
+        ));
+    }
+
+    if template.show_scores {
+        let scores = [
+            ("Verification score", certificate.verification_score),
+            ("Authenticity rating", certificate.authenticity_rating),
+            ("Provenance score", certificate.provenance_score),
+            ("Community trust", certificate.community_trust),
+        ];
+        let mut y = 220.0;
+        for (label, value) in scores {
+            elements.push(text(430.0, y, 11.0, Font::Bold, TEXT_COLOR, label));
+            elements.push(text(
+                560.0,
+                y,
+                11.0,
+                Font::Regular,
+                TEXT_COLOR,
+                format!("{value}/100"),
+            ));
+            y += 22.0;
+        }
+    }
+
+    let proofs = [
+        ("Verification hash", certificate.verification_hash.clone()),
+        ("Blockchain transaction", certificate.blockchain_tx.clone()),
+        (
+            "Signature fingerprint",
+            signature_fingerprint(&certificate.signature),
+        ),
+    ];
+    let mut y = 400.0;
+    for (label, value) in proofs {
+        elements.push(text(70.0, y, 9.0, Font::Bold, MUTED_COLOR, label));
+        elements.push(text(70.0, y + 12.0, 8.0, Font::Mono, TEXT_COLOR, value));
+        y += 32.0;
+    }
+
+    elements.extend(qr_elements(qr));
+    elements.push(text(
+        QR_X,
+        QR_Y + QR_SIZE + 16.0,
+        9.0,
+        Font::Regular,
+        MUTED_COLOR,
+        "Scan to verify",
+    ));
+
+    elements.push(text(
+        70.0,
+        545.0,
+        9.0,
+        Font::Regular,
+        MUTED_COLOR,
+        truncate(&template.footer_text, 130),
+    ));
+
+    elements
+}
+
+// One rectangle per horizontal run of dark modules
+fn qr_elements(qr: &QrCode) -> Vec<Element> {
+    let width = qr.width();
+    let module = QR_SIZE / width as f32;
+    let colors = qr.to_colors();
+
+    let mut elements = Vec::new();
+    for (row, modules) in colors.chunks(width).enumerate() {
+        let mut column = 0;
+        while column < width {
+            if modules[column] != Color::Dark {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && modules[column] == Color::Dark {
+                column += 1;
+            }
+            elements.push(Element::Rect {
+                x: QR_X + start as f32 * module,
+                y: QR_Y + row as f32 * module,
+                width: (column - start) as f32 * module,
+                height: module,
+                fill: Some(Rgb(0, 0, 0)),
+                stroke: None,
+            });
+        }
+    }
+    elements
+}
+
+fn render_svg(elements: &[Element]) -> Vec<u8> {
+    let mut svg = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="297mm" height="210mm" viewBox="0 0 {PAGE_WIDTH} {PAGE_HEIGHT}">
+"#
+    );
+
+    for element in elements {
+        match element {
+            Element::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let fill = fill.map(svg_color).unwrap_or_else(|| "none".to_string());
+                let stroke = stroke
+                    .map(|(color, stroke_width)| {
+                        format!(
+                            r#" stroke="{}" stroke-width="{stroke_width:.2}""#,
+                            svg_color(color)
+                        )
+                    })
+                    .unwrap_or_default();
+                svg.push_str(&format!(
+                    r#"<rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{height:.2}" fill="{fill}"{stroke}/>"#
+                ));
+            }
+            Element::Text {
+                x,
+                y,
+                size,
+                font,
+                color,
+                content,
+            } => {
+                let font = match font {
+                    Font::Regular => r#"font-family="Helvetica, Arial, sans-serif""#,
+                    Font::Bold => {
+                        r#"font-family="Helvetica, Arial, sans-serif" font-weight="bold""#
+                    }
+                    Font::Mono => r#"font-family="Courier, monospace""#,
+                };
+                svg.push_str(&format!(
+                    r#"<text x="{x:.2}" y="{y:.2}" font-size="{size}" {font} fill="{}">{}</text>"#,
+                    svg_color(*color),
+                    escape_html(content)
+                ));
+            }
+        }
+        svg.push('\n');
+    }
+
+    svg.push_str("</svg>\n");
+    svg.into_bytes()
+}
+
+fn svg_color(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+// Single-page PDF 1.4 using the standard Type 1 fonts, so nothing is embedded
+fn render_pdf(elements: &[Element], certificate: &Certificate) -> Vec<u8> {
+    let mut content = String::new();
+    for element in elements {
+        match element {
+            Element::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let bottom = PAGE_HEIGHT - y - height;
+                if let Some(color) = fill {
+                    content.push_str(&format!(
+                        "{} rg {x:.2} {bottom:.2} {width:.2} {height:.2} re f\n",
+                        pdf_color(*color)
+                    ));
+                }
+                if let Some((color, stroke_width)) = stroke {
+                    content.push_str(&format!(
+                        "{} RG {stroke_width:.2} w {x:.2} {bottom:.2} {width:.2} {height:.2} re S\n",
+                        pdf_color(*color)
+                    ));
+                }
+            }
+            Element::Text {
+                x,
+                y,
+                size,
+                font,
+                color,
+                content: value,
+            } => {
+                let font = match font {
+                    Font::Regular => "F1",
+                    Font::Bold => "F2",
+                    Font::Mono => "F3",
+                };
+                content.push_str(&format!(
+                    "BT /{font} {size} Tf {} rg {x:.2} {:.2} Td ({}) Tj ET\n",
+                    pdf_color(*color),
+                    PAGE_HEIGHT - y,
+                    pdf_string(value)
+                ));
+            }
+        }
+    }
+
+    let creation_date = format_timestamp(certificate.issue_date)
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 5 0 R /F2 6 0 R /F3 7 0 R >> >> /Contents 4 0 R >>"
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!(
+            "<< /Title ({}) /Author ({}) /Producer (OriginStamp) /CreationDate (D:{creation_date}Z) >>",
+            pdf_string(&format!("Certificate {}", certificate.certificate_id)),
+            pdf_string(&certificate.issuer)
+        ),
+    ];
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", index + 1).as_bytes());
+    }
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        xref.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    xref.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 8 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf.extend_from_slice(xref.as_bytes());
+    pdf
+}
+
+fn pdf_color(color: Rgb) -> String {
+    format!(
+        "{:.3} {:.3} {:.3}",
+        color.0 as f32 / 255.0,
+        color.1 as f32 / 255.0,
+        color.2 as f32 / 255.0
+    )
+}
+
+// Escape a PDF literal string; Latin-1 maps onto WinAnsi, anything else becomes '?'
+fn pdf_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c if c.is_whitespace() => escaped.push(' '),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
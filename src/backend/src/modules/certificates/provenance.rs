@@ -0,0 +1,407 @@
+use super::get_certificate_by_id;
+use crate::modules::http::{publish_asset, unpublish_asset};
+use crate::modules::{certification, physical_art, signing};
+use crate::types::{Certificate, PhotoEvidence, ProvenanceManifest};
+use crate::utils::{format_rfc3339, from_hex, to_hex};
+use serde_cbor::Value;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+// =============================================================================
+// Signed provenance manifests (sidecar files)
+// =============================================================================
+//
+// A manifest store is a JUMBF box tree:
+//   c2pa (store) > c2ma (manifest) > c2as (assertions), c2cl (claim), c2cs (signature)
+// The claim references each assertion by hashed URI and is signed as a detached
+// COSE_Sign1 with the canister's threshold key, identified by its did:web
+// verification method (`kid`).
+//
+// The box layout and assertion labels are borrowed from C2PA, but this is not a
+// C2PA manifest and is not served as one: it has no hard binding over the photo
+// bytes (the canister only holds the hashes owners report, carried as ingredient
+// hashes) and is signed with ES256K without an x5chain. Verify it against the
+// did:web key.
+
+const CLAIM_GENERATOR: &str = concat!("OriginStamp/", env!("CARGO_PKG_VERSION"));
+const COSE_ALG_ES256K: i128 = -47;
+const COSE_HEADER_ALG: i128 = 1;
+const COSE_HEADER_KID: i128 = 4;
+const COSE_SIGN1_TAG: u8 = 0xD2; // CBOR tag 18
+
+thread_local! {
+    static MANIFEST_CACHE: RefCell<HashMap<String, ([u8; 32], ProvenanceManifest)>> = RefCell::new(HashMap::new());
+}
+
+// Build (or reuse) the signed provenance manifest for a certificate and publish it
+// at /certificate/{certificate_id}.provenance.jumbf
+#[ic_cdk::update]
+pub async fn export_certificate_provenance(
+    certificate_id: String,
+) -> Result<ProvenanceManifest, String> {
+    let certificate = get_certificate_by_id(certificate_id.clone())
+        .ok_or_else(|| "Certificate not found".to_string())?;
+
+    let signed_hash = certificate_hash(&certificate);
+    if let Some(cached) = MANIFEST_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .get(&certificate_id)
+            .filter(|(hash, _)| *hash == signed_hash)
+            .map(|(_, manifest)| manifest.clone())
+    }) {
+        return Ok(cached);
+    }
+
+    let key = signing::active_signing_key()?;
+    let manifest_label = format!("urn:uuid:{}", uuid_from(&signed_hash));
+    let evidence = physical_art::get_session_evidence(certificate.session_id.clone());
+    let session_started = physical_art::get_session_details(certificate.session_id.clone())
+        .map(|session| session.created_at)
+        .unwrap_or(certificate.issue_date);
+
+    let assertions = assertions(&certificate, &evidence, session_started);
+    let claim = claim(&certificate, &manifest_label, &assertions);
+    let claim_bytes = cbor(&claim);
+
+    let protected = cbor(&int_map(vec![(
+        COSE_HEADER_ALG,
+        Value::Integer(COSE_ALG_ES256K),
+    )]));
+    let sig_structure = Value::Array(vec![
+        text("Signature1"),
+        Value::Bytes(protected.clone()),
+        Value::Bytes(Vec::new()),
+        Value::Bytes(claim_bytes.clone()),
+    ]);
+    let signature = signing::sign_message(&key, &cbor(&sig_structure)).await?;
+
+    // Don't publish a manifest for a certificate that changed during signing
+    if get_certificate_by_id(certificate_id.clone()).map(|cert| certificate_hash(&cert))
+        != Some(signed_hash)
+    {
+        return Err("Certificate changed while the manifest was being signed".to_string());
+    }
+
+    let mut cose_sign1 = vec![COSE_SIGN1_TAG];
+    cose_sign1.extend(cbor(&Value::Array(vec![
+        Value::Bytes(protected),
+        int_map(vec![(
+            COSE_HEADER_KID,
+            Value::Bytes(signing::verification_method_id(&key.key_id).into_bytes()),
+        )]),
+        Value::Null, // detached payload: the claim box
+        Value::Bytes(signature),
+    ])));
+
+    let assertion_store = superbox(
+        b"c2as",
+        "c2pa.assertions",
+        &assertions
+            .iter()
+            .map(|(_, assertion_box)| assertion_box.clone())
+            .collect::<Vec<_>>(),
+    );
+    let manifest = superbox(
+        b"c2ma",
+        &manifest_label,
+        &[
+            assertion_store,
+            superbox(b"c2cl", "c2pa.claim", &[content_box(b"cbor", &claim_bytes)]),
+            superbox(
+                b"c2cs",
+                "c2pa.signature",
+                &[content_box(b"cbor", &cose_sign1)],
+            ),
+        ],
+    );
+    let store = superbox(b"c2pa", "c2pa", &[manifest]);
+
+    let manifest = ProvenanceManifest {
+        certificate_id: certificate_id.clone(),
+        manifest_label,
+        file_name: manifest_file_name(&certificate_id),
+        content_type: "application/octet-stream".to_string(),
+        data: store,
+    };
+
+    publish_asset(
+        &manifest_path(&certificate_id),
+        &manifest.content_type,
+        manifest.data.clone(),
+    );
+    MANIFEST_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .insert(certificate_id, (signed_hash, manifest.clone()))
+    });
+    Ok(manifest)
+}
+
+// Stop serving a published manifest once the certificate it was signed for changes
+pub fn unpublish_stale_manifest(certificate: &Certificate) {
+    let current_hash = certificate_hash(certificate);
+    let stale = MANIFEST_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        match cache.get(&certificate.certificate_id) {
+            Some((hash, _)) if *hash != current_hash => {
+                cache.remove(&certificate.certificate_id);
+                true
+            }
+            _ => false,
+        }
+    });
+    if stale {
+        unpublish_asset(&manifest_path(&certificate.certificate_id));
+    }
+}
+
+fn manifest_path(certificate_id: &str) -> String {
+    format!("/certificate/{}", manifest_file_name(certificate_id))
+}
+
+fn manifest_file_name(certificate_id: &str) -> String {
+    format!("{certificate_id}.provenance.jumbf")
+}
+
+fn certificate_hash(certificate: &Certificate) -> [u8; 32] {
+    Sha256::digest(certification::encode_certificate(certificate)).into()
+}
+
+// (label, assertion superbox) pairs in the order they appear in the store
+fn assertions(
+    certificate: &Certificate,
+    evidence: &[PhotoEvidence],
+    session_started: u64,
+) -> Vec<(String, Vec<u8>)> {
+    let mut assertions = Vec::new();
+
+    // One ingredient per evidence photo: c2pa.ingredient, c2pa.ingredient__1, ...
+    let mut ingredient_refs = Vec::new();
+    for (index, photo) in evidence.iter().enumerate() {
+        let label = if index == 0 {
+            "c2pa.ingredient".to_string()
+        } else {
+            format!("c2pa.ingredient__{index}")
+        };
+        let assertion = cbor_assertion(&label, &ingredient(photo));
+        ingredient_refs.push((photo.uploaded_at, hashed_uri(&label, &assertion)));
+        assertions.push((label, assertion));
+    }
+
+    let mut actions = vec![str_map(vec![
+        ("action", text("c2pa.created")),
+        ("when", text(&format_rfc3339(session_started))),
+        ("softwareAgent", text("OriginStamp")),
+    ])];
+    for (uploaded_at, ingredient_ref) in ingredient_refs {
+        actions.push(str_map(vec![
+            ("action", text("c2pa.placed")),
+            ("when", text(&format_rfc3339(uploaded_at))),
+            (
+                "parameters",
+                str_map(vec![("ingredients", Value::Array(vec![ingredient_ref]))]),
+            ),
+        ]));
+    }
+    actions.push(str_map(vec![
+        ("action", text("org.originstamp.certified")),
+        ("when", text(&format_rfc3339(certificate.issue_date))),
+        ("softwareAgent", text(CLAIM_GENERATOR)),
+        (
+            "parameters",
+            str_map(vec![
+                ("certificateId", text(&certificate.certificate_id)),
+                ("verificationHash", text(&certificate.verification_hash)),
+                ("verificationUrl", text(&certificate.verification_url)),
+            ]),
+        ),
+    ]));
+    assertions.push((
+        "c2pa.actions".to_string(),
+        cbor_assertion(
+            "c2pa.actions",
+            &str_map(vec![("actions", Value::Array(actions))]),
+        ),
+    ));
+
+    let creative_work = serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "CreativeWork",
+        "name": certificate.art_title,
+        "author": [{ "@type": "Person", "name": certificate.username }],
+        "identifier": certificate.certificate_id,
+        "url": certificate.verification_url,
+    });
+    assertions.push((
+        "stds.schema-org.CreativeWork".to_string(),
+        superbox(
+            b"json",
+            "stds.schema-org.CreativeWork",
+            &[content_box(b"json", creative_work.to_string().as_bytes())],
+        ),
+    ));
+
+    let certificate_assertion = str_map(vec![
+        ("certificateId", text(&certificate.certificate_id)),
+        ("version", Value::Integer(certificate.version.into())),
+        ("issuer", text(&signing::issuer_did())),
+        ("verificationHash", text(&certificate.verification_hash)),
+        ("signature", text(&certificate.signature)),
+    ]);
+    assertions.push((
+        "org.originstamp.certificate".to_string(),
+        cbor_assertion("org.originstamp.certificate", &certificate_assertion),
+    ));
+
+    assertions
+}
+
+fn ingredient(photo: &PhotoEvidence) -> Value {
+    let file_name = photo
+        .photo_url
+        .rsplit('/')
+        .next()
+        .unwrap_or(&photo.photo_url);
+    let format = match file_name.rsplit('.').next().map(str::to_ascii_lowercase) {
+        Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+        Some(ext) if ext == "png" => "image/png",
+        Some(ext) if ext == "webp" => "image/webp",
+        Some(ext) if ext == "heic" => "image/heic",
+        _ => "application/octet-stream",
+    };
+
+    let mut fields = vec![
+        ("dc:title", text(file_name)),
+        ("dc:format", text(format)),
+        ("relationship", text("componentOf")),
+        ("documentID", text(&photo.photo_url)),
+    ];
+    // Only hashes reported at upload time are included; URLs alone are not content
+    if let Some(content_hash) = photo.content_hash.as_deref().and_then(from_hex) {
+        fields.push(("alg", text("sha256")));
+        fields.push(("hash", Value::Bytes(content_hash)));
+    }
+    str_map(fields)
+}
+
+fn claim(
+    certificate: &Certificate,
+    manifest_label: &str,
+    assertions: &[(String, Vec<u8>)],
+) -> Value {
+    str_map(vec![
+        ("claim_generator", text(CLAIM_GENERATOR)),
+        (
+            "claim_generator_info",
+            Value::Array(vec![str_map(vec![
+                ("name", text("OriginStamp")),
+                ("version", text(env!("CARGO_PKG_VERSION"))),
+            ])]),
+        ),
+        ("signature", text("self#jumbf=c2pa.signature")),
+        (
+            "assertions",
+            Value::Array(
+                assertions
+                    .iter()
+                    .map(|(label, assertion_box)| hashed_uri(label, assertion_box))
+                    .collect(),
+            ),
+        ),
+        ("dc:title", text(&certificate.art_title)),
+        ("dc:format", text(&certificate.metadata.file_format)),
+        (
+            "instanceID",
+            text(&format!(
+                "xmp:iid:{}",
+                manifest_label.trim_start_matches("urn:uuid:")
+            )),
+        ),
+        ("alg", text("sha256")),
+    ])
+}
+
+// Hashed URI: SHA-256 over the assertion superbox contents (after its box header)
+fn hashed_uri(label: &str, assertion_box: &[u8]) -> Value {
+    str_map(vec![
+        ("url", text(&format!("self#jumbf=c2pa.assertions/{label}"))),
+        ("alg", text("sha256")),
+        (
+            "hash",
+            Value::Bytes(Sha256::digest(&assertion_box[8..]).to_vec()),
+        ),
+    ])
+}
+
+fn cbor_assertion(label: &str, value: &Value) -> Vec<u8> {
+    superbox(b"cbor", label, &[content_box(b"cbor", &cbor(value))])
+}
+
+// JUMBF superbox: description box (type UUID, toggles, label) followed by content
+fn superbox(uuid_prefix: &[u8; 4], label: &str, contents: &[Vec<u8>]) -> Vec<u8> {
+    let mut description = uuid_prefix.to_vec();
+    description.extend_from_slice(&[
+        0x00, 0x11, 0x00, 0x10, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+    ]);
+    description.push(0x03); // requestable, label present
+    description.extend_from_slice(label.as_bytes());
+    description.push(0x00);
+
+    let mut payload = content_box(b"jumd", &description);
+    for content in contents {
+        payload.extend_from_slice(content);
+    }
+    content_box(b"jumb", &payload)
+}
+
+fn content_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut bytes = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(box_type);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn cbor(value: &Value) -> Vec<u8> {
+    serde_cbor::to_vec(value).unwrap_or_default()
+}
+
+fn text(value: &str) -> Value {
+    Value::Text(value.to_string())
+}
+
+fn str_map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (text(key), value))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn int_map(entries: Vec<(i128, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::Integer(key), value))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+// RFC 4122 version 4 layout over the first 16 bytes of a digest
+fn uuid_from(digest: &[u8; 32]) -> String {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0F) | 0x40;
+    bytes[8] = (bytes[8] & 0x3F) | 0x80;
+    let hex = to_hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
@@ -0,0 +1,147 @@
+use super::get_certificate_by_id;
+use crate::modules::http::{is_published, publish_asset};
+use crate::types::{
+    Certificate, CertificateQrCode, QrCodeFormat, QrCodeOptions, QrErrorCorrection,
+};
+use qrcode::render::svg;
+use qrcode::{Color, EcLevel, QrCode};
+
+// =============================================================================
+// Server-side QR codes pointing at the certificate verification URL
+// =============================================================================
+
+const DEFAULT_QR_SIZE: u32 = 256;
+const MIN_QR_SIZE: u32 = 64;
+const MAX_QR_SIZE: u32 = 2048;
+// Quiet zone width in modules, as required by ISO/IEC 18004
+const QUIET_ZONE_MODULES: u32 = 4;
+
+// Get a certificate's QR code as SVG or PNG bytes
+#[ic_cdk::query]
+pub fn get_certificate_qr(
+    certificate_id: String,
+    format: QrCodeFormat,
+    options: Option<QrCodeOptions>,
+) -> Result<CertificateQrCode, String> {
+    let certificate =
+        get_certificate_by_id(certificate_id).ok_or_else(|| "Certificate not found".to_string())?;
+    render_certificate_qr(&certificate, format, &options.unwrap_or_default())
+}
+
+fn qr_path(certificate_id: &str) -> String {
+    format!("/qr/{certificate_id}.svg")
+}
+
+pub fn is_certificate_qr_published(certificate_id: &str) -> bool {
+    is_published(&qr_path(certificate_id))
+}
+
+// Publish the default SVG rendering at /qr/{certificate_id}.svg
+pub fn publish_certificate_qr(certificate: &Certificate) {
+    if let Ok(qr) = render_certificate_qr(certificate, QrCodeFormat::Svg, &QrCodeOptions::default())
+    {
+        publish_asset(
+            &qr_path(&certificate.certificate_id),
+            &qr.content_type,
+            qr.data,
+        );
+    }
+}
+
+fn render_certificate_qr(
+    certificate: &Certificate,
+    format: QrCodeFormat,
+    options: &QrCodeOptions,
+) -> Result<CertificateQrCode, String> {
+    let target_size = options.size.unwrap_or(DEFAULT_QR_SIZE);
+    if !(MIN_QR_SIZE..=MAX_QR_SIZE).contains(&target_size) {
+        return Err(format!(
+            "QR size must be between {MIN_QR_SIZE} and {MAX_QR_SIZE} pixels"
+        ));
+    }
+
+    let code = encode_qr(
+        &certificate.qr_code_data,
+        options
+            .error_correction
+            .unwrap_or(QrErrorCorrection::Medium),
+    )?;
+
+    let quiet_zone = options.quiet_zone.unwrap_or(true);
+    let border = if quiet_zone { QUIET_ZONE_MODULES } else { 0 };
+    let modules = code.width() as u32 + 2 * border;
+    if modules > target_size {
+        return Err(format!(
+            "QR size must be at least {modules} pixels for this code"
+        ));
+    }
+    let module_size = target_size / modules;
+    let size = module_size * modules;
+
+    let (content_type, data) = match format {
+        QrCodeFormat::Svg => (
+            "image/svg+xml",
+            code.render::<svg::Color>()
+                .module_dimensions(module_size, module_size)
+                .quiet_zone(quiet_zone)
+                .build()
+                .into_bytes(),
+        ),
+        QrCodeFormat::Png => ("image/png", encode_png(&code, border, module_size, size)?),
+    };
+
+    Ok(CertificateQrCode {
+        certificate_id: certificate.certificate_id.clone(),
+        format,
+        content_type: content_type.to_string(),
+        encoded_url: certificate.qr_code_data.clone(),
+        size,
+        data,
+    })
+}
+
+pub(super) fn encode_qr(data: &str, error_correction: QrErrorCorrection) -> Result<QrCode, String> {
+    let ec_level = match error_correction {
+        QrErrorCorrection::Low => EcLevel::L,
+        QrErrorCorrection::Medium => EcLevel::M,
+        QrErrorCorrection::Quartile => EcLevel::Q,
+        QrErrorCorrection::High => EcLevel::H,
+    };
+    QrCode::with_error_correction_level(data, ec_level)
+        .map_err(|e| format!("Failed to encode QR code: {e}"))
+}
+
+// Rasterize the code into an 8-bit grayscale PNG
+fn encode_png(code: &QrCode, border: u32, module_size: u32, size: u32) -> Result<Vec<u8>, String> {
+    let width = code.width();
+    let colors = code.to_colors();
+
+    let mut pixels = vec![0xFF_u8; (size * size) as usize];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x0 = ((index % width) as u32 + border) * module_size;
+        let y0 = ((index / width) as u32 + border) * module_size;
+        for y in y0..y0 + module_size {
+            let row = (y * size) as usize;
+            pixels[row + x0 as usize..row + (x0 + module_size) as usize].fill(0x00);
+        }
+    }
+
+    let mut png_bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png_bytes, size, size);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+
+    Ok(png_bytes)
+}
@@ -0,0 +1,64 @@
+use super::{get_certificate_by_id, pages, printable, qr, require_admin};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+// =============================================================================
+// Deferred rendering of the public assets derived from certificates
+// =============================================================================
+//
+// Verification pages, printable documents and QR codes are not rendered inside
+// the update that changed a certificate, its evidence or its template. The
+// certificate is queued instead and rendered in bounded batches by one-way calls
+// to self, so writes stay cheap and repeated changes render once. Published
+// assets can lag the certified certificate data by a message or two.
+
+// Certificates rendered per message
+const RENDER_BATCH_SIZE: usize = 25;
+
+thread_local! {
+    // Certificates whose published assets are missing or out of date
+    static PENDING_RENDERS: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+// Queue certificates for re-rendering and make sure a batch is scheduled
+pub(super) fn queue_certificate_renders(certificate_ids: impl IntoIterator<Item = String>) {
+    PENDING_RENDERS.with(|pending| pending.borrow_mut().extend(certificate_ids));
+    schedule_pending_renders();
+}
+
+// Render the next batch of queued certificates and queue another batch if any
+// remain (the canister itself or admins); returns how many are still pending
+#[ic_cdk::update]
+pub fn render_pending_certificate_assets() -> Result<u64, String> {
+    if ic_cdk::api::caller() != ic_cdk::api::id() {
+        require_admin()?;
+    }
+
+    let batch: Vec<String> = PENDING_RENDERS.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        std::iter::from_fn(|| pending.pop_first())
+            .take(RENDER_BATCH_SIZE)
+            .collect()
+    });
+    for certificate in batch.into_iter().filter_map(get_certificate_by_id) {
+        pages::publish_certificate_pages(&certificate);
+        printable::publish_certificate_documents(&certificate);
+        // The QR code only encodes the verification URL, which never changes
+        if !qr::is_certificate_qr_published(&certificate.certificate_id) {
+            qr::publish_certificate_qr(&certificate);
+        }
+    }
+
+    schedule_pending_renders();
+    Ok(PENDING_RENDERS.with(|pending| pending.borrow().len()) as u64)
+}
+
+// Process the next batch in a separate message via a one-way call to self
+fn schedule_pending_renders() {
+    if PENDING_RENDERS.with(|pending| pending.borrow().is_empty()) {
+        return;
+    }
+    // If the call cannot be enqueued, the next certificate change or an admin
+    // call to render_pending_certificate_assets resumes the queue
+    let _ = ic_cdk::api::call::notify(ic_cdk::api::id(), "render_pending_certificate_assets", ());
+}
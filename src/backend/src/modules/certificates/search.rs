@@ -0,0 +1,232 @@
+use super::CERTIFICATES;
+use crate::types::{
+    Certificate, CertificateSearchHit, CertificateSearchQuery, CertificateSearchResult,
+    CertificateStatus,
+};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+// =============================================================================
+// Full-text certificate search
+// =============================================================================
+//
+// Inverted index from normalized terms to the certificates containing them,
+// weighted by the field a term appears in. It is updated whenever a certificate
+// is written. A query matches certificates containing every query term; the last
+// term also matches as a prefix so partial input finds results while typing.
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+const MAX_QUERY_LENGTH: usize = 200;
+const MAX_QUERY_TERMS: usize = 10;
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 40;
+// Shortest last term that is also matched as a prefix
+const MIN_PREFIX_LENGTH: usize = 3;
+
+// Field weights: a title match outranks a description match
+const TITLE_WEIGHT: u32 = 5;
+const ARTIST_WEIGHT: u32 = 4;
+const TOOLS_WEIGHT: u32 = 2;
+const FORMAT_WEIGHT: u32 = 1;
+const DESCRIPTION_WEIGHT: u32 = 1;
+
+thread_local! {
+    // term -> certificate_id -> weight
+    static SEARCH_INDEX: RefCell<BTreeMap<String, HashMap<String, u32>>> = const { RefCell::new(BTreeMap::new()) };
+    // certificate_id -> indexed terms, to unindex on update
+    static INDEXED_TERMS: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+// Search certificates by text with filters, ordered by relevance
+#[ic_cdk::query]
+pub fn search_certificates(
+    query: CertificateSearchQuery,
+) -> Result<CertificateSearchResult, String> {
+    if query.query.len() > MAX_QUERY_LENGTH {
+        return Err(format!(
+            "Query too long: at most {MAX_QUERY_LENGTH} characters"
+        ));
+    }
+    let terms = tokenize(&query.query);
+    if terms.len() > MAX_QUERY_TERMS {
+        return Err(format!("Query too long: at most {MAX_QUERY_TERMS} terms"));
+    }
+    if let (Some(after), Some(before)) = (query.issued_after, query.issued_before) {
+        if after > before {
+            return Err("issued_after must not be later than issued_before".to_string());
+        }
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+
+    let now = time();
+    let (total, hits) = CERTIFICATES.with(|certificates| {
+        let certificates = certificates.borrow();
+        let mut scored: Vec<(&Certificate, f64)> = if terms.is_empty() {
+            certificates.values().map(|cert| (cert, 0.0)).collect()
+        } else {
+            score_matches(&terms, certificates.len())
+                .into_iter()
+                .filter_map(|(id, score)| certificates.get(&id).map(|cert| (cert, score)))
+                .collect()
+        };
+        scored.retain(|(cert, _)| matches_filters(cert, &query, now));
+
+        // Highest score first; newest first among equal scores (and for filter-only queries).
+        // Sort borrowed entries and clone only the requested page.
+        scored.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then(b.issue_date.cmp(&a.issue_date))
+                .then(a.certificate_id.cmp(&b.certificate_id))
+        });
+
+        let total = scored.len() as u64;
+        let hits = scored
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(cert, score)| CertificateSearchHit {
+                certificate: cert.clone(),
+                score,
+            })
+            .collect::<Vec<_>>();
+        (total, hits)
+    });
+
+    Ok(CertificateSearchResult {
+        total,
+        offset,
+        limit,
+        hits,
+    })
+}
+
+// Replace the index entries of a certificate with its current content
+pub(super) fn index_certificate(certificate: &Certificate) {
+    let mut weights: HashMap<String, u32> = HashMap::new();
+    let mut add = |text: &str, weight: u32| {
+        for term in tokenize(text) {
+            *weights.entry(term).or_default() += weight;
+        }
+    };
+    add(&certificate.art_title, TITLE_WEIGHT);
+    add(&certificate.username, ARTIST_WEIGHT);
+    for tool in &certificate.metadata.creation_tools {
+        add(tool, TOOLS_WEIGHT);
+    }
+    add(&certificate.metadata.file_format, FORMAT_WEIGHT);
+    add(&certificate.description, DESCRIPTION_WEIGHT);
+
+    let certificate_id = &certificate.certificate_id;
+    SEARCH_INDEX.with(|index| {
+        let mut index = index.borrow_mut();
+
+        let previous = INDEXED_TERMS
+            .with(|indexed| indexed.borrow_mut().remove(certificate_id))
+            .unwrap_or_default();
+        for term in previous {
+            if let Some(postings) = index.get_mut(&term) {
+                postings.remove(certificate_id);
+                if postings.is_empty() {
+                    index.remove(&term);
+                }
+            }
+        }
+
+        let terms: Vec<String> = weights.keys().cloned().collect();
+        for (term, weight) in weights {
+            index
+                .entry(term)
+                .or_default()
+                .insert(certificate_id.clone(), weight);
+        }
+        INDEXED_TERMS.with(|indexed| indexed.borrow_mut().insert(certificate_id.clone(), terms));
+    });
+}
+
+// Lowercase alphanumeric terms, deduplicated in order of appearance
+fn tokenize(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let term = word.to_lowercase();
+        let length = term.chars().count();
+        if (MIN_TERM_LENGTH..=MAX_TERM_LENGTH).contains(&length) && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+// Certificates containing every term, scored by field weight times inverse
+// document frequency so rare terms count for more
+fn score_matches(terms: &[String], document_count: usize) -> HashMap<String, f64> {
+    SEARCH_INDEX.with(|index| {
+        let index = index.borrow();
+        let mut scores: Option<HashMap<String, f64>> = None;
+
+        for (position, term) in terms.iter().enumerate() {
+            let is_prefix =
+                position == terms.len() - 1 && term.chars().count() >= MIN_PREFIX_LENGTH;
+            let mut term_scores: HashMap<String, f64> = HashMap::new();
+
+            let postings = index.range(term.clone()..).take_while(|(key, _)| {
+                if is_prefix {
+                    key.starts_with(term.as_str())
+                } else {
+                    *key == term
+                }
+            });
+            for (key, postings) in postings {
+                let idf = (1.0 + document_count as f64 / postings.len() as f64).ln();
+                // Exact matches rank above prefix completions
+                let exactness = if key == term { 1.0 } else { 0.5 };
+                for (certificate_id, weight) in postings {
+                    let score = *weight as f64 * idf * exactness;
+                    let entry = term_scores.entry(certificate_id.clone()).or_default();
+                    *entry = entry.max(score);
+                }
+            }
+
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(id, score)| term_scores.get(&id).map(|term| (id, score + term)))
+                    .collect(),
+            });
+        }
+
+        scores.unwrap_or_default()
+    })
+}
+
+fn matches_filters(certificate: &Certificate, query: &CertificateSearchQuery, now: u64) -> bool {
+    // Filter on the effective status, as verify_certificate reports it
+    let status = if certificate.certificate_status == CertificateStatus::Active
+        && now > certificate.expiry_date
+    {
+        CertificateStatus::Expired
+    } else {
+        certificate.certificate_status.clone()
+    };
+
+    query.status.as_ref().is_none_or(|wanted| *wanted == status)
+        && query
+            .certificate_type
+            .is_none_or(|wanted| wanted == certificate.certificate_type)
+        && query
+            .issued_after
+            .is_none_or(|after| certificate.issue_date >= after)
+        && query
+            .issued_before
+            .is_none_or(|before| certificate.issue_date <= before)
+        && query
+            .nft_minted
+            .is_none_or(|minted| minted == certificate.nft_id.is_some())
+}
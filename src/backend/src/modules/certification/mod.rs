@@ -0,0 +1,160 @@
+use crate::types::Certificate;
+use ic_certified_map::{
+    fork, fork_hash, labeled, labeled_hash, AsHashTree, Hash, HashTree, RbTree,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+// =============================================================================
+// Certified data
+// =============================================================================
+//
+// The certified root hash covers labeled subtrees, kept in label order:
+//   certificates/<certificate_id> -> sha256(candid-encoded certificate)
+//   http_assets/<url path>        -> sha256(response body)
+//   last_block_hash               -> hash of the newest ICRC-3 block
+//   last_block_index              -> its index (LEB128)
+// The block labels are present once the NFT transaction log has a block.
+
+const CERTIFICATES_LABEL: &[u8] = b"certificates";
+const HTTP_ASSETS_LABEL: &[u8] = b"http_assets";
+const LAST_BLOCK_HASH_LABEL: &[u8] = b"last_block_hash";
+const LAST_BLOCK_INDEX_LABEL: &[u8] = b"last_block_index";
+
+thread_local! {
+    static CERTIFICATE_HASHES: RefCell<RbTree<String, Hash>> = const { RefCell::new(RbTree::new()) };
+    static ASSET_HASHES: RefCell<RbTree<String, Hash>> = const { RefCell::new(RbTree::new()) };
+    // (index, hash) of the last block in the NFT transaction log
+    static BLOCK_TIP: RefCell<Option<(u64, Hash)>> = const { RefCell::new(None) };
+}
+
+// Candid encoding of a certificate; its SHA-256 is the certified leaf value
+pub fn encode_certificate(certificate: &Certificate) -> Vec<u8> {
+    candid::encode_one(certificate).unwrap_or_default()
+}
+
+// Record the current state of a certificate in the certified tree
+pub fn certify_certificate(certificate: &Certificate) {
+    let hash: Hash = Sha256::digest(encode_certificate(certificate)).into();
+    CERTIFICATE_HASHES.with(|tree| {
+        tree.borrow_mut()
+            .insert(certificate.certificate_id.clone(), hash)
+    });
+    update_certified_data();
+}
+
+// Record the body served at an HTTP path in the certified tree
+pub fn certify_asset(path: &str, body: &[u8]) {
+    let hash: Hash = Sha256::digest(body).into();
+    ASSET_HASHES.with(|tree| tree.borrow_mut().insert(path.to_string(), hash));
+    update_certified_data();
+}
+
+pub fn remove_asset(path: &str) {
+    ASSET_HASHES.with(|tree| tree.borrow_mut().delete(path.as_bytes()));
+    update_certified_data();
+}
+
+// Record the newest block of the NFT transaction log
+pub fn certify_block_tip(index: u64, hash: Hash) {
+    BLOCK_TIP.with(|tip| *tip.borrow_mut() = Some((index, hash)));
+    update_certified_data();
+}
+
+fn certificates_hash() -> Hash {
+    CERTIFICATE_HASHES.with(|tree| labeled_hash(CERTIFICATES_LABEL, &tree.borrow().root_hash()))
+}
+
+fn assets_hash() -> Hash {
+    ASSET_HASHES.with(|tree| labeled_hash(HTTP_ASSETS_LABEL, &tree.borrow().root_hash()))
+}
+
+fn block_tip_tree() -> Option<HashTree<'static>> {
+    BLOCK_TIP.with(|tip| {
+        tip.borrow().map(|(index, hash)| {
+            fork(
+                labeled(
+                    LAST_BLOCK_HASH_LABEL,
+                    HashTree::Leaf(Cow::Owned(hash.to_vec())),
+                ),
+                labeled(
+                    LAST_BLOCK_INDEX_LABEL,
+                    HashTree::Leaf(Cow::Owned(leb128(index))),
+                ),
+            )
+        })
+    })
+}
+
+// Hash of the certificate and asset subtrees
+fn content_hash() -> Hash {
+    fork_hash(&certificates_hash(), &assets_hash())
+}
+
+// Publish the root hash as the canister's certified data
+pub fn update_certified_data() {
+    let root_hash = match block_tip_tree() {
+        Some(tip) => fork_hash(&content_hash(), &tip.reconstruct()),
+        None => content_hash(),
+    };
+    ic_cdk::api::set_certified_data(&root_hash);
+}
+
+// Extend a witness of the content subtrees with the pruned block tip
+fn with_block_tip(tree: HashTree) -> HashTree {
+    match block_tip_tree() {
+        Some(tip) => fork(tree, HashTree::Pruned(tip.reconstruct())),
+        None => tree,
+    }
+}
+
+// CBOR-encoded witness for the last block hash and index (ICRC-3 tip certificate)
+pub fn block_tip_witness() -> Option<Vec<u8>> {
+    let tip = block_tip_tree()?;
+    Some(serialize_tree(&fork(HashTree::Pruned(content_hash()), tip)))
+}
+
+// CBOR-encoded witness for a certificate ID (proves presence or absence)
+pub fn certificate_witness(certificate_id: &str) -> Vec<u8> {
+    CERTIFICATE_HASHES.with(|tree| {
+        let tree = tree.borrow();
+        serialize_tree(&with_block_tip(fork(
+            labeled(CERTIFICATES_LABEL, tree.witness(certificate_id.as_bytes())),
+            HashTree::Pruned(assets_hash()),
+        )))
+    })
+}
+
+// CBOR-encoded witness for an HTTP path (proves presence or absence)
+pub fn asset_witness(path: &str) -> Vec<u8> {
+    ASSET_HASHES.with(|tree| {
+        let tree = tree.borrow();
+        serialize_tree(&with_block_tip(fork(
+            HashTree::Pruned(certificates_hash()),
+            labeled(HTTP_ASSETS_LABEL, tree.witness(path.as_bytes())),
+        )))
+    })
+}
+
+fn serialize_tree(tree: &HashTree) -> Vec<u8> {
+    let mut serializer = serde_cbor::ser::Serializer::new(Vec::new());
+    serializer.self_describe().ok();
+    tree.serialize(&mut serializer).ok();
+    serializer.into_inner()
+}
+
+// Unsigned LEB128, as used for the certified last block index
+fn leb128(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::modules::certification;
+use crate::types::{HeaderField, HttpRequest, HttpResponse};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// HTTP gateway: serves pre-rendered, certified assets
+// =============================================================================
+//
+// Modules publish a response body whenever the underlying data changes; the
+// body hash is added to the certified tree so boundary nodes can validate
+// responses (response verification v1: `IC-Certificate` header).
+
+struct HttpAsset {
+    content_type: String,
+    body: Vec<u8>,
+}
+
+thread_local! {
+    static ASSETS: RefCell<HashMap<String, HttpAsset>> = RefCell::new(HashMap::new());
+}
+
+// Store (or replace) the body served at a path and certify it
+pub fn publish_asset(path: &str, content_type: &str, body: Vec<u8>) {
+    certification::certify_asset(path, &body);
+    ASSETS.with(|assets| {
+        assets.borrow_mut().insert(
+            path.to_string(),
+            HttpAsset {
+                content_type: content_type.to_string(),
+                body,
+            },
+        );
+    });
+}
+
+pub fn is_published(path: &str) -> bool {
+    ASSETS.with(|assets| assets.borrow().contains_key(path))
+}
+
+pub fn unpublish_asset(path: &str) {
+    certification::remove_asset(path);
+    ASSETS.with(|assets| assets.borrow_mut().remove(path));
+}
+
+// Serve certified assets over HTTP
+#[ic_cdk::query]
+pub fn http_request(request: HttpRequest) -> HttpResponse {
+    if request.method != "GET" && request.method != "HEAD" {
+        return text_response(405, "Method not allowed");
+    }
+
+    let path = request.url.split(['?', '#']).next().unwrap_or("/");
+
+    ASSETS.with(|assets| {
+        let assets = assets.borrow();
+        let Some(asset) = assets.get(path) else {
+            return text_response(404, "Not found");
+        };
+
+        let mut headers = vec![
+            ("Content-Type".to_string(), asset.content_type.clone()),
+            ("Content-Length".to_string(), asset.body.len().to_string()),
+            ("Cache-Control".to_string(), "no-cache".to_string()),
+            ("Access-Control-Allow-Origin".to_string(), "*".to_string()),
+        ];
+        headers.extend(certificate_header(path));
+
+        // HEAD gets the same body as GET: the certificate covers the body hash,
+        // so an empty body would fail response verification
+        HttpResponse {
+            status_code: 200,
+            headers,
+            body: asset.body.clone(),
+        }
+    })
+}
+
+fn certificate_header(path: &str) -> Option<HeaderField> {
+    let data_certificate = ic_cdk::api::data_certificate()?;
+    let witness = certification::asset_witness(path);
+    Some((
+        "IC-Certificate".to_string(),
+        format!(
+            "certificate=:{}:, tree=:{}:",
+            BASE64.encode(data_certificate),
+            BASE64.encode(witness)
+        ),
+    ))
+}
+
+fn text_response(status_code: u16, message: &str) -> HttpResponse {
+    HttpResponse {
+        status_code,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8".to_string(),
+        )],
+        body: message.as_bytes().to_vec(),
+    }
+}
+
+// Escape text for inclusion in HTML/SVG markup
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,5 +1,8 @@
 pub mod certificates;
+pub mod certification;
+pub mod http;
 pub mod nft;
 pub mod physical_art;
 pub mod s3;
+pub mod signing;
 pub mod users;
@@ -0,0 +1,541 @@
+use super::{
+    blocks, check_duplicate, check_query_batch, check_request, complete_transfer, policy,
+    request_hash, token_id_from_nat, token_owner, Transfer, BATCH_SIZE_ERROR_CODE,
+    DEFAULT_TAKE_VALUE, MAX_TAKE_VALUE, MAX_UPDATE_BATCH_SIZE,
+};
+use crate::types::{
+    Account, ApprovalInfo, ApproveCollectionArg, ApproveCollectionError, ApproveCollectionResult,
+    ApproveTokenArg, ApproveTokenError, ApproveTokenResult, CollectionApproval, IsApprovedArg,
+    RevokeCollectionApprovalArg, RevokeCollectionApprovalError, RevokeCollectionApprovalResult,
+    RevokeTokenApprovalArg, RevokeTokenApprovalError, RevokeTokenApprovalResponse, TokenApproval,
+    TransferError, TransferFromArg, TransferFromResult, Value,
+};
+use candid::{Nat, Principal};
+use ic_cdk::api::{caller, time};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// ICRC-37 approvals
+// =============================================================================
+//
+// A holder can let a spender (typically a marketplace) move a single token or
+// every token the holding account owns. Token approvals belong to the holder
+// that granted them and are dropped when the token changes hands; collection
+// approvals stay with the account. Expired approvals are ignored and pruned on
+// the next write.
+
+// Most approvals a single token or account can carry at once
+pub const MAX_APPROVALS: u64 = 32;
+
+const TOO_MANY_APPROVALS_ERROR_CODE: u64 = 3;
+const ALREADY_EXPIRED_ERROR_CODE: u64 = 4;
+
+thread_local! {
+    // token id -> approvals granted by the current holder
+    static TOKEN_APPROVALS: RefCell<HashMap<u64, Vec<ApprovalInfo>>> = RefCell::new(HashMap::new());
+    // holding account -> approvals covering all of its tokens
+    static COLLECTION_APPROVALS: RefCell<HashMap<Account, Vec<ApprovalInfo>>> = RefCell::new(HashMap::new());
+}
+
+#[ic_cdk::query]
+pub fn icrc37_metadata() -> Vec<(String, Value)> {
+    vec![
+        (
+            "icrc37:max_approvals_per_token_or_collection".to_string(),
+            Value::Nat(MAX_APPROVALS.into()),
+        ),
+        (
+            "icrc37:max_revoke_approvals".to_string(),
+            Value::Nat(MAX_UPDATE_BATCH_SIZE.into()),
+        ),
+    ]
+}
+
+#[ic_cdk::query]
+pub fn icrc37_max_approvals_per_token_or_collection() -> Option<Nat> {
+    Some(MAX_APPROVALS.into())
+}
+
+#[ic_cdk::query]
+pub fn icrc37_max_revoke_approvals() -> Option<Nat> {
+    Some(MAX_UPDATE_BATCH_SIZE.into())
+}
+
+// Approve spenders for individual tokens held by the caller
+#[ic_cdk::update]
+pub fn icrc37_approve_tokens(args: Vec<ApproveTokenArg>) -> Vec<Option<ApproveTokenResult>> {
+    if let Some(message) = batch_size_error(args.len()) {
+        return vec![Some(Err(ApproveTokenError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message,
+        }))];
+    }
+    let caller = caller();
+    let now = time();
+
+    args.into_iter()
+        .map(|arg| Some(approve_token(caller, arg, now)))
+        .collect()
+}
+
+fn approve_token(caller: Principal, arg: ApproveTokenArg, now: u64) -> ApproveTokenResult {
+    let approval = arg.approval_info;
+    check_approval(&approval, now)?;
+
+    let holder = Account {
+        owner: caller,
+        subaccount: approval.from_subaccount.clone(),
+    };
+    if approval.spender == holder {
+        return Err(ApproveTokenError::InvalidSpender);
+    }
+    let (token_id, owner) =
+        token_owner(&arg.token_id).ok_or(ApproveTokenError::NonExistingTokenId)?;
+    if owner != holder {
+        return Err(ApproveTokenError::Unauthorized);
+    }
+
+    let mut tx = vec![("tid".to_string(), Value::Nat(token_id.into()))];
+    tx.extend(approval_fields(&holder, &approval));
+    TOKEN_APPROVALS
+        .with(|approvals| {
+            let mut approvals = approvals.borrow_mut();
+            insert_approval(approvals.entry(token_id).or_default(), approval, now)
+        })
+        .map_err(|message| ApproveTokenError::GenericError {
+            error_code: TOO_MANY_APPROVALS_ERROR_CODE.into(),
+            message,
+        })?;
+
+    Ok(blocks::append_block(blocks::APPROVE, tx).into())
+}
+
+// Approve spenders for every token held by the caller's account
+#[ic_cdk::update]
+pub fn icrc37_approve_collection(
+    args: Vec<ApproveCollectionArg>,
+) -> Vec<Option<ApproveCollectionResult>> {
+    if let Some(message) = batch_size_error(args.len()) {
+        return vec![Some(Err(ApproveCollectionError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message,
+        }))];
+    }
+    let caller = caller();
+    let now = time();
+
+    args.into_iter()
+        .map(|arg| Some(approve_collection(caller, arg, now)))
+        .collect()
+}
+
+fn approve_collection(
+    caller: Principal,
+    arg: ApproveCollectionArg,
+    now: u64,
+) -> ApproveCollectionResult {
+    let approval = arg.approval_info;
+    check_approval(&approval, now)?;
+
+    let holder = Account {
+        owner: caller,
+        subaccount: approval.from_subaccount.clone(),
+    };
+    if approval.spender == holder {
+        return Err(ApproveCollectionError::InvalidSpender);
+    }
+
+    let tx = approval_fields(&holder, &approval);
+    COLLECTION_APPROVALS
+        .with(|approvals| {
+            let mut approvals = approvals.borrow_mut();
+            insert_approval(approvals.entry(holder).or_default(), approval, now)
+        })
+        .map_err(|message| ApproveCollectionError::GenericError {
+            error_code: TOO_MANY_APPROVALS_ERROR_CODE.into(),
+            message,
+        })?;
+
+    Ok(blocks::append_block(blocks::APPROVE_COLLECTION, tx).into())
+}
+
+// Revoke token approvals granted by the caller, for one spender or all of them
+#[ic_cdk::update]
+pub fn icrc37_revoke_token_approvals(
+    args: Vec<RevokeTokenApprovalArg>,
+) -> Vec<Option<RevokeTokenApprovalResponse>> {
+    if let Some(message) = batch_size_error(args.len()) {
+        return vec![Some(Err(RevokeTokenApprovalError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message,
+        }))];
+    }
+    let caller = caller();
+    let now = time();
+
+    args.into_iter()
+        .map(|arg| Some(revoke_token_approvals(caller, arg, now)))
+        .collect()
+}
+
+fn revoke_token_approvals(
+    caller: Principal,
+    arg: RevokeTokenApprovalArg,
+    now: u64,
+) -> RevokeTokenApprovalResponse {
+    check_request(&arg.memo, arg.created_at_time, now)?;
+
+    let (token_id, owner) =
+        token_owner(&arg.token_id).ok_or(RevokeTokenApprovalError::NonExistingTokenId)?;
+    let holder = Account {
+        owner: caller,
+        subaccount: arg.from_subaccount,
+    };
+    if owner != holder {
+        return Err(RevokeTokenApprovalError::Unauthorized);
+    }
+
+    let revoked = TOKEN_APPROVALS.with(|approvals| {
+        let mut approvals = approvals.borrow_mut();
+        let revoked = approvals
+            .get_mut(&token_id)
+            .is_some_and(|token_approvals| remove_approvals(token_approvals, &arg.spender, now));
+        if approvals.get(&token_id).is_some_and(Vec::is_empty) {
+            approvals.remove(&token_id);
+        }
+        revoked
+    });
+    if !revoked {
+        return Err(RevokeTokenApprovalError::ApprovalDoesNotExist);
+    }
+
+    let mut tx = vec![("tid".to_string(), Value::Nat(token_id.into()))];
+    tx.extend(revocation_fields(
+        &holder,
+        &arg.spender,
+        &arg.memo,
+        arg.created_at_time,
+    ));
+    Ok(blocks::append_block(blocks::REVOKE, tx).into())
+}
+
+// Revoke collection approvals of the caller's account, for one spender or all of them
+#[ic_cdk::update]
+pub fn icrc37_revoke_collection_approvals(
+    args: Vec<RevokeCollectionApprovalArg>,
+) -> Vec<Option<RevokeCollectionApprovalResult>> {
+    if let Some(message) = batch_size_error(args.len()) {
+        return vec![Some(Err(
+            RevokeCollectionApprovalError::GenericBatchError {
+                error_code: BATCH_SIZE_ERROR_CODE.into(),
+                message,
+            },
+        ))];
+    }
+    let caller = caller();
+    let now = time();
+
+    args.into_iter()
+        .map(|arg| Some(revoke_collection_approvals(caller, arg, now)))
+        .collect()
+}
+
+fn revoke_collection_approvals(
+    caller: Principal,
+    arg: RevokeCollectionApprovalArg,
+    now: u64,
+) -> RevokeCollectionApprovalResult {
+    check_request(&arg.memo, arg.created_at_time, now)?;
+
+    let holder = Account {
+        owner: caller,
+        subaccount: arg.from_subaccount,
+    };
+    let revoked = COLLECTION_APPROVALS.with(|approvals| {
+        let mut approvals = approvals.borrow_mut();
+        let revoked = approvals.get_mut(&holder).is_some_and(|account_approvals| {
+            remove_approvals(account_approvals, &arg.spender, now)
+        });
+        if approvals.get(&holder).is_some_and(Vec::is_empty) {
+            approvals.remove(&holder);
+        }
+        revoked
+    });
+    if !revoked {
+        return Err(RevokeCollectionApprovalError::ApprovalDoesNotExist);
+    }
+
+    let tx = revocation_fields(&holder, &arg.spender, &arg.memo, arg.created_at_time);
+    Ok(blocks::append_block(blocks::REVOKE_COLLECTION, tx).into())
+}
+
+// Transfer tokens on behalf of their holder using a token or collection approval
+#[ic_cdk::update]
+pub fn icrc37_transfer_from(args: Vec<TransferFromArg>) -> Vec<Option<TransferFromResult>> {
+    if let Some(message) = batch_size_error(args.len()) {
+        return vec![Some(Err(TransferError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message,
+        }))];
+    }
+    let caller = caller();
+    let now = time();
+    super::prune_recent_transfers(now);
+
+    args.into_iter()
+        .map(|arg| Some(transfer_from(caller, arg, now)))
+        .collect()
+}
+
+fn transfer_from(caller: Principal, arg: TransferFromArg, now: u64) -> TransferFromResult {
+    check_request(&arg.memo, arg.created_at_time, now)?;
+    let dedup_key = arg
+        .created_at_time
+        .map(|created_at_time| (request_hash(caller, &arg), created_at_time));
+    check_duplicate(dedup_key)?;
+
+    if arg.from == arg.to {
+        return Err(TransferError::InvalidRecipient);
+    }
+    let (token_id, owner) = token_owner(&arg.token_id).ok_or(TransferError::NonExistingTokenId)?;
+    let spender = Account {
+        owner: caller,
+        subaccount: arg.spender_subaccount,
+    };
+    if owner != arg.from || !is_approved(token_id, &owner, &spender, now) {
+        return Err(TransferError::Unauthorized);
+    }
+    policy::check_transfer(token_id, &arg.to, now)?;
+
+    let transfer = Transfer {
+        token_id,
+        from: arg.from,
+        to: arg.to,
+        spender: Some(spender),
+        memo: arg.memo,
+        created_at_time: arg.created_at_time,
+    };
+    Ok(complete_transfer(transfer, dedup_key).into())
+}
+
+// Whether each spender may currently transfer the token from the given subaccount
+#[ic_cdk::query]
+pub fn icrc37_is_approved(args: Vec<IsApprovedArg>) -> Vec<bool> {
+    check_query_batch(args.len());
+    let now = time();
+
+    args.into_iter()
+        .map(|arg| match token_owner(&arg.token_id) {
+            Some((token_id, owner)) => {
+                owner.subaccount == arg.from_subaccount
+                    && is_approved(token_id, &owner, &arg.spender, now)
+            }
+            None => false,
+        })
+        .collect()
+}
+
+// Active approvals on a token, ordered by grant, paginated after `prev`
+#[ic_cdk::query]
+pub fn icrc37_get_token_approvals(
+    token_id: Nat,
+    prev: Option<TokenApproval>,
+    take: Option<Nat>,
+) -> Vec<TokenApproval> {
+    let Some(id) = token_id_from_nat(&token_id) else {
+        return Vec::new();
+    };
+    let approvals = TOKEN_APPROVALS
+        .with(|approvals| approvals.borrow().get(&id).cloned())
+        .unwrap_or_default();
+
+    page(approvals, prev.map(|prev| prev.approval_info.spender), take)
+        .into_iter()
+        .map(|approval_info| TokenApproval {
+            token_id: token_id.clone(),
+            approval_info,
+        })
+        .collect()
+}
+
+// Active collection approvals of an account, ordered by grant, paginated after `prev`
+#[ic_cdk::query]
+pub fn icrc37_get_collection_approvals(
+    owner: Account,
+    prev: Option<CollectionApproval>,
+    take: Option<Nat>,
+) -> Vec<CollectionApproval> {
+    let approvals = COLLECTION_APPROVALS
+        .with(|approvals| approvals.borrow().get(&owner).cloned())
+        .unwrap_or_default();
+
+    page(approvals, prev.map(|prev| prev.spender), take)
+}
+
+// Approvals granted by the previous holder do not carry over to the new one
+pub(super) fn clear_token_approvals(token_id: u64) {
+    TOKEN_APPROVALS.with(|approvals| {
+        approvals.borrow_mut().remove(&token_id);
+    });
+}
+
+// Block fields of an approval: holder, spender, expiry, memo and created_at_time
+fn approval_fields(holder: &Account, approval: &ApprovalInfo) -> Vec<(String, Value)> {
+    let mut tx = vec![
+        ("from".to_string(), blocks::account_value(holder)),
+        (
+            "spender".to_string(),
+            blocks::account_value(&approval.spender),
+        ),
+    ];
+    if let Some(expires_at) = approval.expires_at {
+        tx.push(("exp".to_string(), Value::Nat(expires_at.into())));
+    }
+    blocks::push_request_fields(&mut tx, &approval.memo, Some(approval.created_at_time));
+    tx
+}
+
+// Block fields of a revocation; the spender is omitted when all approvals are revoked
+fn revocation_fields(
+    holder: &Account,
+    spender: &Option<Account>,
+    memo: &Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+) -> Vec<(String, Value)> {
+    let mut tx = vec![("from".to_string(), blocks::account_value(holder))];
+    if let Some(spender) = spender {
+        tx.push(("spender".to_string(), blocks::account_value(spender)));
+    }
+    blocks::push_request_fields(&mut tx, memo, created_at_time);
+    tx
+}
+
+fn is_approved(token_id: u64, owner: &Account, spender: &Account, now: u64) -> bool {
+    let covers = |approvals: Option<&Vec<ApprovalInfo>>| {
+        approvals.is_some_and(|approvals| {
+            approvals
+                .iter()
+                .any(|approval| approval.spender == *spender && is_active(approval, now))
+        })
+    };
+    TOKEN_APPROVALS.with(|approvals| covers(approvals.borrow().get(&token_id)))
+        || COLLECTION_APPROVALS.with(|approvals| covers(approvals.borrow().get(owner)))
+}
+
+fn is_active(approval: &ApprovalInfo, now: u64) -> bool {
+    approval
+        .expires_at
+        .is_none_or(|expires_at| expires_at > now)
+}
+
+fn check_approval(approval: &ApprovalInfo, now: u64) -> Result<(), ApprovalCheckError> {
+    check_request(&approval.memo, Some(approval.created_at_time), now)
+        .map_err(ApprovalCheckError::Request)?;
+    if !is_active(approval, now) {
+        return Err(ApprovalCheckError::AlreadyExpired);
+    }
+    Ok(())
+}
+
+// Problems with an approval shared by token and collection approvals
+enum ApprovalCheckError {
+    Request(super::RequestError),
+    AlreadyExpired,
+}
+
+impl From<ApprovalCheckError> for ApproveTokenError {
+    fn from(error: ApprovalCheckError) -> Self {
+        match error {
+            ApprovalCheckError::Request(error) => error.into(),
+            ApprovalCheckError::AlreadyExpired => ApproveTokenError::GenericError {
+                error_code: ALREADY_EXPIRED_ERROR_CODE.into(),
+                message: "Approval expires_at is in the past".to_string(),
+            },
+        }
+    }
+}
+
+impl From<ApprovalCheckError> for ApproveCollectionError {
+    fn from(error: ApprovalCheckError) -> Self {
+        match error {
+            ApprovalCheckError::Request(error) => error.into(),
+            ApprovalCheckError::AlreadyExpired => ApproveCollectionError::GenericError {
+                error_code: ALREADY_EXPIRED_ERROR_CODE.into(),
+                message: "Approval expires_at is in the past".to_string(),
+            },
+        }
+    }
+}
+
+// Add or replace the approval of a spender, dropping expired ones first
+fn insert_approval(
+    approvals: &mut Vec<ApprovalInfo>,
+    approval: ApprovalInfo,
+    now: u64,
+) -> Result<(), String> {
+    approvals.retain(|existing| is_active(existing, now) && existing.spender != approval.spender);
+    if approvals.len() as u64 >= MAX_APPROVALS {
+        return Err(format!(
+            "Too many approvals: at most {MAX_APPROVALS} per token or account"
+        ));
+    }
+    approvals.push(approval);
+    Ok(())
+}
+
+// Remove the approval of one spender (or all approvals); true if an active one was removed
+fn remove_approvals(
+    approvals: &mut Vec<ApprovalInfo>,
+    spender: &Option<Account>,
+    now: u64,
+) -> bool {
+    let mut revoked = false;
+    approvals.retain(|approval| {
+        let matches = spender
+            .as_ref()
+            .is_none_or(|spender| approval.spender == *spender);
+        if matches && is_active(approval, now) {
+            revoked = true;
+        }
+        !matches && is_active(approval, now)
+    });
+    revoked
+}
+
+// Active approvals after the one granted to `prev_spender`, at most `take` long
+fn page(
+    approvals: Vec<ApprovalInfo>,
+    prev_spender: Option<Account>,
+    take: Option<Nat>,
+) -> Vec<ApprovalInfo> {
+    let now = time();
+    let take = take
+        .and_then(|take| token_id_from_nat(&take))
+        .unwrap_or(DEFAULT_TAKE_VALUE)
+        .min(MAX_TAKE_VALUE);
+    let start = prev_spender
+        .and_then(|prev| {
+            approvals
+                .iter()
+                .position(|approval| approval.spender == prev)
+        })
+        .map_or(0, |position| position + 1);
+
+    approvals
+        .into_iter()
+        .skip(start)
+        .filter(|approval| is_active(approval, now))
+        .take(take as usize)
+        .collect()
+}
+
+fn batch_size_error(len: usize) -> Option<String> {
+    if len == 0 || len as u64 > MAX_UPDATE_BATCH_SIZE {
+        Some(format!(
+            "Batch must contain between 1 and {MAX_UPDATE_BATCH_SIZE} entries"
+        ))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,252 @@
+use super::blocks;
+use crate::modules::certificates::require_admin;
+use crate::types::{ArchiveConfig, ArchiveFailure, ArchiveInfo, GetArchivesArgs};
+use candid::{CandidType, Nat, Principal};
+use ic_cdk::api::management_canister::main::{
+    create_canister, install_code, CanisterInstallMode, CanisterSettings, CreateCanisterArgument,
+    InstallCodeArgument,
+};
+use serde::Deserialize;
+use std::cell::RefCell;
+
+// =============================================================================
+// ICRC-3 archives
+// =============================================================================
+//
+// Once the local log holds more than `trigger_threshold` blocks, the oldest
+// `num_blocks_to_archive` are copied to an archive canister (built from
+// src/archive) and dropped locally. Archives are spawned on demand from the
+// Wasm uploaded by an admin and filled in order, so each one holds a
+// contiguous range of block indexes.
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+// An archive canister and the range of blocks it holds
+#[derive(Clone, Debug)]
+pub(super) struct Archive {
+    pub canister_id: Principal,
+    pub start: u64,
+    pub block_count: u64,
+}
+
+// Init arguments of the archive canister
+#[derive(CandidType, Deserialize)]
+struct ArchiveInitArgs {
+    ledger_id: Principal,
+    first_index: u64,
+    max_blocks: u64,
+}
+
+thread_local! {
+    static ARCHIVE_WASM: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+    static ARCHIVE_CONFIG: RefCell<ArchiveConfig> = const {
+        RefCell::new(ArchiveConfig {
+            trigger_threshold: 2000,
+            num_blocks_to_archive: 1000,
+            max_blocks_per_archive: 1_000_000,
+            cycles_for_archive_creation: 2_000_000_000_000,
+        })
+    };
+    static ARCHIVES: RefCell<Vec<Archive>> = const { RefCell::new(Vec::new()) };
+    // Set while a round of archiving is awaiting other canisters
+    static ARCHIVING: RefCell<bool> = const { RefCell::new(false) };
+    // Most recent failed round, cleared once a chunk is archived again
+    static LAST_ARCHIVE_FAILURE: RefCell<Option<ArchiveFailure>> = const { RefCell::new(None) };
+}
+
+// Upload the archive canister Wasm (raw or gzipped); archiving stays off until this is set
+#[ic_cdk::update]
+pub fn set_icrc3_archive_wasm(wasm: Vec<u8>) -> Result<(), String> {
+    require_admin()?;
+    if !wasm.starts_with(WASM_MAGIC) && !wasm.starts_with(GZIP_MAGIC) {
+        return Err("Archive module must be a Wasm or gzipped Wasm binary".to_string());
+    }
+    ARCHIVE_WASM.with(|archive_wasm| *archive_wasm.borrow_mut() = Some(wasm));
+    maybe_archive();
+    Ok(())
+}
+
+#[ic_cdk::update]
+pub fn set_icrc3_archive_config(config: ArchiveConfig) -> Result<(), String> {
+    require_admin()?;
+    if config.num_blocks_to_archive == 0 {
+        return Err("num_blocks_to_archive must be at least 1".to_string());
+    }
+    if config.max_blocks_per_archive < config.num_blocks_to_archive {
+        return Err("max_blocks_per_archive must be at least num_blocks_to_archive".to_string());
+    }
+    ARCHIVE_CONFIG.with(|archive_config| *archive_config.borrow_mut() = config);
+    maybe_archive();
+    Ok(())
+}
+
+#[ic_cdk::query]
+pub fn get_icrc3_archive_config() -> ArchiveConfig {
+    ARCHIVE_CONFIG.with(|config| config.borrow().clone())
+}
+
+// Why the last round of archiving stopped, if the log has not been archived since (admin only)
+#[ic_cdk::query]
+pub fn get_icrc3_archive_failure() -> Result<Option<ArchiveFailure>, String> {
+    require_admin()?;
+    Ok(LAST_ARCHIVE_FAILURE.with(|failure| failure.borrow().clone()))
+}
+
+// Archives holding blocks, oldest first; `from` skips up to and including that archive
+#[ic_cdk::query]
+pub fn icrc3_get_archives(args: GetArchivesArgs) -> Vec<ArchiveInfo> {
+    let archives = archives();
+    let skip = args
+        .from
+        .and_then(|from| {
+            archives
+                .iter()
+                .position(|archive| archive.canister_id == from)
+        })
+        .map_or(0, |position| position + 1);
+
+    archives
+        .into_iter()
+        .skip(skip)
+        .map(|archive| ArchiveInfo {
+            canister_id: archive.canister_id,
+            start: Nat::from(archive.start),
+            end: Nat::from(archive.start + archive.block_count - 1),
+        })
+        .collect()
+}
+
+// Archives that hold at least one block
+pub(super) fn archives() -> Vec<Archive> {
+    ARCHIVES.with(|archives| {
+        archives
+            .borrow()
+            .iter()
+            .filter(|archive| archive.block_count > 0)
+            .cloned()
+            .collect()
+    })
+}
+
+// Start a round of archiving in the background if the local log is over the threshold
+pub(super) fn maybe_archive() {
+    let threshold = ARCHIVE_CONFIG.with(|config| config.borrow().trigger_threshold);
+    let has_wasm = ARCHIVE_WASM.with(|wasm| wasm.borrow().is_some());
+    if !has_wasm || blocks::local_block_count() <= threshold {
+        return;
+    }
+    if ARCHIVING.with(|archiving| archiving.replace(true)) {
+        return;
+    }
+
+    ic_cdk::spawn(async {
+        let threshold = ARCHIVE_CONFIG.with(|config| config.borrow().trigger_threshold);
+        while blocks::local_block_count() > threshold {
+            if let Err(error) = archive_oldest_blocks().await {
+                LAST_ARCHIVE_FAILURE.with(|failure| {
+                    *failure.borrow_mut() = Some(ArchiveFailure {
+                        failed_at: ic_cdk::api::time(),
+                        error,
+                    })
+                });
+                break;
+            }
+            LAST_ARCHIVE_FAILURE.with(|failure| *failure.borrow_mut() = None);
+        }
+        ARCHIVING.with(|archiving| *archiving.borrow_mut() = false);
+    });
+}
+
+// Move one chunk of the oldest local blocks to an archive
+async fn archive_oldest_blocks() -> Result<(), String> {
+    let config = get_icrc3_archive_config();
+    let (start, chunk) = blocks::oldest_blocks(config.num_blocks_to_archive);
+    let count = chunk.len() as u64;
+    if count == 0 {
+        return Ok(());
+    }
+
+    let archive_id = match open_archive(start, count, config.max_blocks_per_archive) {
+        Some(canister_id) => canister_id,
+        None => create_archive(start, &config).await?,
+    };
+
+    let (result,): (Result<(), String>,) =
+        ic_cdk::call(archive_id, "append_blocks", (start, chunk))
+            .await
+            .map_err(|(_, e)| format!("Failed to append blocks to archive: {e}"))?;
+    result?;
+
+    // Only this task removes blocks from the front, so the chunk is still there
+    blocks::drop_oldest_blocks(count);
+    ARCHIVES.with(|archives| {
+        if let Some(archive) = archives
+            .borrow_mut()
+            .iter_mut()
+            .find(|archive| archive.canister_id == archive_id)
+        {
+            archive.block_count += count;
+        }
+    });
+    Ok(())
+}
+
+// The newest archive, if it continues at `start` and has room for `count` more blocks
+fn open_archive(start: u64, count: u64, max_blocks: u64) -> Option<Principal> {
+    ARCHIVES.with(|archives| {
+        archives
+            .borrow()
+            .last()
+            .filter(|archive| {
+                archive.start + archive.block_count == start
+                    && archive.block_count + count <= max_blocks
+            })
+            .map(|archive| archive.canister_id)
+    })
+}
+
+// Create and install a new archive canister whose first block is `start`
+async fn create_archive(start: u64, config: &ArchiveConfig) -> Result<Principal, String> {
+    let wasm_module = ARCHIVE_WASM
+        .with(|wasm| wasm.borrow().clone())
+        .ok_or_else(|| "Archive Wasm is not set".to_string())?;
+
+    let settings = CanisterSettings {
+        controllers: Some(vec![ic_cdk::id()]),
+        ..Default::default()
+    };
+    let (record,) = create_canister(
+        CreateCanisterArgument {
+            settings: Some(settings),
+        },
+        config.cycles_for_archive_creation as u128,
+    )
+    .await
+    .map_err(|(_, e)| format!("Failed to create archive canister: {e}"))?;
+    let canister_id = record.canister_id;
+
+    let init_args = ArchiveInitArgs {
+        ledger_id: ic_cdk::id(),
+        first_index: start,
+        max_blocks: config.max_blocks_per_archive,
+    };
+    install_code(InstallCodeArgument {
+        mode: CanisterInstallMode::Install,
+        canister_id,
+        wasm_module,
+        arg: candid::encode_one(init_args).map_err(|e| e.to_string())?,
+    })
+    .await
+    .map_err(|(_, e)| format!("Failed to install archive canister: {e}"))?;
+
+    // Recorded empty until the first chunk lands, so a failed append is retried here
+    ARCHIVES.with(|archives| {
+        archives.borrow_mut().push(Archive {
+            canister_id,
+            start,
+            block_count: 0,
+        })
+    });
+    Ok(canister_id)
+}
@@ -0,0 +1,84 @@
+use super::{get_user_nfts, icrc7_balance_of, icrc7_tokens, icrc7_tokens_of, insert_token, TOKENS};
+use crate::types::{Account, Token, TokenMetadata, TransferPolicy};
+use candid::{Nat, Principal};
+use ic_cdk::api::performance_counter;
+
+// =============================================================================
+// Ownership query benchmark (built with `--features bench`)
+// =============================================================================
+//
+// bench_nft_ownership(size) grows the collection to `size` tokens spread over
+// 1000 holders, with one collector holding the first 10, and reports the
+// instructions each ownership query takes for the collector. The full scan
+// the owner index replaced is measured alongside as the baseline.
+
+const HOLDERS: u64 = 1_000;
+const COLLECTOR_TOKENS: u64 = 10;
+// Tokens inserted per call (each also renders and certifies its metadata
+// documents), to stay within the instruction limit
+const MAX_SEED_PER_CALL: u64 = 20_000;
+
+#[ic_cdk::update]
+pub fn bench_nft_ownership(size: u64) -> Vec<(String, u64)> {
+    let collector = holder(HOLDERS);
+    let existing = TOKENS.with(|tokens| tokens.borrow().len() as u64);
+    let target = size.min(existing + MAX_SEED_PER_CALL);
+    for id in existing + 1..=target {
+        let owner = if id <= COLLECTOR_TOKENS {
+            collector.clone()
+        } else {
+            holder(id % HOLDERS)
+        };
+        insert_token(Token {
+            id,
+            owner,
+            metadata: TokenMetadata {
+                name: format!("Token #{id}"),
+                description: None,
+                image: None,
+                attributes: Vec::new(),
+            },
+            created_at: 0,
+            session_id: None,
+            transfer_policy: TransferPolicy::Transferable,
+        });
+    }
+
+    vec![
+        measure("tokens", target, || {
+            icrc7_tokens(Some(Nat::from(target - 100)), None)
+        }),
+        measure("balance_of", target, || {
+            icrc7_balance_of(vec![collector.clone()])
+        }),
+        measure("balance_of_scan", target, || {
+            TOKENS.with(|tokens| {
+                tokens
+                    .borrow()
+                    .values()
+                    .filter(|token| token.owner == collector)
+                    .count()
+            })
+        }),
+        measure("tokens_of", target, || {
+            icrc7_tokens_of(collector.clone(), None, None)
+        }),
+        measure("user_nfts", target, || get_user_nfts(collector.owner)),
+    ]
+}
+
+fn holder(index: u64) -> Account {
+    let mut bytes = [0u8; 29];
+    bytes[..8].copy_from_slice(&index.to_be_bytes());
+    Account {
+        owner: Principal::from_slice(&bytes),
+        subaccount: None,
+    }
+}
+
+// Instructions spent in `f`, labelled with the collection size
+fn measure<R>(name: &str, size: u64, f: impl FnOnce() -> R) -> (String, u64) {
+    let start = performance_counter(0);
+    std::hint::black_box(f());
+    (format!("{name}@{size}"), performance_counter(0) - start)
+}
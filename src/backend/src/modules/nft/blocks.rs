@@ -0,0 +1,257 @@
+use super::archive;
+use super::token_id_from_nat;
+use crate::modules::certification;
+use crate::types::{
+    Account, ArchivedBlocks, BlockWithId, DataCertificate, GetBlocksCallback, GetBlocksRequest,
+    GetBlocksResult, SupportedBlockType, Value,
+};
+use candid::Nat;
+use ic_cdk::api::time;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+// =============================================================================
+// ICRC-3 transaction log
+// =============================================================================
+//
+// Every mint, transfer, burn and approval change appends a block. Blocks are
+// ICRC-3 `Value` maps {btype, ts, phash, tx}; each carries the hash of its
+// predecessor (phash), so the chain is tamper-evident, and the hash and index
+// of the newest block are certified so clients can verify the tip. Older
+// blocks are moved to archive canisters (see archive.rs); only the blocks from
+// FIRST_LOCAL_INDEX onwards are held here.
+
+const ICRC7_URL: &str = "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md";
+const ICRC37_URL: &str = "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md";
+
+// Block types written to the log
+pub(super) const MINT: &str = "7mint";
+pub(super) const BURN: &str = "7burn";
+pub(super) const TRANSFER: &str = "7xfer";
+pub(super) const APPROVE: &str = "37approve";
+pub(super) const APPROVE_COLLECTION: &str = "37approve_coll";
+pub(super) const REVOKE: &str = "37revoke";
+pub(super) const REVOKE_COLLECTION: &str = "37revoke_coll";
+pub(super) const TRANSFER_FROM: &str = "37xfer";
+
+// Most blocks returned by a single icrc3_get_blocks call
+const MAX_BLOCKS_PER_RESPONSE: u64 = 1000;
+
+thread_local! {
+    static BLOCKS: RefCell<VecDeque<Value>> = const { RefCell::new(VecDeque::new()) };
+    // Index of the oldest block still held locally
+    static FIRST_LOCAL_INDEX: RefCell<u64> = const { RefCell::new(0) };
+    static LAST_BLOCK_HASH: RefCell<Option<[u8; 32]>> = const { RefCell::new(None) };
+}
+
+// Blocks in the requested ranges, in request order; archived ranges point to their archive
+#[ic_cdk::query]
+pub fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    let archives = archive::archives();
+    let first_local = FIRST_LOCAL_INDEX.with(|first| *first.borrow());
+    let mut archived: Vec<(candid::Principal, Vec<GetBlocksRequest>)> = Vec::new();
+
+    BLOCKS.with(|blocks| {
+        let blocks = blocks.borrow();
+        let log_length = first_local + blocks.len() as u64;
+        let mut budget = MAX_BLOCKS_PER_RESPONSE;
+        let mut found = Vec::new();
+
+        for range in args {
+            let Some(start) = token_id_from_nat(&range.start) else {
+                continue;
+            };
+            let end = token_id_from_nat(&range.length)
+                .map_or(u64::MAX, |length| start.saturating_add(length))
+                .min(log_length);
+
+            for archive in &archives {
+                let archive_end = archive.start + archive.block_count;
+                let (from, to) = (start.max(archive.start), end.min(archive_end));
+                if from >= to {
+                    continue;
+                }
+                let request = GetBlocksRequest {
+                    start: Nat::from(from),
+                    length: Nat::from(to - from),
+                };
+                match archived
+                    .iter_mut()
+                    .find(|(id, _)| *id == archive.canister_id)
+                {
+                    Some((_, requests)) => requests.push(request),
+                    None => archived.push((archive.canister_id, vec![request])),
+                }
+            }
+
+            let local_start = start.max(first_local);
+            let local_end = end.min(local_start.saturating_add(budget));
+            for id in local_start..local_end {
+                found.push(BlockWithId {
+                    id: Nat::from(id),
+                    block: blocks[(id - first_local) as usize].clone(),
+                });
+            }
+            budget -= local_end.saturating_sub(local_start);
+        }
+
+        GetBlocksResult {
+            log_length: Nat::from(log_length),
+            blocks: found,
+            archived_blocks: archived
+                .into_iter()
+                .map(|(canister_id, args)| ArchivedBlocks {
+                    args,
+                    callback: GetBlocksCallback::new(canister_id, "icrc3_get_blocks".to_string()),
+                })
+                .collect(),
+        }
+    })
+}
+
+// Certificate and witness for the hash and index of the newest block
+#[ic_cdk::query]
+pub fn icrc3_get_tip_certificate() -> Option<DataCertificate> {
+    Some(DataCertificate {
+        certificate: ic_cdk::api::data_certificate()?,
+        hash_tree: certification::block_tip_witness()?,
+    })
+}
+
+#[ic_cdk::query]
+pub fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    [
+        (MINT, ICRC7_URL),
+        (BURN, ICRC7_URL),
+        (TRANSFER, ICRC7_URL),
+        (APPROVE, ICRC37_URL),
+        (APPROVE_COLLECTION, ICRC37_URL),
+        (REVOKE, ICRC37_URL),
+        (REVOKE_COLLECTION, ICRC37_URL),
+        (TRANSFER_FROM, ICRC37_URL),
+    ]
+    .into_iter()
+    .map(|(block_type, url)| SupportedBlockType {
+        block_type: block_type.to_string(),
+        url: url.to_string(),
+    })
+    .collect()
+}
+
+// Append a block and certify it as the new tip; returns its index
+pub(super) fn append_block(btype: &str, tx: Vec<(String, Value)>) -> u64 {
+    let mut block = vec![
+        ("btype".to_string(), Value::Text(btype.to_string())),
+        ("ts".to_string(), Value::Nat(time().into())),
+    ];
+    if let Some(phash) = LAST_BLOCK_HASH.with(|hash| *hash.borrow()) {
+        block.push(("phash".to_string(), Value::Blob(phash.to_vec())));
+    }
+    block.push(("tx".to_string(), Value::Map(tx)));
+    let block = Value::Map(block);
+
+    let hash = hash_value(&block);
+    let first_local = FIRST_LOCAL_INDEX.with(|first| *first.borrow());
+    let index = BLOCKS.with(|blocks| {
+        let mut blocks = blocks.borrow_mut();
+        blocks.push_back(block);
+        first_local + blocks.len() as u64 - 1
+    });
+    LAST_BLOCK_HASH.with(|last| *last.borrow_mut() = Some(hash));
+    certification::certify_block_tip(index, hash);
+    archive::maybe_archive();
+    index
+}
+
+// Number of blocks held in this canister
+pub(super) fn local_block_count() -> u64 {
+    BLOCKS.with(|blocks| blocks.borrow().len() as u64)
+}
+
+// Index of the oldest local block and copies of up to `count` blocks from it
+pub(super) fn oldest_blocks(count: u64) -> (u64, Vec<Value>) {
+    let first_local = FIRST_LOCAL_INDEX.with(|first| *first.borrow());
+    let blocks = BLOCKS.with(|blocks| {
+        blocks
+            .borrow()
+            .iter()
+            .take(count as usize)
+            .cloned()
+            .collect()
+    });
+    (first_local, blocks)
+}
+
+// Drop the oldest `count` local blocks once they are held by an archive
+pub(super) fn drop_oldest_blocks(count: u64) {
+    BLOCKS.with(|blocks| {
+        blocks.borrow_mut().drain(..count as usize);
+    });
+    FIRST_LOCAL_INDEX.with(|first| *first.borrow_mut() += count);
+}
+
+// Accounts are encoded as [owner] or [owner, subaccount]
+pub(super) fn account_value(account: &Account) -> Value {
+    let mut parts = vec![Value::Blob(account.owner.as_slice().to_vec())];
+    if let Some(subaccount) = &account.subaccount {
+        parts.push(Value::Blob(subaccount.clone()));
+    }
+    Value::Array(parts)
+}
+
+// Optional memo and created_at_time shared by user-initiated blocks
+pub(super) fn push_request_fields(
+    tx: &mut Vec<(String, Value)>,
+    memo: &Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+) {
+    if let Some(memo) = memo {
+        tx.push(("memo".to_string(), Value::Blob(memo.clone())));
+    }
+    if let Some(created_at_time) = created_at_time {
+        tx.push(("ts".to_string(), Value::Nat(created_at_time.into())));
+    }
+}
+
+// ICRC-3 representation-independent hash of a value
+pub fn hash_value(value: &Value) -> [u8; 32] {
+    match value {
+        Value::Blob(bytes) => Sha256::digest(bytes).into(),
+        Value::Text(text) => Sha256::digest(text.as_bytes()).into(),
+        Value::Nat(nat) => {
+            let mut leb128 = Vec::new();
+            nat.encode(&mut leb128).ok();
+            Sha256::digest(leb128).into()
+        }
+        Value::Int(int) => {
+            let mut sleb128 = Vec::new();
+            int.encode(&mut sleb128).ok();
+            Sha256::digest(sleb128).into()
+        }
+        Value::Array(values) => {
+            let mut hasher = Sha256::new();
+            for value in values {
+                hasher.update(hash_value(value));
+            }
+            hasher.finalize().into()
+        }
+        Value::Map(entries) => {
+            let mut pairs: Vec<[u8; 64]> = entries
+                .iter()
+                .map(|(key, value)| {
+                    let mut pair = [0u8; 64];
+                    pair[..32].copy_from_slice(&Sha256::digest(key.as_bytes()));
+                    pair[32..].copy_from_slice(&hash_value(value));
+                    pair
+                })
+                .collect();
+            pairs.sort();
+            let mut hasher = Sha256::new();
+            for pair in pairs {
+                hasher.update(pair);
+            }
+            hasher.finalize().into()
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::{COLLECTION_METADATA, NANOS_PER_SECOND};
+use crate::modules::http::{publish_asset, unpublish_asset};
+use crate::types::{Token, TransferPolicy};
+use serde_json::{json, Map, Value as JsonValue};
+
+// =============================================================================
+// Token metadata served at the token URI, for marketplaces and indexers
+// =============================================================================
+//
+// Documents follow the ERC-721 metadata / OpenSea layout and are re-rendered and
+// certified whenever a token or the collection changes:
+//   /nft/{id}/metadata  token JSON (name, description, image, attributes;
+//                       OpenSea `date` attributes are in seconds)
+//   /collection.json    collection-level JSON
+//
+// Images are user-supplied URLs, so they only appear as the `image` field;
+// the canister never serves a certified redirect to them.
+
+const PUBLIC_URL: &str = "https://originstamp.ic0.app";
+
+// URI stored on certificates and advertised to indexers for a token
+pub fn token_uri(token_id: u64) -> String {
+    format!("{PUBLIC_URL}{}", metadata_path(token_id))
+}
+
+fn metadata_path(token_id: u64) -> String {
+    format!("/nft/{token_id}/metadata")
+}
+
+pub(super) fn publish_token_documents(token: &Token) {
+    let mut document = Map::new();
+    document.insert("name".to_string(), json!(token.metadata.name));
+    insert_some(&mut document, "description", &token.metadata.description);
+    insert_some(&mut document, "image", &token.metadata.image);
+    if let Some(certificate_id) = token
+        .metadata
+        .attributes
+        .iter()
+        .find(|attribute| attribute.trait_type == "certificate_id")
+    {
+        document.insert(
+            "external_url".to_string(),
+            json!(format!("{PUBLIC_URL}/verify/{}", certificate_id.value)),
+        );
+    }
+    let mut attributes: Vec<JsonValue> = token
+        .metadata
+        .attributes
+        .iter()
+        .map(|attribute| json!({ "trait_type": attribute.trait_type, "value": attribute.value }))
+        .collect();
+    attributes.push(json!({
+        "trait_type": "transfer_policy",
+        "value": token.transfer_policy.label(),
+    }));
+    if let TransferPolicy::LockedUntil(unlock_at) = token.transfer_policy {
+        attributes.push(json!({
+            "display_type": "date",
+            "trait_type": "transfer_locked_until",
+            "value": unlock_at / NANOS_PER_SECOND,
+        }));
+    }
+    document.insert("attributes".to_string(), JsonValue::Array(attributes));
+    publish_asset(
+        &metadata_path(token.id),
+        "application/json",
+        serde_json::to_vec_pretty(&document).unwrap_or_default(),
+    );
+}
+
+pub(super) fn unpublish_token_documents(token_id: u64) {
+    unpublish_asset(&metadata_path(token_id));
+}
+
+pub fn publish_collection_document() {
+    let collection = COLLECTION_METADATA.with(|metadata| metadata.borrow().clone());
+    let mut document = Map::new();
+    document.insert("name".to_string(), json!(collection.name));
+    document.insert("symbol".to_string(), json!(collection.symbol));
+    insert_some(&mut document, "description", &collection.description);
+    insert_some(&mut document, "image", &collection.image);
+    document.insert("external_link".to_string(), json!(PUBLIC_URL));
+    document.insert("total_supply".to_string(), json!(collection.total_supply));
+    document.insert(
+        "transfer_policy".to_string(),
+        json!(collection.transfer_policy.label()),
+    );
+    if let Some(max_supply) = collection.max_supply {
+        document.insert("max_supply".to_string(), json!(max_supply));
+    }
+    publish_asset(
+        "/collection.json",
+        "application/json",
+        serde_json::to_vec_pretty(&document).unwrap_or_default(),
+    );
+}
+
+// Omit unset optional fields rather than serving nulls
+fn insert_some(document: &mut Map<String, JsonValue>, key: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        document.insert(key.to_string(), json!(value));
+    }
+}
@@ -1,28 +1,80 @@
 use crate::modules::physical_art;
 use crate::types::{
-    Account, CollectionMetadata, Token, TokenAttribute, TokenMetadata, TransferRequest,
-    TransferResponse,
+    Account, ApproveCollectionError, ApproveTokenError, BurnArg, BurnError, BurnResult,
+    CertificateStatus, CollectionMetadata, CollectionMetadataChange, RevokeCollectionApprovalError,
+    RevokeTokenApprovalError, SupportedStandard, Token, TokenAttribute, TokenMetadata, TransferArg,
+    TransferError, TransferPolicy, TransferResult, Value,
 };
+use candid::Nat;
 use serde_json;
 use sha2::{Digest, Sha256};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound;
+
+mod approvals;
+mod archive;
+#[cfg(feature = "bench")]
+mod bench;
+mod blocks;
+mod documents;
+mod policy;
+
+pub use approvals::{
+    icrc37_approve_collection, icrc37_approve_tokens, icrc37_get_collection_approvals,
+    icrc37_get_token_approvals, icrc37_is_approved, icrc37_max_approvals_per_token_or_collection,
+    icrc37_max_revoke_approvals, icrc37_metadata, icrc37_revoke_collection_approvals,
+    icrc37_revoke_token_approvals, icrc37_transfer_from,
+};
+pub use archive::{
+    get_icrc3_archive_config, get_icrc3_archive_failure, icrc3_get_archives,
+    set_icrc3_archive_config, set_icrc3_archive_wasm,
+};
+#[cfg(feature = "bench")]
+pub use bench::bench_nft_ownership;
+pub use blocks::{icrc3_get_blocks, icrc3_get_tip_certificate, icrc3_supported_block_types};
+pub use documents::publish_collection_document;
+pub use policy::{
+    get_collection_transfer_policy, get_nft_transfer_approval, set_collection_transfer_policy,
+    set_nft_transfer_approval,
+};
 
 // =============================================================================
 // ICRC-7 NFT Implementation
 // =============================================================================
 
+// Token standard and ledger reported on certificates linked to this collection
+pub const TOKEN_STANDARD: &str = "ICRC-7";
+pub const BLOCKCHAIN: &str = "Internet Computer";
+
+type SubaccountTokens = HashMap<Option<Vec<u8>>, BTreeSet<u64>>;
+
 // NFT storage
 thread_local! {
-    static TOKENS: RefCell<HashMap<u64, Token>> = RefCell::new(HashMap::new());
+    static TOKENS: RefCell<BTreeMap<u64, Token>> = const { RefCell::new(BTreeMap::new()) };
+    // Owner principal -> subaccount -> ids of the tokens held there
+    static OWNER_TOKENS: RefCell<HashMap<candid::Principal, SubaccountTokens>> =
+        RefCell::new(HashMap::new());
     static TOKEN_COUNTER: RefCell<u64> = const { RefCell::new(1) };
+    // (tx_window, permitted_drift) in seconds
+    static TRANSFER_WINDOW: RefCell<(u64, u64)> = const {
+        RefCell::new((DEFAULT_TX_WINDOW_SECONDS, DEFAULT_PERMITTED_DRIFT_SECONDS))
+    };
+    // Hash of a deduplicable transfer -> (created_at_time, transaction index)
+    static RECENT_TRANSFERS: RefCell<HashMap<[u8; 32], (u64, u64)>> = RefCell::new(HashMap::new());
     static COLLECTION_METADATA: RefCell<CollectionMetadata> = RefCell::new(CollectionMetadata {
+        symbol: "OSART".to_string(),
         name: "Origin Stamp Art NFTs".to_string(),
         description: Some("NFTs representing physical art pieces authenticated through Origin Stamp".to_string()),
         image: None,
         total_supply: 0,
         max_supply: None,
+        transfer_policy: TransferPolicy::Transferable,
     });
+    // Principals allowed to mint from any session or certificate
+    static MINTERS: RefCell<HashSet<candid::Principal>> = RefCell::new(HashSet::new());
+    static COLLECTION_METADATA_HISTORY: RefCell<Vec<CollectionMetadataChange>> =
+        const { RefCell::new(Vec::new()) };
 }
 
 // Helper functions
@@ -32,12 +84,181 @@ impl Account {
     }
 }
 
+// Token storage: all writes go through these helpers so the owner index and the
+// published token documents stay in sync
+
+// Store a token and index it under its owner
+fn insert_token(token: Token) {
+    index_owner(&token.owner, token.id);
+    documents::publish_token_documents(&token);
+    TOKENS.with(|tokens| tokens.borrow_mut().insert(token.id, token));
+}
+
+fn remove_token(token_id: u64) -> Option<Token> {
+    let token = TOKENS.with(|tokens| tokens.borrow_mut().remove(&token_id))?;
+    unindex_owner(&token.owner, token_id);
+    documents::unpublish_token_documents(token_id);
+    Some(token)
+}
+
+// Apply a change to a token's metadata and re-publish its documents
+fn update_token_metadata(token_id: u64, change: impl FnOnce(&mut Token)) {
+    TOKENS.with(|tokens| {
+        if let Some(token) = tokens.borrow_mut().get_mut(&token_id) {
+            change(token);
+            documents::publish_token_documents(token);
+        }
+    });
+}
+
+fn adjust_total_supply(change: impl FnOnce(u64) -> u64) {
+    COLLECTION_METADATA.with(|metadata| {
+        let mut collection = metadata.borrow_mut();
+        collection.total_supply = change(collection.total_supply);
+    });
+    documents::publish_collection_document();
+}
+
+fn set_token_owner(token_id: u64, owner: Account) {
+    let previous = TOKENS.with(|tokens| {
+        let mut tokens = tokens.borrow_mut();
+        let token = tokens.get_mut(&token_id)?;
+        Some(std::mem::replace(&mut token.owner, owner.clone()))
+    });
+    if let Some(previous) = previous {
+        unindex_owner(&previous, token_id);
+        index_owner(&owner, token_id);
+    }
+}
+
+fn index_owner(owner: &Account, token_id: u64) {
+    OWNER_TOKENS.with(|index| {
+        index
+            .borrow_mut()
+            .entry(owner.owner)
+            .or_default()
+            .entry(owner.subaccount.clone())
+            .or_default()
+            .insert(token_id);
+    });
+}
+
+fn unindex_owner(owner: &Account, token_id: u64) {
+    OWNER_TOKENS.with(|index| {
+        let mut index = index.borrow_mut();
+        let Some(subaccounts) = index.get_mut(&owner.owner) else {
+            return;
+        };
+        if let Some(token_ids) = subaccounts.get_mut(&owner.subaccount) {
+            token_ids.remove(&token_id);
+            if token_ids.is_empty() {
+                subaccounts.remove(&owner.subaccount);
+            }
+        }
+        if subaccounts.is_empty() {
+            index.remove(&owner.owner);
+        }
+    });
+}
+
 // ICRC-7 Standard Methods
+//
+// Token ids are `nat` on the standard surface and u64 internally; ids that do
+// not fit in a u64 cannot exist and are reported as unknown tokens.
+
+// Largest number of token ids or accounts accepted by a batch query
+pub const MAX_QUERY_BATCH_SIZE: u64 = 100;
+// Largest number of transfers accepted by a batch update
+pub const MAX_UPDATE_BATCH_SIZE: u64 = 20;
+// Page size of icrc7_tokens/icrc7_tokens_of when take is omitted, and its upper bound
+pub const DEFAULT_TAKE_VALUE: u64 = 100;
+pub const MAX_TAKE_VALUE: u64 = 1000;
+
+// Largest memo accepted on a transfer, in bytes
+pub const MAX_MEMO_SIZE: u64 = 32;
+// Deduplication window and allowed clock drift of created_at_time, in seconds
+const DEFAULT_TX_WINDOW_SECONDS: u64 = 24 * 60 * 60;
+const DEFAULT_PERMITTED_DRIFT_SECONDS: u64 = 2 * 60;
+const MAX_TX_WINDOW_SECONDS: u64 = 7 * 24 * 60 * 60;
+const MAX_PERMITTED_DRIFT_SECONDS: u64 = 60 * 60;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+// Error codes reported with GenericBatchError/GenericError
+const BATCH_SIZE_ERROR_CODE: u64 = 1;
+const MEMO_TOO_LARGE_ERROR_CODE: u64 = 2;
+// Codes 3 and 4 are used by ICRC-37 approvals
+const REASON_TOO_LONG_ERROR_CODE: u64 = 5;
+
+// Longest burn reason accepted, in bytes
+const MAX_BURN_REASON_SIZE: usize = 256;
+
+// icrc7_collection_metadata - Returns collection metadata as a map of icrc7:* keys
+#[ic_cdk::query]
+pub fn icrc7_collection_metadata() -> Vec<(String, Value)> {
+    let collection = COLLECTION_METADATA.with(|metadata| metadata.borrow().clone());
 
-// icrc7_collection_metadata - Returns collection metadata
+    let mut metadata = vec![
+        ("icrc7:symbol".to_string(), Value::Text(collection.symbol)),
+        ("icrc7:name".to_string(), Value::Text(collection.name)),
+    ];
+    if let Some(description) = collection.description {
+        metadata.push(("icrc7:description".to_string(), Value::Text(description)));
+    }
+    if let Some(logo) = collection.image {
+        metadata.push(("icrc7:logo".to_string(), Value::Text(logo)));
+    }
+    metadata.push((
+        "icrc7:total_supply".to_string(),
+        Value::Nat(icrc7_total_supply()),
+    ));
+    if let Some(supply_cap) = collection.max_supply {
+        metadata.push((
+            "icrc7:supply_cap".to_string(),
+            Value::Nat(supply_cap.into()),
+        ));
+    }
+    metadata.extend([
+        (
+            "icrc7:max_query_batch_size".to_string(),
+            Value::Nat(MAX_QUERY_BATCH_SIZE.into()),
+        ),
+        (
+            "icrc7:max_update_batch_size".to_string(),
+            Value::Nat(MAX_UPDATE_BATCH_SIZE.into()),
+        ),
+        (
+            "icrc7:default_take_value".to_string(),
+            Value::Nat(DEFAULT_TAKE_VALUE.into()),
+        ),
+        (
+            "icrc7:max_take_value".to_string(),
+            Value::Nat(MAX_TAKE_VALUE.into()),
+        ),
+        (
+            "icrc7:max_memo_size".to_string(),
+            Value::Nat(MAX_MEMO_SIZE.into()),
+        ),
+        (
+            "icrc7:atomic_batch_transfers".to_string(),
+            Value::Text("false".to_string()),
+        ),
+    ]);
+    metadata.extend(collection.transfer_policy.metadata());
+    let (tx_window, permitted_drift) = TRANSFER_WINDOW.with(|window| *window.borrow());
+    metadata.extend([
+        ("icrc7:tx_window".to_string(), Value::Nat(tx_window.into())),
+        (
+            "icrc7:permitted_drift".to_string(),
+            Value::Nat(permitted_drift.into()),
+        ),
+    ]);
+    metadata
+}
+
+// icrc7_symbol - Returns the symbol of the NFT collection
 #[ic_cdk::query]
-pub fn icrc7_collection_metadata() -> CollectionMetadata {
-    COLLECTION_METADATA.with(|metadata| metadata.borrow().clone())
+pub fn icrc7_symbol() -> String {
+    COLLECTION_METADATA.with(|metadata| metadata.borrow().symbol.clone())
 }
 
 // icrc7_name - Returns the name of the NFT collection
@@ -52,159 +273,569 @@ pub fn icrc7_description() -> Option<String> {
     COLLECTION_METADATA.with(|metadata| metadata.borrow().description.clone())
 }
 
+// icrc7_logo - Returns the logo URL of the NFT collection
+#[ic_cdk::query]
+pub fn icrc7_logo() -> Option<String> {
+    COLLECTION_METADATA.with(|metadata| metadata.borrow().image.clone())
+}
+
 // icrc7_total_supply - Returns the total number of tokens
 #[ic_cdk::query]
-pub fn icrc7_total_supply() -> u64 {
-    TOKENS.with(|tokens| tokens.borrow().len() as u64)
+pub fn icrc7_total_supply() -> Nat {
+    TOKENS.with(|tokens| Nat::from(tokens.borrow().len() as u64))
 }
 
 // icrc7_supply_cap - Returns the maximum supply (if any)
 #[ic_cdk::query]
-pub fn icrc7_supply_cap() -> Option<u64> {
-    COLLECTION_METADATA.with(|metadata| metadata.borrow().max_supply)
+pub fn icrc7_supply_cap() -> Option<Nat> {
+    COLLECTION_METADATA.with(|metadata| metadata.borrow().max_supply.map(Nat::from))
 }
 
-// icrc7_tokens - Returns a list of token IDs (paginated)
 #[ic_cdk::query]
-pub fn icrc7_tokens(prev: Option<u64>, take: Option<u64>) -> Vec<u64> {
-    let take = take.unwrap_or(100).min(1000); // Limit to 1000 per request
+pub fn icrc7_max_query_batch_size() -> Option<Nat> {
+    Some(MAX_QUERY_BATCH_SIZE.into())
+}
 
-    TOKENS.with(|tokens| {
-        let tokens_map = tokens.borrow();
-        let mut token_ids: Vec<u64> = tokens_map.keys().cloned().collect();
-        token_ids.sort();
-
-        let start_index = match prev {
-            Some(prev_id) => match token_ids.binary_search(&prev_id) {
-                Ok(idx) => idx + 1,
-                Err(idx) => idx,
-            },
-            None => 0,
-        };
+#[ic_cdk::query]
+pub fn icrc7_max_update_batch_size() -> Option<Nat> {
+    Some(MAX_UPDATE_BATCH_SIZE.into())
+}
 
-        token_ids
-            .into_iter()
-            .skip(start_index)
-            .take(take as usize)
-            .collect()
-    })
+#[ic_cdk::query]
+pub fn icrc7_default_take_value() -> Option<Nat> {
+    Some(DEFAULT_TAKE_VALUE.into())
 }
 
-// icrc7_owner_of - Returns the owner of tokens
 #[ic_cdk::query]
-pub fn icrc7_owner_of(token_ids: Vec<u64>) -> Vec<Option<Account>> {
+pub fn icrc7_max_take_value() -> Option<Nat> {
+    Some(MAX_TAKE_VALUE.into())
+}
+
+#[ic_cdk::query]
+pub fn icrc7_max_memo_size() -> Option<Nat> {
+    Some(MAX_MEMO_SIZE.into())
+}
+
+// Transfers in a batch are applied independently
+#[ic_cdk::query]
+pub fn icrc7_atomic_batch_transfers() -> Option<bool> {
+    Some(false)
+}
+
+// Deduplication window for transfers carrying created_at_time, in seconds
+#[ic_cdk::query]
+pub fn icrc7_tx_window() -> Option<Nat> {
+    TRANSFER_WINDOW.with(|window| Some(window.borrow().0.into()))
+}
+
+// Allowed clock drift between created_at_time and canister time, in seconds
+#[ic_cdk::query]
+pub fn icrc7_permitted_drift() -> Option<Nat> {
+    TRANSFER_WINDOW.with(|window| Some(window.borrow().1.into()))
+}
+
+// Configure the transfer deduplication window and permitted drift (admin only)
+#[ic_cdk::update]
+pub fn set_icrc7_transfer_window(
+    tx_window_seconds: u64,
+    permitted_drift_seconds: u64,
+) -> Result<(), String> {
+    crate::modules::certificates::require_admin()?;
+
+    if tx_window_seconds == 0 || tx_window_seconds > MAX_TX_WINDOW_SECONDS {
+        return Err(format!(
+            "Invalid tx_window: must be between 1-{MAX_TX_WINDOW_SECONDS} seconds"
+        ));
+    }
+    if permitted_drift_seconds > MAX_PERMITTED_DRIFT_SECONDS {
+        return Err(format!(
+            "Invalid permitted_drift: must be at most {MAX_PERMITTED_DRIFT_SECONDS} seconds"
+        ));
+    }
+    TRANSFER_WINDOW.with(|window| {
+        *window.borrow_mut() = (tx_window_seconds, permitted_drift_seconds);
+    });
+    Ok(())
+}
+
+// icrc7_tokens - Returns a list of token IDs (paginated)
+#[ic_cdk::query]
+pub fn icrc7_tokens(prev: Option<Nat>, take: Option<Nat>) -> Vec<Nat> {
+    let Some((start, take)) = page_bounds(prev, take) else {
+        return Vec::new();
+    };
     TOKENS.with(|tokens| {
-        let tokens_map = tokens.borrow();
-        token_ids
-            .into_iter()
-            .map(|id| tokens_map.get(&id).map(|token| token.owner.clone()))
+        tokens
+            .borrow()
+            .range((start, Bound::Unbounded))
+            .take(take)
+            .map(|(id, _)| Nat::from(*id))
             .collect()
     })
 }
 
-// icrc7_balance_of - Returns the balance of tokens for accounts
+// icrc7_owner_of - Returns the owner of tokens
 #[ic_cdk::query]
-pub fn icrc7_balance_of(accounts: Vec<Account>) -> Vec<u64> {
+pub fn icrc7_owner_of(token_ids: Vec<Nat>) -> Vec<Option<Account>> {
+    check_query_batch(token_ids.len());
+
     TOKENS.with(|tokens| {
         let tokens_map = tokens.borrow();
-        accounts
-            .into_iter()
-            .map(|account| {
-                tokens_map
-                    .values()
-                    .filter(|token| token.owner.equals(&account))
-                    .count() as u64
+        token_ids
+            .iter()
+            .map(|id| {
+                token_id_from_nat(id)
+                    .and_then(|id| tokens_map.get(&id))
+                    .map(|token| token.owner.clone())
             })
             .collect()
     })
 }
 
-// icrc7_tokens_of - Returns token IDs owned by accounts
+// icrc7_balance_of - Returns the balance of tokens for accounts
 #[ic_cdk::query]
-pub fn icrc7_tokens_of(account: Account, prev: Option<u64>, take: Option<u64>) -> Vec<u64> {
-    let take = take.unwrap_or(100).min(1000);
+pub fn icrc7_balance_of(accounts: Vec<Account>) -> Vec<Nat> {
+    check_query_batch(accounts.len());
 
-    TOKENS.with(|tokens| {
-        let tokens_map = tokens.borrow();
-        let mut owned_tokens: Vec<u64> = tokens_map
-            .iter()
-            .filter(|(_, token)| token.owner.equals(&account))
-            .map(|(id, _)| *id)
-            .collect();
-        owned_tokens.sort();
-
-        let start_index = match prev {
-            Some(prev_id) => match owned_tokens.binary_search(&prev_id) {
-                Ok(idx) => idx + 1,
-                Err(idx) => idx,
-            },
-            None => 0,
-        };
+    accounts
+        .iter()
+        .map(|account| Nat::from(with_account_tokens(account, |ids| ids.len() as u64)))
+        .collect()
+}
 
-        owned_tokens
-            .into_iter()
-            .skip(start_index)
-            .take(take as usize)
+// icrc7_tokens_of - Returns token IDs owned by accounts
+#[ic_cdk::query]
+pub fn icrc7_tokens_of(account: Account, prev: Option<Nat>, take: Option<Nat>) -> Vec<Nat> {
+    let Some((start, take)) = page_bounds(prev, take) else {
+        return Vec::new();
+    };
+    with_account_tokens(&account, |ids| {
+        ids.range((start, Bound::Unbounded))
+            .take(take)
+            .map(|id| Nat::from(*id))
             .collect()
     })
 }
 
+// Run `f` on the ids held by an account (an empty set if it holds none)
+fn with_account_tokens<R>(account: &Account, f: impl FnOnce(&BTreeSet<u64>) -> R) -> R {
+    OWNER_TOKENS.with(|index| {
+        let index = index.borrow();
+        match index
+            .get(&account.owner)
+            .and_then(|subaccounts| subaccounts.get(&account.subaccount))
+        {
+            Some(ids) => f(ids),
+            None => f(&BTreeSet::new()),
+        }
+    })
+}
+
 // icrc7_token_metadata - Returns metadata for tokens
 #[ic_cdk::query]
-pub fn icrc7_token_metadata(token_ids: Vec<u64>) -> Vec<Option<TokenMetadata>> {
+pub fn icrc7_token_metadata(token_ids: Vec<Nat>) -> Vec<Option<Vec<(String, Value)>>> {
+    check_query_batch(token_ids.len());
+
     TOKENS.with(|tokens| {
         let tokens_map = tokens.borrow();
         token_ids
-            .into_iter()
-            .map(|id| tokens_map.get(&id).map(|token| token.metadata.clone()))
+            .iter()
+            .map(|id| {
+                token_id_from_nat(id)
+                    .and_then(|id| tokens_map.get(&id))
+                    .map(token_metadata)
+            })
             .collect()
     })
 }
 
-// icrc7_transfer - Transfer tokens between accounts
+// icrc7_transfer - Transfer tokens from the caller's account
+//
+// Transfers carrying created_at_time must fall inside the transaction window and
+// are deduplicated within it: resubmitting an identical request returns
+// Duplicate with the index of the original transfer.
 #[ic_cdk::update]
-pub fn icrc7_transfer(requests: Vec<TransferRequest>) -> Vec<TransferResponse> {
+pub fn icrc7_transfer(args: Vec<TransferArg>) -> Vec<Option<TransferResult>> {
+    if args.is_empty() || args.len() as u64 > MAX_UPDATE_BATCH_SIZE {
+        return vec![Some(Err(TransferError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message: format!("Batch must contain between 1 and {MAX_UPDATE_BATCH_SIZE} transfers"),
+        }))];
+    }
     let caller = ic_cdk::api::caller();
+    let now = ic_cdk::api::time();
+    prune_recent_transfers(now);
 
-    TOKENS.with(|tokens| {
-        let mut tokens_map = tokens.borrow_mut();
-
-        requests
-            .into_iter()
-            .map(|request| {
-                // Verify caller is the owner or has permission
-                if request.from.owner != caller {
-                    return TransferResponse {
-                        token_id: request.token_id,
-                        result: Err("Unauthorized: caller is not the owner".to_string()),
-                    };
-                }
+    args.into_iter()
+        .map(|arg| Some(transfer_token(caller, arg, now)))
+        .collect()
+}
 
-                match tokens_map.get_mut(&request.token_id) {
-                    Some(token) => {
-                        if !token.owner.equals(&request.from) {
-                            TransferResponse {
-                                token_id: request.token_id,
-                                result: Err("Token not owned by from account".to_string()),
-                            }
-                        } else {
-                            token.owner = request.to.clone();
-                            TransferResponse {
-                                token_id: request.token_id,
-                                result: Ok(()),
-                            }
-                        }
-                    }
-                    None => TransferResponse {
-                        token_id: request.token_id,
-                        result: Err("Token not found".to_string()),
+fn transfer_token(caller: candid::Principal, arg: TransferArg, now: u64) -> TransferResult {
+    check_request(&arg.memo, arg.created_at_time, now)?;
+    let dedup_key = arg
+        .created_at_time
+        .map(|created_at_time| (request_hash(caller, &arg), created_at_time));
+    check_duplicate(dedup_key)?;
+
+    let from = Account {
+        owner: caller,
+        subaccount: arg.from_subaccount,
+    };
+    if from == arg.to {
+        return Err(TransferError::InvalidRecipient);
+    }
+    let (token_id, owner) = token_owner(&arg.token_id).ok_or(TransferError::NonExistingTokenId)?;
+    if owner != from {
+        return Err(TransferError::Unauthorized);
+    }
+    policy::check_transfer(token_id, &arg.to, now)?;
+
+    let transfer = Transfer {
+        token_id,
+        from,
+        to: arg.to,
+        spender: None,
+        memo: arg.memo,
+        created_at_time: arg.created_at_time,
+    };
+    Ok(complete_transfer(transfer, dedup_key).into())
+}
+
+// A checked transfer, recorded as a 7xfer block (37xfer when made by a spender)
+struct Transfer {
+    token_id: u64,
+    from: Account,
+    to: Account,
+    spender: Option<Account>,
+    memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+}
+
+// Memo and created_at_time problems shared by every ICRC-7/ICRC-37 update; each
+// result type reports them with its own variants
+enum RequestError {
+    MemoTooLarge,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+}
+
+macro_rules! impl_from_request_error {
+    ($($error:ty),*) => {
+        $(impl From<RequestError> for $error {
+            fn from(error: RequestError) -> Self {
+                match error {
+                    RequestError::MemoTooLarge => Self::GenericError {
+                        error_code: MEMO_TOO_LARGE_ERROR_CODE.into(),
+                        message: format!("Memo too large: at most {MAX_MEMO_SIZE} bytes"),
                     },
+                    RequestError::TooOld => Self::TooOld,
+                    RequestError::CreatedInFuture { ledger_time } => {
+                        Self::CreatedInFuture { ledger_time }
+                    }
                 }
-            })
-            .collect()
+            }
+        })*
+    };
+}
+
+impl_from_request_error!(
+    TransferError,
+    BurnError,
+    ApproveTokenError,
+    ApproveCollectionError,
+    RevokeTokenApprovalError,
+    RevokeCollectionApprovalError
+);
+
+fn check_request(
+    memo: &Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+    now: u64,
+) -> Result<(), RequestError> {
+    if memo
+        .as_ref()
+        .is_some_and(|memo| memo.len() as u64 > MAX_MEMO_SIZE)
+    {
+        return Err(RequestError::MemoTooLarge);
+    }
+    match created_at_time {
+        Some(created_at_time) => check_created_at_time(created_at_time, now),
+        None => Ok(()),
+    }
+}
+
+// A transfer already applied within the window is reported, not repeated
+fn check_duplicate(dedup_key: Option<([u8; 32], u64)>) -> Result<(), TransferError> {
+    match duplicate_of(dedup_key) {
+        Some(index) => Err(TransferError::Duplicate {
+            duplicate_of: index.into(),
+        }),
+        None => Ok(()),
+    }
+}
+
+// Index of the transaction an identical request already produced within the window
+fn duplicate_of(dedup_key: Option<([u8; 32], u64)>) -> Option<u64> {
+    let (key, _) = dedup_key?;
+    RECENT_TRANSFERS.with(|recent| recent.borrow().get(&key).map(|(_, index)| *index))
+}
+
+fn remember_request(dedup_key: Option<([u8; 32], u64)>, index: u64) {
+    if let Some((key, created_at_time)) = dedup_key {
+        RECENT_TRANSFERS.with(|recent| {
+            recent.borrow_mut().insert(key, (created_at_time, index));
+        });
+    }
+}
+
+fn token_owner(token_id: &Nat) -> Option<(u64, Account)> {
+    let token_id = token_id_from_nat(token_id)?;
+    TOKENS.with(|tokens| {
+        tokens
+            .borrow()
+            .get(&token_id)
+            .map(|token| (token_id, token.owner.clone()))
+    })
+}
+
+// Move a checked token to its new owner, dropping approvals granted by the
+// previous owner or the artist, and return the index of the transfer block
+fn complete_transfer(transfer: Transfer, dedup_key: Option<([u8; 32], u64)>) -> u64 {
+    set_token_owner(transfer.token_id, transfer.to.clone());
+    approvals::clear_token_approvals(transfer.token_id);
+    policy::clear_artist_approval(transfer.token_id);
+
+    let mut tx = vec![("tid".to_string(), Value::Nat(transfer.token_id.into()))];
+    let btype = match &transfer.spender {
+        Some(spender) => {
+            tx.push(("spender".to_string(), blocks::account_value(spender)));
+            blocks::TRANSFER_FROM
+        }
+        None => blocks::TRANSFER,
+    };
+    tx.push(("from".to_string(), blocks::account_value(&transfer.from)));
+    tx.push(("to".to_string(), blocks::account_value(&transfer.to)));
+    blocks::push_request_fields(&mut tx, &transfer.memo, transfer.created_at_time);
+    let index = blocks::append_block(btype, tx);
+    remember_request(dedup_key, index);
+    index
+}
+
+// icrc7_burn - Destroy tokens owned by the caller; admins may burn any token
+//
+// A burned certificate token is unlinked from its certificate, so the
+// certificate can be minted again, e.g. to a corrected recipient.
+#[ic_cdk::update]
+pub fn icrc7_burn(args: Vec<BurnArg>) -> Vec<Option<BurnResult>> {
+    if args.is_empty() || args.len() as u64 > MAX_UPDATE_BATCH_SIZE {
+        return vec![Some(Err(BurnError::GenericBatchError {
+            error_code: BATCH_SIZE_ERROR_CODE.into(),
+            message: format!("Batch must contain between 1 and {MAX_UPDATE_BATCH_SIZE} burns"),
+        }))];
+    }
+    let caller = ic_cdk::api::caller();
+    let now = ic_cdk::api::time();
+    prune_recent_transfers(now);
+    let is_admin = crate::modules::certificates::require_admin().is_ok();
+
+    args.into_iter()
+        .map(|arg| Some(burn_token(caller, is_admin, arg, now)))
+        .collect()
+}
+
+fn burn_token(caller: candid::Principal, is_admin: bool, arg: BurnArg, now: u64) -> BurnResult {
+    check_request(&arg.memo, arg.created_at_time, now)?;
+    if arg
+        .reason
+        .as_ref()
+        .is_some_and(|reason| reason.len() > MAX_BURN_REASON_SIZE)
+    {
+        return Err(BurnError::GenericError {
+            error_code: REASON_TOO_LONG_ERROR_CODE.into(),
+            message: format!("Reason too long: at most {MAX_BURN_REASON_SIZE} bytes"),
+        });
+    }
+    let dedup_key = arg
+        .created_at_time
+        .map(|created_at_time| (request_hash(caller, &arg), created_at_time));
+    if let Some(index) = duplicate_of(dedup_key) {
+        return Err(BurnError::Duplicate {
+            duplicate_of: index.into(),
+        });
+    }
+
+    let (token_id, owner) = token_owner(&arg.token_id).ok_or(BurnError::NonExistingTokenId)?;
+    let from = Account {
+        owner: caller,
+        subaccount: arg.from_subaccount,
+    };
+    if owner != from && !is_admin {
+        return Err(BurnError::Unauthorized);
+    }
+
+    remove_token(token_id);
+    adjust_total_supply(|supply| supply.saturating_sub(1));
+    approvals::clear_token_approvals(token_id);
+    policy::clear_artist_approval(token_id);
+    crate::modules::certificates::unlink_certificate_nft(token_id);
+
+    let mut tx = vec![
+        ("tid".to_string(), Value::Nat(token_id.into())),
+        ("from".to_string(), blocks::account_value(&owner)),
+    ];
+    if owner != from {
+        tx.push(("burned_by".to_string(), blocks::account_value(&from)));
+    }
+    if let Some(reason) = arg.reason {
+        tx.push(("reason".to_string(), Value::Text(reason)));
+    }
+    blocks::push_request_fields(&mut tx, &arg.memo, arg.created_at_time);
+    let index = blocks::append_block(blocks::BURN, tx);
+    remember_request(dedup_key, index);
+    Ok(index.into())
+}
+
+// Record a newly minted token in the transaction log
+fn record_mint(token: &Token) {
+    let minter = Account {
+        owner: ic_cdk::api::caller(),
+        subaccount: None,
+    };
+    blocks::append_block(
+        blocks::MINT,
+        vec![
+            ("tid".to_string(), Value::Nat(token.id.into())),
+            ("from".to_string(), blocks::account_value(&minter)),
+            ("to".to_string(), blocks::account_value(&token.owner)),
+            ("meta".to_string(), Value::Map(token_metadata(token))),
+        ],
+    );
+}
+
+// created_at_time must lie in [now - tx_window - drift, now + drift]
+fn check_created_at_time(created_at_time: u64, now: u64) -> Result<(), RequestError> {
+    let (tx_window, permitted_drift) = window_nanos();
+    if created_at_time < now.saturating_sub(tx_window + permitted_drift) {
+        return Err(RequestError::TooOld);
+    }
+    if created_at_time > now.saturating_add(permitted_drift) {
+        return Err(RequestError::CreatedInFuture { ledger_time: now });
+    }
+    Ok(())
+}
+
+// Forget transfers that have left the window and can no longer be resubmitted
+fn prune_recent_transfers(now: u64) {
+    let (tx_window, permitted_drift) = window_nanos();
+    let oldest = now.saturating_sub(tx_window + permitted_drift);
+    RECENT_TRANSFERS.with(|recent| {
+        recent
+            .borrow_mut()
+            .retain(|_, (created_at_time, _)| *created_at_time >= oldest);
+    });
+}
+
+fn window_nanos() -> (u64, u64) {
+    TRANSFER_WINDOW.with(|window| {
+        let (tx_window, permitted_drift) = *window.borrow();
+        (
+            tx_window * NANOS_PER_SECOND,
+            permitted_drift * NANOS_PER_SECOND,
+        )
     })
 }
 
+// Identical requests from the same caller hash to the same key
+fn request_hash(caller: candid::Principal, arg: &impl candid::CandidType) -> [u8; 32] {
+    let encoded = candid::encode_args((caller, arg)).unwrap_or_default();
+    Sha256::digest(encoded).into()
+}
+
+// icrc10_supported_standards - Lists the standards this canister implements
+#[ic_cdk::query]
+pub fn icrc10_supported_standards() -> Vec<SupportedStandard> {
+    vec![
+        SupportedStandard {
+            name: "ICRC-7".to_string(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-7".to_string(),
+        },
+        SupportedStandard {
+            name: "ICRC-3".to_string(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-3".to_string(),
+        },
+        SupportedStandard {
+            name: "ICRC-37".to_string(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-37".to_string(),
+        },
+        SupportedStandard {
+            name: "ICRC-10".to_string(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-10".to_string(),
+        },
+    ]
+}
+
+// Token metadata on the standard surface: name, description and image as text,
+// attributes as a map of trait to value
+fn token_metadata(token: &Token) -> Vec<(String, Value)> {
+    let mut metadata = vec![("name".to_string(), Value::Text(token.metadata.name.clone()))];
+    if let Some(description) = &token.metadata.description {
+        metadata.push(("description".to_string(), Value::Text(description.clone())));
+    }
+    if let Some(image) = &token.metadata.image {
+        metadata.push(("image".to_string(), Value::Text(image.clone())));
+    }
+    metadata.push((
+        "created_at".to_string(),
+        Value::Nat(token.created_at.into()),
+    ));
+    metadata.push((
+        "attributes".to_string(),
+        Value::Map(
+            token
+                .metadata
+                .attributes
+                .iter()
+                .map(|attribute| {
+                    (
+                        attribute.trait_type.clone(),
+                        Value::Text(attribute.value.clone()),
+                    )
+                })
+                .collect(),
+        ),
+    ));
+    metadata.extend(token.transfer_policy.metadata());
+    metadata
+}
+
+fn token_id_from_nat(id: &Nat) -> Option<u64> {
+    u64::try_from(&id.0).ok()
+}
+
+// Batch queries cannot return an error, so oversized batches are rejected outright
+fn check_query_batch(len: usize) {
+    if len as u64 > MAX_QUERY_BATCH_SIZE {
+        ic_cdk::trap(&format!(
+            "Batch too large: at most {MAX_QUERY_BATCH_SIZE} entries per query"
+        ));
+    }
+}
+
+// Where a page after `prev` starts and how many ids it holds; None when `prev`
+// is beyond any token id
+fn page_bounds(prev: Option<Nat>, take: Option<Nat>) -> Option<(Bound<u64>, usize)> {
+    let take = take
+        .and_then(|take| token_id_from_nat(&take))
+        .unwrap_or(DEFAULT_TAKE_VALUE)
+        .min(MAX_TAKE_VALUE);
+    let start = match prev {
+        Some(prev) => Bound::Excluded(token_id_from_nat(&prev)?),
+        None => Bound::Unbounded,
+    };
+    Some((start, take as usize))
+}
+
 // Custom functions for Origin Stamp integration
 
 // Mint NFT from physical art session
@@ -213,9 +844,8 @@ pub fn mint_nft_from_session(
     session_id: String,
     recipient: Account,
     additional_attributes: Vec<(String, String)>,
+    transfer_policy: Option<TransferPolicy>,
 ) -> Result<u64, String> {
-    let _caller = ic_cdk::api::caller();
-
     // Get session details
     let session = physical_art::get_session_details(session_id.clone());
 
@@ -224,8 +854,9 @@ pub fn mint_nft_from_session(
         None => return Err("Session not found".to_string()),
     };
 
-    // Only session owner can mint NFT (or implement admin logic)
-    // For now, anyone can mint (you might want to add authorization)
+    authorize_mint(&session.username)?;
+    check_supply_cap()?;
+    let transfer_policy = policy::mint_policy(transfer_policy)?;
 
     let token_id = TOKEN_COUNTER.with(|counter| {
         let mut counter_val = counter.borrow_mut();
@@ -294,17 +925,14 @@ pub fn mint_nft_from_session(
         metadata,
         created_at: current_time,
         session_id: Some(session_id),
+        transfer_policy,
     };
 
-    TOKENS.with(|tokens| {
-        tokens.borrow_mut().insert(token_id, token);
-    });
+    record_mint(&token);
+    insert_token(token);
 
     // Update collection total supply
-    COLLECTION_METADATA.with(|metadata| {
-        let mut collection = metadata.borrow_mut();
-        collection.total_supply += 1;
-    });
+    adjust_total_supply(|supply| supply + 1);
 
     Ok(token_id)
 }
@@ -322,20 +950,27 @@ pub fn get_session_nfts(session_id: String) -> Vec<Token> {
     })
 }
 
-// Get all NFTs owned by a user (by principal)
+// Get all NFTs owned by a user (by principal, across subaccounts), in token id order
 #[ic_cdk::query]
 pub fn get_user_nfts(owner: candid::Principal) -> Vec<Token> {
-    TOKENS.with(|tokens| {
-        tokens
+    let mut token_ids: Vec<u64> = OWNER_TOKENS.with(|index| {
+        index
             .borrow()
-            .values()
-            .filter(|token| token.owner.owner == owner)
-            .cloned()
+            .get(&owner)
+            .map(|subaccounts| subaccounts.values().flatten().copied().collect())
+            .unwrap_or_default()
+    });
+    token_ids.sort_unstable();
+    TOKENS.with(|tokens| {
+        let tokens = tokens.borrow();
+        token_ids
+            .iter()
+            .filter_map(|id| tokens.get(id).cloned())
             .collect()
     })
 }
 
-// Update collection metadata (admin function)
+// Update collection metadata (admin only); every change is kept in the metadata history
 #[ic_cdk::update]
 pub fn update_collection_metadata(
     name: String,
@@ -343,17 +978,93 @@ pub fn update_collection_metadata(
     image: Option<String>,
     max_supply: Option<u64>,
 ) -> Result<bool, String> {
-    // You might want to add admin authorization here
-    COLLECTION_METADATA.with(|metadata| {
+    crate::modules::certificates::require_admin()?;
+    if name.trim().is_empty() {
+        return Err("Collection name cannot be empty".to_string());
+    }
+
+    let change = COLLECTION_METADATA.with(|metadata| {
         let mut collection = metadata.borrow_mut();
+        if max_supply.is_some_and(|max_supply| max_supply < collection.total_supply) {
+            return Err(format!(
+                "Max supply cannot be below the current supply of {}",
+                collection.total_supply
+            ));
+        }
+        let previous = collection.clone();
         collection.name = name;
         collection.description = description;
         collection.image = image;
         collection.max_supply = max_supply;
-    });
+        Ok(CollectionMetadataChange {
+            changed_by: crate::modules::users::caller_username()
+                .unwrap_or_else(|| ic_cdk::api::caller().to_string()),
+            changed_at: ic_cdk::api::time(),
+            previous,
+            updated: collection.clone(),
+        })
+    })?;
+    COLLECTION_METADATA_HISTORY.with(|history| history.borrow_mut().push(change));
+    documents::publish_collection_document();
     Ok(true)
 }
 
+// Audit trail of collection metadata changes, oldest first
+#[ic_cdk::query]
+pub fn get_collection_metadata_history() -> Vec<CollectionMetadataChange> {
+    COLLECTION_METADATA_HISTORY.with(|history| history.borrow().clone())
+}
+
+// Allow or stop a principal minting from any session or certificate (admin only)
+#[ic_cdk::update]
+pub fn set_nft_minter(minter: candid::Principal, allowed: bool) -> Result<(), String> {
+    crate::modules::certificates::require_admin()?;
+    MINTERS.with(|minters| {
+        let mut minters = minters.borrow_mut();
+        if allowed {
+            minters.insert(minter);
+        } else {
+            minters.remove(&minter);
+        }
+    });
+    Ok(())
+}
+
+#[ic_cdk::query]
+pub fn get_nft_minters() -> Vec<candid::Principal> {
+    MINTERS.with(|minters| {
+        let mut minters: Vec<_> = minters.borrow().iter().copied().collect();
+        minters.sort();
+        minters
+    })
+}
+
+// Users mint their own sessions and certificates; minters and admins may mint any
+fn authorize_mint(owner_username: &str) -> Result<(), String> {
+    if crate::modules::users::caller_username().as_deref() == Some(owner_username) {
+        return Ok(());
+    }
+    let caller = ic_cdk::api::caller();
+    if MINTERS.with(|minters| minters.borrow().contains(&caller))
+        || crate::modules::certificates::require_admin().is_ok()
+    {
+        return Ok(());
+    }
+    Err("Only the owner, minters or admins can mint this NFT".to_string())
+}
+
+fn check_supply_cap() -> Result<(), String> {
+    COLLECTION_METADATA.with(|metadata| {
+        let collection = metadata.borrow();
+        match collection.max_supply {
+            Some(max_supply) if collection.total_supply >= max_supply => {
+                Err(format!("Supply cap of {max_supply} tokens reached"))
+            }
+            _ => Ok(()),
+        }
+    })
+}
+
 // Get token details (extended information)
 #[ic_cdk::query]
 pub fn get_token_details(token_id: u64) -> Option<Token> {
@@ -366,9 +1077,11 @@ pub fn get_token_details(token_id: u64) -> Option<Token> {
 
 // Mint NFT from certificate
 #[ic_cdk::update]
-pub fn mint_certificate_nft(certificate_id: String, recipient: Account) -> Result<u64, String> {
-    let _caller = ic_cdk::api::caller();
-
+pub fn mint_certificate_nft(
+    certificate_id: String,
+    recipient: Account,
+    transfer_policy: Option<TransferPolicy>,
+) -> Result<u64, String> {
     // 1. Input validation
     if certificate_id.is_empty() || certificate_id.len() > 100 {
         return Err("Invalid certificate ID".to_string());
@@ -383,12 +1096,16 @@ pub fn mint_certificate_nft(certificate_id: String, recipient: Account) -> Resul
     let certificate = certificate.unwrap();
 
     // 3. Verify certificate is active
-    if certificate.certificate_status != "active" {
+    if certificate.certificate_status != CertificateStatus::Active {
         return Err("Certificate is not active".to_string());
     }
 
-    // 4. Check if NFT already exists
-    if certificate.nft_generated {
+    authorize_mint(&certificate.username)?;
+    check_supply_cap()?;
+    let transfer_policy = policy::mint_policy(transfer_policy)?;
+
+    // 4. Check if NFT already exists (only the mint and burn paths write these fields)
+    if certificate.nft_generated || certificate.nft_id.is_some() {
         return Err("NFT already generated for this certificate".to_string());
     }
 
@@ -454,6 +1171,14 @@ pub fn mint_certificate_nft(certificate_id: String, recipient: Account) -> Resul
             trait_type: "verification_hash".to_string(),
             value: certificate.verification_hash.clone(),
         },
+        TokenAttribute {
+            trait_type: "certificate_status".to_string(),
+            value: certificate.certificate_status.label().to_string(),
+        },
+        TokenAttribute {
+            trait_type: "certificate_type".to_string(),
+            value: certificate.certificate_type.label().to_string(),
+        },
         TokenAttribute {
             trait_type: "verification_score".to_string(),
             value: certificate.verification_score.to_string(),
@@ -550,38 +1275,31 @@ pub fn mint_certificate_nft(certificate_id: String, recipient: Account) -> Resul
         metadata,
         created_at: current_time,
         session_id: Some(certificate.session_id.clone()),
+        transfer_policy,
     };
 
     // 13. Store token
-    TOKENS.with(|tokens| {
-        tokens.borrow_mut().insert(token_id, token);
-    });
+    insert_token(token);
 
     // 14. Update collection total supply
-    COLLECTION_METADATA.with(|metadata| {
-        let mut collection = metadata.borrow_mut();
-        collection.total_supply += 1;
-    });
+    adjust_total_supply(|supply| supply + 1);
 
     // 15. Update certificate with NFT info
-    let token_uri = format!("https://originstamp.ic0.app/nft/{token_id}/metadata");
-    let update_result = crate::modules::certificates::update_certificate_nft_info(
-        certificate_id,
-        token_id.to_string(),
-        token_uri,
-    );
+    let token_uri = documents::token_uri(token_id);
+    let update_result =
+        crate::modules::certificates::link_certificate_nft(&certificate_id, token_id, token_uri);
 
     match update_result {
-        Ok(_) => Ok(token_id),
+        Ok(_) => {
+            if let Some(token) = get_token_details(token_id) {
+                record_mint(&token);
+            }
+            Ok(token_id)
+        }
         Err(e) => {
             // Rollback token creation if certificate update fails
-            TOKENS.with(|tokens| {
-                tokens.borrow_mut().remove(&token_id);
-            });
-            COLLECTION_METADATA.with(|metadata| {
-                let mut collection = metadata.borrow_mut();
-                collection.total_supply = collection.total_supply.saturating_sub(1);
-            });
+            remove_token(token_id);
+            adjust_total_supply(|supply| supply.saturating_sub(1));
             Err(format!("Failed to update certificate: {e}"))
         }
     }
@@ -611,6 +1329,47 @@ pub fn get_certificate_nft_metadata(certificate_id: String) -> Option<String> {
     Some(serde_json::to_string(&token.metadata).unwrap_or_default())
 }
 
+// Reflect a certificate status change in the linked token's attributes
+pub fn update_certificate_token_status(
+    token_id: u64,
+    status: CertificateStatus,
+    reason: Option<String>,
+) {
+    update_token_metadata(token_id, |token| {
+        set_token_attribute(token, "certificate_status", status.label().to_string());
+        if let Some(reason) = reason {
+            set_token_attribute(token, "status_reason", reason);
+        }
+    });
+}
+
+// Point a certificate token at the latest version of its certificate
+pub fn relink_certificate_token(token_id: u64, certificate_id: &str) {
+    update_token_metadata(token_id, |token| {
+        set_token_attribute(token, "certificate_id", certificate_id.to_string());
+        set_token_attribute(
+            token,
+            "certificate_status",
+            CertificateStatus::Active.label().to_string(),
+        );
+    });
+}
+
+fn set_token_attribute(token: &mut Token, trait_type: &str, value: String) {
+    match token
+        .metadata
+        .attributes
+        .iter_mut()
+        .find(|attribute| attribute.trait_type == trait_type)
+    {
+        Some(attribute) => attribute.value = value,
+        None => token.metadata.attributes.push(TokenAttribute {
+            trait_type: trait_type.to_string(),
+            value,
+        }),
+    }
+}
+
 fn generate_token_hash(token_id: u64, session_id: &str, timestamp: u64) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token_id.to_be_bytes());
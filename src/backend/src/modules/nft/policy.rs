@@ -0,0 +1,176 @@
+use super::{COLLECTION_METADATA, COLLECTION_METADATA_HISTORY, TOKENS};
+use crate::types::{
+    Account, CollectionMetadataChange, Token, TransferError, TransferPolicy, Value,
+};
+use crate::utils::format_timestamp;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// =============================================================================
+// Transfer policies: soulbound, artist-approved and locked-up tokens
+// =============================================================================
+//
+// Every token carries the policy it was minted with; mints without one take the
+// collection default. Policies apply to icrc7_transfer and icrc37_transfer_from;
+// burning is always allowed.
+
+// Error code reported with GenericError when a policy blocks a transfer
+const TRANSFER_RESTRICTED_ERROR_CODE: u64 = 6;
+
+thread_local! {
+    // Token id -> recipient the artist approved for the token's next transfer
+    static ARTIST_APPROVALS: RefCell<HashMap<u64, Account>> = RefCell::new(HashMap::new());
+}
+
+impl TransferPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferPolicy::Transferable => "transferable",
+            TransferPolicy::Soulbound => "soulbound",
+            TransferPolicy::ArtistApproval => "artist_approval",
+            TransferPolicy::LockedUntil(_) => "locked_until",
+        }
+    }
+
+    // Metadata entries describing the policy, for icrc7_token_metadata
+    pub(super) fn metadata(&self) -> Vec<(String, Value)> {
+        let mut metadata = vec![(
+            "transfer_policy".to_string(),
+            Value::Text(self.label().to_string()),
+        )];
+        if let TransferPolicy::LockedUntil(unlock_at) = self {
+            metadata.push((
+                "transfer_locked_until".to_string(),
+                Value::Nat((*unlock_at).into()),
+            ));
+        }
+        metadata
+    }
+}
+
+// Policy of a new token: the requested one, or the collection default (whose
+// lock-up may since have ended, leaving the token freely transferable)
+pub(super) fn mint_policy(requested: Option<TransferPolicy>) -> Result<TransferPolicy, String> {
+    match requested {
+        Some(policy) => {
+            validate_policy(&policy)?;
+            Ok(policy)
+        }
+        None => Ok(get_collection_transfer_policy()),
+    }
+}
+
+fn validate_policy(policy: &TransferPolicy) -> Result<(), String> {
+    match policy {
+        TransferPolicy::LockedUntil(unlock_at) if *unlock_at <= ic_cdk::api::time() => {
+            Err("Lock-up date must be in the future".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Check that a token's policy allows moving it to `to` now
+pub(super) fn check_transfer(token_id: u64, to: &Account, now: u64) -> Result<(), TransferError> {
+    let policy = TOKENS.with(|tokens| {
+        tokens
+            .borrow()
+            .get(&token_id)
+            .map(|token| token.transfer_policy.clone())
+    });
+    let message = match policy {
+        Some(TransferPolicy::Soulbound) => {
+            format!("Token {token_id} is soulbound and cannot be transferred")
+        }
+        Some(TransferPolicy::ArtistApproval)
+            if ARTIST_APPROVALS.with(|approvals| approvals.borrow().get(&token_id) != Some(to)) =>
+        {
+            format!(
+                "Token {token_id} can only be transferred to a recipient approved by the artist"
+            )
+        }
+        Some(TransferPolicy::LockedUntil(unlock_at)) if now < unlock_at => format!(
+            "Token {token_id} is locked until {}",
+            format_timestamp(unlock_at)
+        ),
+        _ => return Ok(()),
+    };
+    Err(TransferError::GenericError {
+        error_code: TRANSFER_RESTRICTED_ERROR_CODE.into(),
+        message,
+    })
+}
+
+// An artist approval covers a single transfer
+pub(super) fn clear_artist_approval(token_id: u64) {
+    ARTIST_APPROVALS.with(|approvals| approvals.borrow_mut().remove(&token_id));
+}
+
+// Set the default policy of tokens minted from now on (admin only); existing
+// tokens keep theirs
+#[ic_cdk::update]
+pub fn set_collection_transfer_policy(policy: TransferPolicy) -> Result<(), String> {
+    crate::modules::certificates::require_admin()?;
+    validate_policy(&policy)?;
+
+    let change = COLLECTION_METADATA.with(|metadata| {
+        let mut collection = metadata.borrow_mut();
+        let previous = collection.clone();
+        collection.transfer_policy = policy;
+        CollectionMetadataChange {
+            changed_by: crate::modules::users::caller_username()
+                .unwrap_or_else(|| ic_cdk::api::caller().to_string()),
+            changed_at: ic_cdk::api::time(),
+            previous,
+            updated: collection.clone(),
+        }
+    });
+    COLLECTION_METADATA_HISTORY.with(|history| history.borrow_mut().push(change));
+    super::documents::publish_collection_document();
+    Ok(())
+}
+
+#[ic_cdk::query]
+pub fn get_collection_transfer_policy() -> TransferPolicy {
+    COLLECTION_METADATA.with(|metadata| metadata.borrow().transfer_policy.clone())
+}
+
+// Approve the recipient of the next transfer of an artist-approval token, or
+// withdraw the approval with None (artist or admin only)
+#[ic_cdk::update]
+pub fn set_nft_transfer_approval(token_id: u64, to: Option<Account>) -> Result<(), String> {
+    let token = TOKENS
+        .with(|tokens| tokens.borrow().get(&token_id).cloned())
+        .ok_or_else(|| "Token not found".to_string())?;
+    if token.transfer_policy != TransferPolicy::ArtistApproval {
+        return Err("Token does not require artist approval to transfer".to_string());
+    }
+    let is_artist = crate::modules::users::caller_username()
+        .is_some_and(|username| token_artist(&token) == Some(username.as_str()));
+    if !is_artist && crate::modules::certificates::require_admin().is_err() {
+        return Err("Only the artist or admins can approve transfers of this NFT".to_string());
+    }
+
+    ARTIST_APPROVALS.with(|approvals| {
+        let mut approvals = approvals.borrow_mut();
+        match to {
+            Some(to) => approvals.insert(token_id, to),
+            None => approvals.remove(&token_id),
+        }
+    });
+    Ok(())
+}
+
+#[ic_cdk::query]
+pub fn get_nft_transfer_approval(token_id: u64) -> Option<Account> {
+    ARTIST_APPROVALS.with(|approvals| approvals.borrow().get(&token_id).cloned())
+}
+
+// Username recorded in the token's `artist` attribute at mint time
+fn token_artist(token: &Token) -> Option<&str> {
+    token
+        .metadata
+        .attributes
+        .iter()
+        .find(|attribute| attribute.trait_type == "artist")
+        .map(|attribute| attribute.value.as_str())
+}
@@ -1,10 +1,12 @@
-use crate::types::PhysicalArtSession;
+use crate::types::{PhotoEvidence, PhysicalArtSession};
 use crate::utils::generate_random_id;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
 thread_local! {
     static PHYSICAL_ART_SESSIONS: RefCell<HashMap<String, PhysicalArtSession>> = RefCell::new(HashMap::new());
+    // session_id -> photo_url -> evidence
+    static PHOTO_EVIDENCE: RefCell<HashMap<String, HashMap<String, PhotoEvidence>>> = RefCell::new(HashMap::new());
 }
 
 // Create physical art session
@@ -33,20 +35,43 @@ pub fn create_physical_art_session(
     Ok(session_id)
 }
 
-// Upload photo to session (record the uploaded photo)
+// Upload photo to session (record the uploaded photo; session owner or admin).
+// Each URL is recorded once, so a re-upload cannot reset its evidence record.
 #[ic_cdk::update]
 pub fn upload_photo_to_session(session_id: String, photo_url: String) -> Result<bool, String> {
-    PHYSICAL_ART_SESSIONS.with(|sessions| {
+    let session =
+        get_session_details(session_id.clone()).ok_or_else(|| "Session not found".to_string())?;
+    authorize_session_owner(&session, "change session photos")?;
+    if session.uploaded_photos.contains(&photo_url) {
+        return Err("Photo already uploaded to this session".to_string());
+    }
+
+    let uploaded = PHYSICAL_ART_SESSIONS.with(|sessions| {
         let mut sessions_map = sessions.borrow_mut();
         match sessions_map.get_mut(&session_id) {
             Some(session) => {
+                PHOTO_EVIDENCE.with(|evidence| {
+                    evidence
+                        .borrow_mut()
+                        .entry(session_id.clone())
+                        .or_default()
+                        .entry(photo_url.clone())
+                        .or_insert(PhotoEvidence {
+                            photo_url: photo_url.clone(),
+                            content_hash: None,
+                            uploaded_at: ic_cdk::api::time(),
+                        });
+                });
                 session.uploaded_photos.push(photo_url);
                 session.updated_at = ic_cdk::api::time();
                 Ok(true)
             }
             None => Err("Session not found".to_string()),
         }
-    })
+    })?;
+    // Pages read the session, so render after the borrow ends
+    crate::modules::certificates::republish_session_pages(&session_id);
+    Ok(uploaded)
 }
 
 // Get session details
@@ -84,19 +109,130 @@ pub fn update_session_status(session_id: String, status: String) -> Result<bool,
     })
 }
 
-// Remove photo from session
+// Remove photo from session (session owner or admin)
 #[ic_cdk::update]
 pub fn remove_photo_from_session(session_id: String, photo_url: String) -> Result<bool, String> {
-    PHYSICAL_ART_SESSIONS.with(|sessions| {
+    let session =
+        get_session_details(session_id.clone()).ok_or_else(|| "Session not found".to_string())?;
+    authorize_session_owner(&session, "change session photos")?;
+
+    let removed = PHYSICAL_ART_SESSIONS.with(|sessions| {
         let mut sessions_map = sessions.borrow_mut();
         match sessions_map.get_mut(&session_id) {
             Some(session) => {
                 session.uploaded_photos.retain(|url| url != &photo_url);
                 session.updated_at = ic_cdk::api::time();
+                PHOTO_EVIDENCE.with(|evidence| {
+                    if let Some(photos) = evidence.borrow_mut().get_mut(&session_id) {
+                        photos.remove(&photo_url);
+                    }
+                });
                 Ok(true)
             }
             None => Err("Session not found".to_string()),
         }
+    })?;
+    crate::modules::certificates::republish_session_pages(&session_id);
+    Ok(removed)
+}
+
+// Record the SHA-256 of an uploaded photo (session owner or admin). Once set it
+// can only be replaced through correct_photo_hash.
+#[ic_cdk::update]
+pub fn record_photo_hash(
+    session_id: String,
+    photo_url: String,
+    content_hash: String,
+) -> Result<bool, String> {
+    set_photo_hash(session_id, photo_url, content_hash, false)
+}
+
+// Replace a wrongly recorded photo hash (session owner or admin)
+#[ic_cdk::update]
+pub fn correct_photo_hash(
+    session_id: String,
+    photo_url: String,
+    content_hash: String,
+) -> Result<bool, String> {
+    set_photo_hash(session_id, photo_url, content_hash, true)
+}
+
+fn set_photo_hash(
+    session_id: String,
+    photo_url: String,
+    content_hash: String,
+    replace: bool,
+) -> Result<bool, String> {
+    let content_hash = content_hash.to_lowercase();
+    if content_hash.len() != 64 || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid content hash: expected 64 hex characters (SHA-256)".to_string());
+    }
+
+    let session =
+        get_session_details(session_id.clone()).ok_or_else(|| "Session not found".to_string())?;
+    authorize_session_owner(&session, "record photo hashes")?;
+    if !session.uploaded_photos.contains(&photo_url) {
+        return Err("Photo not found in session".to_string());
+    }
+
+    PHOTO_EVIDENCE.with(|evidence| {
+        let mut evidence = evidence.borrow_mut();
+        let entry = evidence
+            .entry(session_id)
+            .or_default()
+            .entry(photo_url.clone())
+            .or_insert(PhotoEvidence {
+                photo_url,
+                content_hash: None,
+                uploaded_at: session.updated_at,
+            });
+        match &entry.content_hash {
+            Some(existing) if *existing != content_hash && !replace => Err(
+                "A different content hash is already recorded for this photo; use correct_photo_hash to replace it"
+                    .to_string(),
+            ),
+            _ => {
+                entry.content_hash = Some(content_hash);
+                Ok(true)
+            }
+        }
+    })
+}
+
+// The caller must be bound to the session's username, or be an admin
+fn authorize_session_owner(session: &PhysicalArtSession, action: &str) -> Result<(), String> {
+    if crate::modules::users::caller_username().as_deref() == Some(session.username.as_str())
+        || crate::modules::certificates::require_admin().is_ok()
+    {
+        Ok(())
+    } else {
+        Err(format!("Only the session owner or admins can {action}"))
+    }
+}
+
+// Get the evidence recorded for each photo of a session, in upload order
+#[ic_cdk::query]
+pub fn get_session_evidence(session_id: String) -> Vec<PhotoEvidence> {
+    let Some(session) = get_session_details(session_id.clone()) else {
+        return Vec::new();
+    };
+
+    PHOTO_EVIDENCE.with(|evidence| {
+        let evidence = evidence.borrow();
+        let photos = evidence.get(&session_id);
+        session
+            .uploaded_photos
+            .iter()
+            .map(|photo_url| {
+                photos
+                    .and_then(|photos| photos.get(photo_url).cloned())
+                    .unwrap_or_else(|| PhotoEvidence {
+                        photo_url: photo_url.clone(),
+                        content_hash: None,
+                        uploaded_at: session.created_at,
+                    })
+            })
+            .collect()
     })
 }
 
@@ -114,6 +250,7 @@ pub fn get_recent_sessions(limit: usize) -> Vec<PhysicalArtSession> {
             sessions.borrow().values().cloned().collect();
 
         // Sort by creation date (newest first)
+        #[allow(clippy::unnecessary_sort_by)]
         sessions_vec.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
         // Return limited number of sessions
@@ -0,0 +1,224 @@
+use crate::types::CertificatePublicKey;
+use crate::utils::to_hex;
+use ic_cdk::api::management_canister::ecdsa::{
+    ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument,
+    SignWithEcdsaArgument,
+};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+// =============================================================================
+// Threshold ECDSA signing for certificates
+// =============================================================================
+
+// Key names: "dfx_test_key" (local replica / PocketIC), "test_key_1", "key_1" (mainnet).
+// There is no default: which key exists depends on the network, so an admin picks
+// it with set_signing_key_name after deployment.
+const KEY_NAMES: [&str; 3] = ["dfx_test_key", "test_key_1", "key_1"];
+const CERTIFICATE_DERIVATION_PATH: &[u8] = b"certificates";
+const SIGNATURE_ALGORITHM: &str = "ecdsa-secp256k1-sha256";
+const DID_DOCUMENT_PATH: &str = "/.well-known/did.json";
+// Multicodec prefix for a compressed secp256k1 public key (0xe7, varint-encoded)
+const SECP256K1_PUB_MULTICODEC: [u8; 2] = [0xe7, 0x01];
+
+thread_local! {
+    // Every key this canister has signed with, by key id. Retired keys stay here
+    // (and in the DID document) so older signatures remain verifiable.
+    static SIGNING_KEYS: RefCell<BTreeMap<String, SigningKey>> = const { RefCell::new(BTreeMap::new()) };
+    static ACTIVE_KEY_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone)]
+struct SigningKey {
+    key_name: String,
+    // SEC1 compressed public key
+    public_key: Vec<u8>,
+}
+
+// Stable identifier of a public key: first 8 bytes of its SHA-256, hex encoded
+fn key_id_for(public_key: &[u8]) -> String {
+    format!("secp256k1-{}", to_hex(&Sha256::digest(public_key)[..8]))
+}
+
+fn ecdsa_key_id(key_name: &str) -> EcdsaKeyId {
+    EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: key_name.to_string(),
+    }
+}
+
+fn derivation_path() -> Vec<Vec<u8>> {
+    vec![CERTIFICATE_DERIVATION_PATH.to_vec()]
+}
+
+fn describe_key(key_id: &str, key: &SigningKey) -> CertificatePublicKey {
+    CertificatePublicKey {
+        key_id: key_id.to_string(),
+        key_name: key.key_name.clone(),
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        derivation_path: derivation_path()
+            .into_iter()
+            .map(|segment| String::from_utf8_lossy(&segment).to_string())
+            .collect(),
+        public_key: to_hex(&key.public_key),
+        active: ACTIVE_KEY_ID.with(|active| active.borrow().as_deref() == Some(key_id)),
+    }
+}
+
+// Fetch the public key for a threshold key name and make it the active signing key
+async fn activate_key(key_name: &str) -> Result<CertificatePublicKey, String> {
+    let (response,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+        canister_id: None,
+        derivation_path: derivation_path(),
+        key_id: ecdsa_key_id(key_name),
+    })
+    .await
+    .map_err(|(code, message)| format!("Failed to fetch public key: {code:?} {message}"))?;
+
+    let key_id = key_id_for(&response.public_key);
+    SIGNING_KEYS.with(|keys| {
+        keys.borrow_mut().insert(
+            key_id.clone(),
+            SigningKey {
+                key_name: key_name.to_string(),
+                public_key: response.public_key,
+            },
+        )
+    });
+    ACTIVE_KEY_ID.with(|active| *active.borrow_mut() = Some(key_id.clone()));
+    publish_did_document();
+
+    signing_key(&key_id).ok_or_else(|| "Public key unavailable".to_string())
+}
+
+// The key new signatures are made with. Its public key is always known: it is
+// fetched when the key is configured, before anything is signed.
+pub fn active_signing_key() -> Result<CertificatePublicKey, String> {
+    ACTIVE_KEY_ID
+        .with(|active| active.borrow().clone())
+        .and_then(|key_id| signing_key(&key_id))
+        .ok_or_else(|| {
+            "No signing key configured; an admin must call set_signing_key_name".to_string()
+        })
+}
+
+// Sign SHA-256(message) with the given threshold key.
+// Returns the 64-byte compact (r || s) signature.
+pub async fn sign_message(key: &CertificatePublicKey, message: &[u8]) -> Result<Vec<u8>, String> {
+    let (response,) = sign_with_ecdsa(SignWithEcdsaArgument {
+        message_hash: Sha256::digest(message).to_vec(),
+        derivation_path: derivation_path(),
+        key_id: ecdsa_key_id(&key.key_name),
+    })
+    .await
+    .map_err(|(code, message)| format!("Failed to sign certificate: {code:?} {message}"))?;
+
+    Ok(response.signature)
+}
+
+// Public key bytes for a key id, including retired keys
+pub fn public_key_bytes(key_id: &str) -> Option<Vec<u8>> {
+    SIGNING_KEYS.with(|keys| keys.borrow().get(key_id).map(|key| key.public_key.clone()))
+}
+
+pub fn signing_key(key_id: &str) -> Option<CertificatePublicKey> {
+    SIGNING_KEYS.with(|keys| {
+        keys.borrow()
+            .get(key_id)
+            .map(|key| describe_key(key_id, key))
+    })
+}
+
+// Get the public key that new certificate signatures verify against
+#[ic_cdk::query]
+pub fn get_certificate_public_key() -> Option<CertificatePublicKey> {
+    active_signing_key().ok()
+}
+
+// Get every key certificates have been signed with, including retired ones
+#[ic_cdk::query]
+pub fn get_certificate_signing_keys() -> Vec<CertificatePublicKey> {
+    SIGNING_KEYS.with(|keys| {
+        keys.borrow()
+            .iter()
+            .map(|(key_id, key)| describe_key(key_id, key))
+            .collect()
+    })
+}
+
+// Re-fetch the active public key, e.g. to republish a lost DID document
+#[ic_cdk::update]
+pub async fn refresh_certificate_public_key() -> Result<CertificatePublicKey, String> {
+    let key_name = active_signing_key()?.key_name;
+    activate_key(&key_name).await
+}
+
+// Choose the threshold key used for new signatures (admin only). Keys signed with
+// before stay registered, so existing certificates keep verifying.
+#[ic_cdk::update]
+pub async fn set_signing_key_name(key_name: String) -> Result<CertificatePublicKey, String> {
+    crate::modules::certificates::require_admin()?;
+
+    if !KEY_NAMES.contains(&key_name.as_str()) {
+        return Err("Invalid key name: must be one of dfx_test_key, test_key_1, key_1".to_string());
+    }
+
+    activate_key(&key_name).await
+}
+
+// did:web identifier of this canister; resolves to /.well-known/did.json
+pub fn issuer_did() -> String {
+    format!("did:web:{}.icp0.io", ic_cdk::api::id())
+}
+
+pub fn verification_method_id(key_id: &str) -> String {
+    format!("{}#{key_id}", issuer_did())
+}
+
+// Key id from a verification method of this canister's DID, if it is one
+pub fn key_id_from_verification_method(verification_method: &str) -> Option<&str> {
+    verification_method
+        .strip_prefix(issuer_did().as_str())
+        .and_then(|fragment| fragment.strip_prefix('#'))
+}
+
+// Multikey encoding of a compressed secp256k1 public key (base58btc multibase)
+pub fn public_key_multibase(public_key: &[u8]) -> String {
+    let mut bytes = SECP256K1_PUB_MULTICODEC.to_vec();
+    bytes.extend_from_slice(public_key);
+    format!("z{}", bs58::encode(bytes).into_string())
+}
+
+// Lists every registered key; retired keys stay assertion methods because the
+// certificates they signed are still valid
+fn publish_did_document() {
+    let did = issuer_did();
+    let keys = SIGNING_KEYS.with(|keys| keys.borrow().clone());
+    let verification_methods: Vec<_> = keys
+        .iter()
+        .map(|(key_id, key)| {
+            serde_json::json!({
+                "id": verification_method_id(key_id),
+                "type": "Multikey",
+                "controller": did,
+                "publicKeyMultibase": public_key_multibase(&key.public_key),
+            })
+        })
+        .collect();
+    let assertion_methods: Vec<_> = keys
+        .keys()
+        .map(|key_id| verification_method_id(key_id))
+        .collect();
+    let document = serde_json::json!({
+        "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/multikey/v1"],
+        "id": did,
+        "verificationMethod": verification_methods,
+        "assertionMethod": assertion_methods,
+    });
+    crate::modules::http::publish_asset(
+        DID_DOCUMENT_PATH,
+        "application/did+json",
+        serde_json::to_vec_pretty(&document).unwrap_or_default(),
+    );
+}
@@ -1,9 +1,29 @@
 use crate::types::{LoginResult, User};
+use candid::Principal;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
 thread_local! {
     static USERS: RefCell<HashMap<String, User>> = RefCell::new(HashMap::new());
+    static PRINCIPAL_USERNAMES: RefCell<HashMap<Principal, String>> = RefCell::new(HashMap::new());
+}
+
+// Remember which username the calling principal authenticated as
+fn bind_caller_to_username(username: &str) {
+    let caller = ic_cdk::api::caller();
+    if caller == Principal::anonymous() {
+        return;
+    }
+
+    PRINCIPAL_USERNAMES.with(|bindings| {
+        bindings.borrow_mut().insert(caller, username.to_string());
+    });
+}
+
+// Username the calling principal last registered or logged in as
+pub fn caller_username() -> Option<String> {
+    let caller = ic_cdk::api::caller();
+    PRINCIPAL_USERNAMES.with(|bindings| bindings.borrow().get(&caller).cloned())
 }
 
 fn simple_hash(password: &str) -> String {
@@ -38,6 +58,7 @@ pub fn register_user(username: String, password: String) -> LoginResult {
             };
 
             users_map.insert(username.clone(), user);
+            bind_caller_to_username(&username);
 
             LoginResult {
                 success: true,
@@ -66,6 +87,7 @@ pub fn login(username: String, password: String) -> LoginResult {
                 let password_hash = simple_hash(&password);
 
                 if user.password_hash == password_hash {
+                    bind_caller_to_username(&username);
                     LoginResult {
                         success: true,
                         message: "Login successful".to_string(),
@@ -168,6 +190,14 @@ pub fn update_username(
             user.username = new_username.clone();
             users_map.insert(new_username.clone(), user);
 
+            PRINCIPAL_USERNAMES.with(|bindings| {
+                for bound in bindings.borrow_mut().values_mut() {
+                    if *bound == old_username {
+                        *bound = new_username.clone();
+                    }
+                }
+            });
+
             LoginResult {
                 success: true,
                 message: "Username updated successfully".to_string(),
@@ -12,14 +12,19 @@ pub struct Certificate {
     pub expiry_date: u64,
     pub verification_hash: String,
     pub blockchain_tx: String,
+    pub signature: String,
+    // Key id of the threshold key that made `signature`, see get_certificate_signing_keys
+    pub signing_key_id: String,
     pub qr_code_data: String,
     pub verification_url: String,
-    pub certificate_type: String,
+    pub certificate_type: CertificateType,
+    // Type-specific issuance data, signed with the rest of the certificate
+    pub type_details: CertificateTypeDetails,
     pub verification_score: u32,
     pub authenticity_rating: u32,
     pub provenance_score: u32,
     pub community_trust: u32,
-    pub certificate_status: String,
+    pub certificate_status: CertificateStatus,
     pub issuer: String,
     pub blockchain: String,
     pub token_standard: String,
@@ -27,6 +32,87 @@ pub struct Certificate {
     pub nft_generated: bool,
     pub nft_id: Option<String>,
     pub token_uri: Option<String>,
+    pub version: u32,
+    pub supersedes: Option<String>,
+    pub superseded_by: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CertificateType {
+    Standard,
+    LimitedEdition,
+    Commission,
+    Restoration,
+    Digital,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CertificateTypeDetails {
+    Standard,
+    LimitedEdition {
+        edition_number: u32,
+        edition_size: u32,
+    },
+    Commission {
+        commissioned_by: String,
+        commissioned_at: Option<u64>,
+    },
+    Restoration {
+        original_artist: String,
+        original_year: Option<u32>,
+        scope: String,
+    },
+    Digital {
+        // SHA-256 (hex) of the master file
+        master_file_hash: String,
+    },
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CertificateStatus {
+    Active,
+    Suspended,
+    Revoked,
+    Expired,
+    Superseded,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum RevocationReason {
+    Fraudulent,
+    IssuedInError,
+    DisputedAuthorship,
+    OwnerRequest,
+    Other(String),
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct RevocationEntry {
+    pub certificate_id: String,
+    pub reason: RevocationReason,
+    pub revoked_by: String,
+    pub revoked_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificatePublicKey {
+    pub key_id: String,
+    pub key_name: String,
+    pub algorithm: String,
+    pub derivation_path: Vec<String>,
+    pub public_key: String,
+    // False once another key has been configured for new signatures
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertifiedCertificate {
+    pub certificate: Option<Certificate>,
+    // Candid encoding of `certificate`; SHA-256 of these bytes is the certified leaf
+    pub certificate_bytes: Vec<u8>,
+    pub data_certificate: Vec<u8>,
+    // CBOR hash tree: certificates/<certificate_id> -> sha256(certificate_bytes)
+    pub witness: Vec<u8>,
 }
 
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
@@ -49,11 +135,202 @@ pub struct CreateCertificateRequest {
     pub file_format: String,
     pub creation_tools: Vec<String>,
     pub file_sizes: Vec<u64>, // Actual file sizes in bytes
+    // Defaults to Standard
+    pub type_details: Option<CertificateTypeDetails>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub verification_score: u32,
+    pub authenticity_rating: u32,
+    pub provenance_score: u32,
+    pub community_trust: u32,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateNftLink {
+    pub token_id: String,
+    pub token_uri: Option<String>,
+    pub owner: Option<crate::types::Account>,
 }
 
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
 pub struct VerificationResult {
+    pub certificate_id: String,
     pub valid: bool,
-    pub score: u32,
-    pub details: String,
+    // Effective status: Active certificates past their expiry date report Expired
+    pub status: CertificateStatus,
+    pub issue_date: u64,
+    pub expiry_date: u64,
+    pub issuer: String,
+    pub verification_hash: String,
+    pub blockchain_tx: String,
+    pub signature: String,
+    pub signing_key_id: String,
+    pub scores: ScoreBreakdown,
+    pub nft: Option<CertificateNftLink>,
+    pub revocation: Option<RevocationEntry>,
+    pub superseded_by: Option<String>,
+    pub checked_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct BatchVerificationEntry {
+    pub certificate_id: String,
+    pub result: Result<VerificationResult, String>,
+}
+
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+pub struct CertificateSearchQuery {
+    // Free text over title, description, artist, creation tools and file format
+    pub query: String,
+    pub status: Option<CertificateStatus>,
+    pub certificate_type: Option<CertificateType>,
+    pub issued_after: Option<u64>,
+    pub issued_before: Option<u64>,
+    pub nft_minted: Option<bool>,
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateSearchHit {
+    pub certificate: Certificate,
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateSearchResult {
+    // Number of matches before pagination
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+    pub hits: Vec<CertificateSearchHit>,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum VerificationChannel {
+    Qr,
+    Api,
+    Web,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct VerificationEvent {
+    pub certificate_id: String,
+    pub timestamp: u64,
+    // Salted hash of the caller principal; None for anonymous callers
+    pub caller_hash: Option<String>,
+    pub channel: VerificationChannel,
+    pub valid: bool,
+}
+
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+pub struct VerificationCounts {
+    pub qr: u64,
+    pub api: u64,
+    pub web: u64,
+    pub total: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct DailyVerificationCount {
+    // Start of the UTC day (nanoseconds)
+    pub day_start: u64,
+    pub counts: VerificationCounts,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct VerificationStats {
+    pub certificate_id: String,
+    pub total: VerificationCounts,
+    pub last_verified_at: Option<u64>,
+    pub daily: Vec<DailyVerificationCount>,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QrCodeFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    Low,      // ~7% recovery
+    Medium,   // ~15% recovery
+    Quartile, // ~25% recovery
+    High,     // ~30% recovery, best for printed labels that may get scuffed
+}
+
+#[derive(Clone, Debug, Default, CandidType, Serialize, Deserialize)]
+pub struct QrCodeOptions {
+    pub error_correction: Option<QrErrorCorrection>,
+    // Target image width/height in pixels; rounded down to a whole module size
+    pub size: Option<u32>,
+    pub quiet_zone: Option<bool>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateQrCode {
+    pub certificate_id: String,
+    pub format: QrCodeFormat,
+    pub content_type: String,
+    pub encoded_url: String,
+    pub size: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CertificateDocumentFormat {
+    Svg,
+    Pdf,
+}
+
+// Layout settings for printable certificates, configured per certificate_type
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateTemplate {
+    pub certificate_type: CertificateType,
+    pub heading: String,
+    pub accent_color: String,     // #RRGGBB
+    pub background_color: String, // #RRGGBB
+    pub footer_text: String,
+    pub show_scores: bool,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CertificateDocument {
+    pub certificate_id: String,
+    pub format: CertificateDocumentFormat,
+    pub content_type: String,
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CredentialVerificationResult {
+    // Signature is valid and the certificate is currently Active and unchanged
+    pub valid: bool,
+    pub signature_valid: bool,
+    pub certificate_id: Option<String>,
+    pub status: Option<CertificateStatus>,
+    pub errors: Vec<String>,
+}
+
+// Signed provenance manifest (JUMBF), see certificates/provenance.rs
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ProvenanceManifest {
+    pub certificate_id: String,
+    pub manifest_label: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+// Self-contained JSON document for offline verification (see docs/05-backend)
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct VerificationBundle {
+    pub certificate_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
 }
@@ -1,11 +1,13 @@
 // Re-export types from submodules
 mod certificate;
+mod http;
 mod nft;
 mod physical_art;
 mod s3;
 mod user;
 
 pub use certificate::*;
+pub use http::*;
 pub use nft::*;
 pub use physical_art::*;
 pub use s3::*;
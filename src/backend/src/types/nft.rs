@@ -1,7 +1,7 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CandidType, Serialize, Deserialize)]
 pub struct Account {
     pub owner: candid::Principal,
     pub subaccount: Option<Vec<u8>>,
@@ -28,30 +28,350 @@ pub struct Token {
     pub metadata: TokenMetadata,
     pub created_at: u64,
     pub session_id: Option<String>,
+    pub transfer_policy: TransferPolicy,
+}
+
+// Who may move a token between accounts; chosen at mint time
+#[derive(Clone, Debug, PartialEq, CandidType, Serialize, Deserialize)]
+pub enum TransferPolicy {
+    Transferable,
+    // Stays with the account it was minted to (it can still be burned)
+    Soulbound,
+    // Each transfer needs the artist to approve its recipient first
+    ArtistApproval,
+    // Transferable from this time on (nanoseconds since the epoch)
+    LockedUntil(u64),
+}
+
+// Generic metadata value shared by ICRC-3, ICRC-7 and ICRC-16
+#[derive(Clone, Debug, PartialEq, CandidType, Serialize, Deserialize)]
+pub enum Value {
+    Blob(Vec<u8>),
+    Text(String),
+    Nat(candid::Nat),
+    Int(candid::Int),
+    Array(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TransferArg {
+    pub from_subaccount: Option<Vec<u8>>,
+    pub to: Account,
+    pub token_id: candid::Nat,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum TransferError {
+    NonExistingTokenId,
+    InvalidRecipient,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    Duplicate {
+        duplicate_of: candid::Nat,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+// Ok carries the index of the transfer transaction
+pub type TransferResult = Result<candid::Nat, TransferError>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct BurnArg {
+    pub from_subaccount: Option<Vec<u8>>,
+    pub token_id: candid::Nat,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+    // Why the token is destroyed, recorded in the burn block
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum BurnError {
+    NonExistingTokenId,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    Duplicate {
+        duplicate_of: candid::Nat,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+// Ok carries the index of the burn transaction
+pub type BurnResult = Result<candid::Nat, BurnError>;
+
+// ICRC-37 approvals
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ApprovalInfo {
+    pub spender: Account,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub expires_at: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: u64,
+}
+
+// A collection approval covers every token the approving account holds
+pub type CollectionApproval = ApprovalInfo;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TokenApproval {
+    pub token_id: candid::Nat,
+    pub approval_info: ApprovalInfo,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ApproveTokenArg {
+    pub token_id: candid::Nat,
+    pub approval_info: ApprovalInfo,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum ApproveTokenError {
+    InvalidSpender,
+    Unauthorized,
+    NonExistingTokenId,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+pub type ApproveTokenResult = Result<candid::Nat, ApproveTokenError>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ApproveCollectionArg {
+    pub approval_info: ApprovalInfo,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum ApproveCollectionError {
+    InvalidSpender,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+pub type ApproveCollectionResult = Result<candid::Nat, ApproveCollectionError>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct RevokeTokenApprovalArg {
+    // None revokes every approval on the token
+    pub spender: Option<Account>,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub token_id: candid::Nat,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
 }
 
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
-pub struct TransferRequest {
+pub enum RevokeTokenApprovalError {
+    ApprovalDoesNotExist,
+    Unauthorized,
+    NonExistingTokenId,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+pub type RevokeTokenApprovalResponse = Result<candid::Nat, RevokeTokenApprovalError>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct RevokeCollectionApprovalArg {
+    // None revokes every collection approval of the account
+    pub spender: Option<Account>,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum RevokeCollectionApprovalError {
+    ApprovalDoesNotExist,
+    TooOld,
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    GenericError {
+        error_code: candid::Nat,
+        message: String,
+    },
+    GenericBatchError {
+        error_code: candid::Nat,
+        message: String,
+    },
+}
+
+pub type RevokeCollectionApprovalResult = Result<candid::Nat, RevokeCollectionApprovalError>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct IsApprovedArg {
+    pub spender: Account,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub token_id: candid::Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TransferFromArg {
+    pub spender_subaccount: Option<Vec<u8>>,
     pub from: Account,
     pub to: Account,
-    pub token_id: u64,
+    pub token_id: candid::Nat,
     pub memo: Option<Vec<u8>>,
     pub created_at_time: Option<u64>,
 }
 
+// TransferFromError has the same variants as TransferError
+pub type TransferFromResult = Result<candid::Nat, TransferError>;
+
+// ICRC-3 transaction log
+
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
-pub struct TransferResponse {
-    pub token_id: u64,
-    pub result: Result<(), String>,
+pub struct GetBlocksRequest {
+    pub start: candid::Nat,
+    pub length: candid::Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct BlockWithId {
+    pub id: candid::Nat,
+    pub block: Value,
+}
+
+candid::define_function!(pub GetBlocksCallback : (Vec<GetBlocksRequest>) -> (GetBlocksResult) query);
+
+// Blocks held by an archive canister, fetched by calling `callback` with `args`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ArchivedBlocks {
+    pub args: Vec<GetBlocksRequest>,
+    pub callback: GetBlocksCallback,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetBlocksResult {
+    pub log_length: candid::Nat,
+    pub blocks: Vec<BlockWithId>,
+    pub archived_blocks: Vec<ArchivedBlocks>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct GetArchivesArgs {
+    // Only archives after this canister are listed, for pagination
+    pub from: Option<candid::Principal>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+    pub canister_id: candid::Principal,
+    pub start: candid::Nat,
+    pub end: candid::Nat,
+}
+
+// When and how blocks move from the backend to archive canisters
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    // Archiving starts once more than this many blocks are held locally
+    pub trigger_threshold: u64,
+    // Blocks moved to an archive per round
+    pub num_blocks_to_archive: u64,
+    // Capacity of each spawned archive canister
+    pub max_blocks_per_archive: u64,
+    // Cycles attached when creating an archive canister
+    pub cycles_for_archive_creation: u64,
+}
+
+// A failed round of moving blocks to an archive canister
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct ArchiveFailure {
+    pub failed_at: u64,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct DataCertificate {
+    pub certificate: Vec<u8>,
+    pub hash_tree: Vec<u8>,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct SupportedBlockType {
+    pub block_type: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct SupportedStandard {
+    pub name: String,
+    pub url: String,
 }
 
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
 pub struct CollectionMetadata {
+    pub symbol: String,
     pub name: String,
     pub description: Option<String>,
     pub image: Option<String>,
     pub total_supply: u64,
     pub max_supply: Option<u64>,
+    // Policy of tokens minted without an explicit one
+    pub transfer_policy: TransferPolicy,
+}
+
+// One admin change to the collection metadata, with the values before and after
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CollectionMetadataChange {
+    pub changed_by: String,
+    pub changed_at: u64,
+    pub previous: CollectionMetadata,
+    pub updated: CollectionMetadata,
 }
 
 #[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
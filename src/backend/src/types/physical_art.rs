@@ -12,3 +12,11 @@ pub struct PhysicalArtSession {
     pub created_at: u64,
     pub updated_at: u64,
 }
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct PhotoEvidence {
+    pub photo_url: String,
+    // Hex SHA-256 of the uploaded file, reported by the uploading client
+    pub content_hash: Option<String>,
+    pub uploaded_at: u64,
+}
@@ -20,3 +20,77 @@ fn simple_hash(password: &str) -> String {
     let char_sum: u32 = password.chars().map(|c| c as u32).sum::<u32>();
     format!("{:x}", (password.len() as u32) * 42 + char_sum)
 }
+
+// Lowercase hex encoding
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// Decode hex (either case, optional "0x" prefix); None if malformed
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+// Format a nanosecond timestamp as "YYYY-MM-DD HH:MM UTC"
+pub fn format_timestamp(timestamp_ns: u64) -> String {
+    let (year, month, day, seconds_of_day) = civil_from_timestamp(timestamp_ns);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60
+    )
+}
+
+// Format a nanosecond timestamp as RFC 3339 ("YYYY-MM-DDTHH:MM:SSZ")
+pub fn format_rfc3339(timestamp_ns: u64) -> String {
+    let (year, month, day, seconds_of_day) = civil_from_timestamp(timestamp_ns);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+// (year, month, day, seconds of day) for a nanosecond UTC timestamp
+fn civil_from_timestamp(timestamp_ns: u64) -> (i64, i64, i64, u64) {
+    let seconds = timestamp_ns / 1_000_000_000;
+    let days = (seconds / 86_400) as i64;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day, seconds % 86_400)
+}
+
+// Canonical JSON (RFC 8785 / JCS): object keys sorted by UTF-16 code units,
+// no insignificant whitespace. Floats are not normalized, so callers should
+// only canonicalize documents holding strings, integers, booleans and null.
+// Shared with the offline verifier so bundle signatures check out on both sides.
+pub use originstamp_verifier::canonical_json;
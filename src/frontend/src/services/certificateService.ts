@@ -13,12 +13,18 @@ export interface CertificateData {
   blockchain_tx: string;
   qr_code_data: string;
   verification_url: string;
-  certificate_type: "standard" | "premium";
+  certificate_type: CertificateType;
+  type_details: CertificateTypeDetails;
   verification_score: number;
   authenticity_rating: number;
   provenance_score: number;
   community_trust: number;
-  certificate_status: "active" | "expired" | "revoked";
+  certificate_status:
+    | "active"
+    | "suspended"
+    | "revoked"
+    | "expired"
+    | "superseded";
   issuer: string;
   blockchain: string;
   token_standard: string;
@@ -35,6 +41,29 @@ export interface CertificateData {
   token_uri?: string;
 }
 
+export type CertificateType =
+  | "standard"
+  | "limited_edition"
+  | "commission"
+  | "restoration"
+  | "digital";
+
+// Candid variant carrying type-specific issuance data
+export type CertificateTypeDetails =
+  | { Standard: null }
+  | { LimitedEdition: { edition_number: number; edition_size: number } }
+  | {
+      Commission: { commissioned_by: string; commissioned_at: [] | [bigint] };
+    }
+  | {
+      Restoration: {
+        original_artist: string;
+        original_year: [] | [number];
+        scope: string;
+      };
+    }
+  | { Digital: { master_file_hash: string } };
+
 export interface CreateCertificateRequest {
   session_id: string;
   username: string;
@@ -45,6 +74,7 @@ export interface CreateCertificateRequest {
   file_format: string;
   creation_tools: string[];
   file_sizes: bigint[]; // Actual file sizes in bytes (bigint for Candid compatibility)
+  type_details?: CertificateTypeDetails; // Defaults to a standard certificate
 }
 
 /**
@@ -68,6 +98,7 @@ export class CertificateService {
         file_format: request.file_format,
         creation_tools: request.creation_tools,
         file_sizes: request.file_sizes,
+        type_details: request.type_details ? [request.type_details] : [],
       });
 
       if ("Ok" in result) {
@@ -110,9 +141,13 @@ export class CertificateService {
   }
 
   /**
-   * Verify certificate
+   * Verify certificate (query) and record the check in its verification log
+   * without waiting for the update
    */
-  static async verifyCertificate(certificateId: string): Promise<{
+  static async verifyCertificate(
+    certificateId: string,
+    channel: "qr" | "web" | "api" = "web",
+  ): Promise<{
     valid: boolean;
     score: number;
     details: any;
@@ -120,10 +155,11 @@ export class CertificateService {
     try {
       const result = await backend.verify_certificate(certificateId);
       if ("Ok" in result) {
+        this.recordVerification(certificateId, channel);
         return {
           valid: result.Ok.valid,
-          score: result.Ok.score,
-          details: JSON.parse(result.Ok.details),
+          score: result.Ok.scores.verification_score,
+          details: result.Ok,
         };
       } else {
         return {
@@ -141,6 +177,23 @@ export class CertificateService {
     }
   }
 
+  // Analytics only: a failed or slow update must not delay or change the result
+  private static recordVerification(
+    certificateId: string,
+    channel: "qr" | "web" | "api",
+  ): void {
+    backend
+      .record_certificate_verification(
+        certificateId,
+        channel === "qr"
+          ? { Qr: null }
+          : channel === "api"
+            ? { Api: null }
+            : { Web: null },
+      )
+      .catch(() => undefined);
+  }
+
   // Generate NFT for certificate using NFT Module
   static async generateNFT(
     certificateId: string,
@@ -198,6 +251,7 @@ export class CertificateService {
       const result = await backend.mint_certificate_nft(
         certificateId,
         recipient,
+        [],
       );
 
       if ("Ok" in result) {
@@ -378,12 +432,19 @@ export class CertificateService {
       blockchain_tx: backendCert.blockchain_tx,
       qr_code_data: backendCert.qr_code_data,
       verification_url: backendCert.verification_url,
-      certificate_type: backendCert.certificate_type,
+      // LimitedEdition -> limited_edition
+      certificate_type: Object.keys(backendCert.certificate_type)[0]
+        .replace(/([a-z])([A-Z])/g, "$1_$2")
+        .toLowerCase() as CertificateType,
+      type_details: backendCert.type_details,
       verification_score: Number(backendCert.verification_score),
       authenticity_rating: Number(backendCert.authenticity_rating),
       provenance_score: Number(backendCert.provenance_score),
       community_trust: Number(backendCert.community_trust),
-      certificate_status: backendCert.certificate_status,
+      // Candid variants arrive as { Active: null }
+      certificate_status: Object.keys(
+        backendCert.certificate_status,
+      )[0].toLowerCase() as CertificateData["certificate_status"],
       issuer: backendCert.issuer,
       blockchain: backendCert.blockchain,
       token_standard: backendCert.token_standard,
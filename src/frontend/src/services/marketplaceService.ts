@@ -7,7 +7,7 @@ import type {
   CreateNFTData,
 } from "../types/marketplace";
 import { backend } from "../../../declarations/backend";
-import type { Token } from "../../../declarations/backend/backend.did";
+import type { Token, Value } from "../../../declarations/backend/backend.did";
 
 /**
  * Marketplace Service - Handles all marketplace operations
@@ -45,6 +45,17 @@ export class MarketplaceService {
     }
   }
 
+  /**
+   * Read a text entry from ICRC-7 metadata
+   */
+  private static getMetadataText(
+    metadata: Array<[string, Value]>,
+    key: string,
+  ): string | undefined {
+    const value = metadata.find(([entryKey]) => entryKey === key)?.[1];
+    return value && "Text" in value ? value.Text : undefined;
+  }
+
   /**
    * Helper function to convert backend Token to frontend NFT type
    */
@@ -243,26 +254,23 @@ export class MarketplaceService {
 
       // Fallback: Create a single collection with all NFTs if no session data
       const collectionMetadata = await backend.icrc7_collection_metadata();
+      const name =
+        this.getMetadataText(collectionMetadata, "icrc7:name") ||
+        "Origin Stamp Art NFTs";
+      const totalSupply = collectionMetadata.find(
+        ([key]) => key === "icrc7:total_supply",
+      )?.[1];
 
       const fallbackCollection: Collection = {
         id: "1",
-        name: collectionMetadata.name,
+        name,
         description:
-          (Array.isArray(collectionMetadata.description)
-            ? collectionMetadata.description.length > 0
-              ? collectionMetadata.description[0]
-              : "NFT collection from Origin Stamp"
-            : collectionMetadata.description ||
-              "NFT collection from Origin Stamp") ||
+          this.getMetadataText(collectionMetadata, "icrc7:description") ||
           "NFT collection from Origin Stamp",
         coverImage:
-          (Array.isArray(collectionMetadata.image)
-            ? collectionMetadata.image.length > 0
-              ? collectionMetadata.image[0]
-              : ""
-            : collectionMetadata.image || "") ||
+          this.getMetadataText(collectionMetadata, "icrc7:logo") ||
           "https://via.placeholder.com/600x400/4A5568/ffffff?text=" +
-            encodeURIComponent(collectionMetadata.name),
+            encodeURIComponent(name),
         creator: {
           username: "Origin Stamp",
           avatar: "",
@@ -275,7 +283,10 @@ export class MarketplaceService {
         },
         nfts: nfts,
         stats: {
-          totalItems: Number(collectionMetadata.total_supply),
+          totalItems:
+            totalSupply && "Nat" in totalSupply
+              ? Number(totalSupply.Nat)
+              : nfts.length,
           floorPrice: "0",
           totalVolume: "0",
           owners: new Set(nfts.map((nft) => nft.creator.username)).size,
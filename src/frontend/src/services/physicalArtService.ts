@@ -227,7 +227,21 @@ export class PhysicalArtService {
         );
 
         if ("Ok" in recordResult && recordResult.Ok) {
-          // Backend record successful
+          // Backend record successful; attach the content hash as provenance
+          // evidence (best effort, the upload itself already succeeded)
+          try {
+            const digest = await crypto.subtle.digest("SHA-256", fileBuffer);
+            const contentHash = Array.from(new Uint8Array(digest))
+              .map((byte) => byte.toString(16).padStart(2, "0"))
+              .join("");
+            await backend.record_photo_hash(sessionId, fileUrl, contentHash);
+          } catch (hashError) {
+            console.warn(
+              `[S3Upload] Failed to record content hash:`,
+              hashError,
+            );
+          }
+
           return {
             success: true,
             message: "Photo uploaded successfully",
@@ -9,6 +9,7 @@ vi.mock("../../../declarations/backend", () => ({
     get_user_sessions: vi.fn(),
     update_session_status: vi.fn(),
     upload_photo_to_session: vi.fn(),
+    record_photo_hash: vi.fn(),
     remove_photo_from_session: vi.fn(),
   },
 }));
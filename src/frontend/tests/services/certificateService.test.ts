@@ -8,6 +8,7 @@ vi.mock("../../../declarations/backend", () => ({
     get_certificate_by_id: vi.fn(),
     get_user_certificates: vi.fn(),
     verify_certificate: vi.fn(),
+    record_certificate_verification: vi.fn(),
     mint_certificate_nft: vi.fn(),
     get_certificate_nft_metadata: vi.fn(),
   },
@@ -54,15 +55,16 @@ describe("CertificateService", () => {
         blockchain_tx: "0xabc",
         qr_code_data: "https://verify.example.com/CERT-123",
         verification_url: "https://verify.example.com/CERT-123",
-        certificate_type: "standard",
+        certificate_type: { Standard: null },
+        type_details: { Standard: null },
         verification_score: 85,
         authenticity_rating: 90,
         provenance_score: 88,
         community_trust: 82,
-        certificate_status: "active",
-        issuer: "IC-Vibe",
+        certificate_status: { Active: null },
+        issuer: "OriginStamp",
         blockchain: "Internet Computer",
-        token_standard: "ICP-721",
+        token_standard: "ICRC-7",
         metadata: {
           creation_duration: "2 hours 30 minutes",
           total_actions: 5,
@@ -97,10 +99,14 @@ describe("CertificateService", () => {
 
       const result = await CertificateService.generateCertificate(request);
 
-      expect(backend.generate_certificate).toHaveBeenCalledWith(request);
+      expect(backend.generate_certificate).toHaveBeenCalledWith({
+        ...request,
+        type_details: [],
+      });
       expect(result.certificate_id).toBe("CERT-123");
       expect(result.username).toBe("testuser");
       expect(result.art_title).toBe("Test Art");
+      expect(result.certificate_type).toBe("standard");
     });
 
     it("should handle certificate generation errors", async () => {
@@ -147,15 +153,16 @@ describe("CertificateService", () => {
         blockchain_tx: "0xabc",
         qr_code_data: "https://verify.example.com/CERT-123",
         verification_url: "https://verify.example.com/CERT-123",
-        certificate_type: "standard",
+        certificate_type: { Standard: null },
+        type_details: { Standard: null },
         verification_score: 85,
         authenticity_rating: 90,
         provenance_score: 88,
         community_trust: 82,
-        certificate_status: "active",
-        issuer: "IC-Vibe",
+        certificate_status: { Active: null },
+        issuer: "OriginStamp",
         blockchain: "Internet Computer",
-        token_standard: "ICP-721",
+        token_standard: "ICRC-7",
         metadata: {
           creation_duration: "2 hours 30 minutes",
           total_actions: 5,
@@ -201,15 +208,16 @@ describe("CertificateService", () => {
           blockchain_tx: "0xabc",
           qr_code_data: "https://verify.example.com/CERT-123",
           verification_url: "https://verify.example.com/CERT-123",
-          certificate_type: "standard",
+          certificate_type: { Standard: null },
+          type_details: { Standard: null },
           verification_score: 85,
           authenticity_rating: 90,
           provenance_score: 88,
           community_trust: 82,
-          certificate_status: "active",
-          issuer: "IC-Vibe",
+          certificate_status: { Active: null },
+          issuer: "OriginStamp",
           blockchain: "Internet Computer",
-          token_standard: "ICP-721",
+          token_standard: "ICRC-7",
           metadata: {
             creation_duration: "2 hours 30 minutes",
             total_actions: 5,
@@ -236,22 +244,37 @@ describe("CertificateService", () => {
   describe("verifyCertificate", () => {
     it("should verify certificate successfully", async () => {
       const mockVerification = {
+        certificate_id: "CERT-123",
         valid: true,
-        score: 85,
-        details: '{"verified": true, "timestamp": 1234567890}',
+        status: { Active: null },
+        scores: {
+          verification_score: 85,
+          authenticity_rating: 90,
+          provenance_score: 88,
+          community_trust: 82,
+        },
+        revocation: [],
+        checked_at: BigInt(1234567890),
       };
 
       const { backend } = await import("../../../declarations/backend");
       (backend.verify_certificate as any).mockResolvedValue({
         Ok: mockVerification,
       });
+      (backend.record_certificate_verification as any).mockResolvedValue({
+        Ok: mockVerification,
+      });
 
       const result = await CertificateService.verifyCertificate("CERT-123");
 
       expect(backend.verify_certificate).toHaveBeenCalledWith("CERT-123");
+      expect(backend.record_certificate_verification).toHaveBeenCalledWith(
+        "CERT-123",
+        { Web: null },
+      );
       expect(result.valid).toBe(true);
       expect(result.score).toBe(85);
-      expect(result.details.verified).toBe(true);
+      expect(result.details.status).toEqual({ Active: null });
     });
 
     it("should handle invalid certificate", async () => {
@@ -265,6 +288,39 @@ describe("CertificateService", () => {
       expect(result.valid).toBe(false);
       expect(result.score).toBe(0);
       expect(result.details.error).toBe("Certificate not found");
+      expect(backend.record_certificate_verification).not.toHaveBeenCalled();
+    });
+
+    it("should record the verification channel", async () => {
+      const { backend } = await import("../../../declarations/backend");
+      (backend.verify_certificate as any).mockResolvedValue({
+        Ok: { valid: true, scores: { verification_score: 85 } },
+      });
+      (backend.record_certificate_verification as any).mockResolvedValue({
+        Ok: {},
+      });
+
+      await CertificateService.verifyCertificate("CERT-123", "qr");
+
+      expect(backend.record_certificate_verification).toHaveBeenCalledWith(
+        "CERT-123",
+        { Qr: null },
+      );
+    });
+
+    it("should not let a failed analytics update affect the result", async () => {
+      const { backend } = await import("../../../declarations/backend");
+      (backend.verify_certificate as any).mockResolvedValue({
+        Ok: { valid: true, scores: { verification_score: 85 } },
+      });
+      (backend.record_certificate_verification as any).mockRejectedValue(
+        new Error("Rate limited"),
+      );
+
+      const result = await CertificateService.verifyCertificate("CERT-123");
+
+      expect(result.valid).toBe(true);
+      expect(result.score).toBe(85);
     });
   });
 
@@ -315,6 +371,7 @@ describe("CertificateService", () => {
           owner: expect.any(Object),
           subaccount: [],
         }),
+        [],
       );
       expect(result.nft_id).toBe("NFT-CERT-123-456");
       expect(result.token_uri).toBe(
@@ -13,6 +13,7 @@ vi.mock("../../../declarations/backend", () => ({
   backend: {
     create_physical_art_session: vi.fn(),
     upload_photo_to_session: vi.fn(),
+    record_photo_hash: vi.fn(),
     get_s3_config: vi.fn(),
   },
 }));
@@ -87,6 +88,13 @@ describe("PhysicalArtService - AWS S3 Integration", () => {
       expect(result.success).toBe(true);
       expect(result.message).toBe("Photo uploaded successfully");
       expect(result.file_url).toContain(mockS3Config.bucket_name);
+
+      // Verify the SHA-256 of the uploaded bytes was recorded as evidence
+      expect(backend.record_photo_hash).toHaveBeenCalledWith(
+        "test-session",
+        result.file_url,
+        expect.stringMatching(/^[0-9a-f]{64}$/),
+      );
     });
 
     it("should handle S3 configuration not found", async () => {
@@ -0,0 +1,434 @@
+// Offline verifier for OriginStamp verification bundles
+// (format "originstamp-verification-bundle", version 2).
+//
+// Everything is checked from the bundle itself; no canister or network access.
+// See docs/05-backend/07-verification-bundle.md for the format.
+//
+// The canister builds bundles with the evidence_leaf, merkle_root and
+// canonical_json defined here, so both sides hash the same way.
+
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature, VerifyingKey};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub const BUNDLE_FORMAT: &str = "originstamp-verification-bundle";
+pub const BUNDLE_VERSION: u64 = 2;
+// Largest integer an IEEE 754 double holds exactly (2^53 - 1)
+pub const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    // Every check passed against a pinned issuer key
+    Valid,
+    // Internally consistent, but only checked against the key the bundle
+    // carries, so anyone could have produced it
+    Unauthenticated,
+    Invalid,
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub certificate_id: String,
+    pub checks: Vec<Check>,
+    // Whether signatures were checked against a key supplied by the caller
+    pub key_pinned: bool,
+}
+
+impl Report {
+    pub fn verdict(&self) -> Verdict {
+        if self
+            .checks
+            .iter()
+            .any(|check| check.status == CheckStatus::Fail)
+        {
+            Verdict::Invalid
+        } else if !self.key_pinned {
+            Verdict::Unauthenticated
+        } else {
+            Verdict::Valid
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.verdict() == Verdict::Valid
+    }
+
+    fn push(&mut self, name: &'static str, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name,
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    fn check(&mut self, name: &'static str, passed: bool, pass: &str, fail: &str) {
+        if passed {
+            self.push(name, CheckStatus::Pass, pass);
+        } else {
+            self.push(name, CheckStatus::Fail, fail);
+        }
+    }
+}
+
+// Verify a bundle. `pinned_public_key` is the SEC1 key the verifier trusts; without
+// it the key embedded in the bundle is used and the verdict is at best
+// Unauthenticated.
+pub fn verify_bundle(
+    bundle_json: &str,
+    pinned_public_key: Option<&[u8]>,
+) -> Result<Report, String> {
+    let bundle: Value =
+        serde_json::from_str(bundle_json).map_err(|e| format!("Invalid bundle JSON: {e}"))?;
+    if bundle["format"] != BUNDLE_FORMAT || bundle["version"] != BUNDLE_VERSION {
+        return Err(format!(
+            "Unsupported bundle: expected {BUNDLE_FORMAT} version {BUNDLE_VERSION}"
+        ));
+    }
+
+    let certificate = &bundle["certificate"];
+    let mut report = Report {
+        certificate_id: certificate["certificate_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        checks: Vec::new(),
+        key_pinned: pinned_public_key.is_some(),
+    };
+
+    // Public key
+    let bundle_key = bundle["public_key"]["sec1"]
+        .as_str()
+        .and_then(from_hex)
+        .unwrap_or_default();
+    let public_key = match pinned_public_key {
+        Some(pinned) if pinned == bundle_key.as_slice() => {
+            report.push("public_key", CheckStatus::Pass, "matches the pinned key");
+            pinned.to_vec()
+        }
+        Some(pinned) => {
+            report.push(
+                "public_key",
+                CheckStatus::Fail,
+                "bundle key differs from the pinned key; verifying with the pinned key",
+            );
+            pinned.to_vec()
+        }
+        None => {
+            report.push(
+                "public_key",
+                CheckStatus::Warn,
+                "not pinned; using the key embedded in the bundle, which proves nothing about the issuer",
+            );
+            bundle_key
+        }
+    };
+    let verifying_key = VerifyingKey::from_sec1_bytes(&public_key).ok();
+    if verifying_key.is_none() {
+        report.push("public_key", CheckStatus::Fail, "not a valid secp256k1 key");
+    }
+
+    // Signing payload must describe the certificate it came with; the bundle
+    // carries large integers as strings, so compare in that form
+    let payload = bundle["signing_payload"].as_str().unwrap_or_default();
+    let payload_matches = serde_json::from_str::<Value>(payload)
+        .ok()
+        .map(|mut payload| {
+            stringify_large_integers(&mut payload);
+            payload
+        })
+        .and_then(|payload| payload.as_object().cloned())
+        .is_some_and(|fields| {
+            !fields.is_empty()
+                && fields
+                    .iter()
+                    .all(|(key, value)| certificate.get(key) == Some(value))
+        });
+    report.check(
+        "signing_payload",
+        payload_matches,
+        "matches the certificate fields",
+        "does not match the certificate fields",
+    );
+
+    let expected_hash = format!("0x{}", to_hex(&Sha256::digest(payload.as_bytes())));
+    report.check(
+        "verification_hash",
+        certificate["verification_hash"] == expected_hash.as_str(),
+        "SHA-256 of the signing payload",
+        "does not match the signing payload",
+    );
+
+    let signature = certificate["signature"]
+        .as_str()
+        .and_then(from_hex)
+        .unwrap_or_default();
+    report.check(
+        "certificate_signature",
+        verify_signature(verifying_key.as_ref(), payload.as_bytes(), &signature),
+        "valid for the signing payload",
+        "invalid for the signing payload",
+    );
+
+    let mut tx_hasher = Sha256::new();
+    tx_hasher.update(payload.as_bytes());
+    tx_hasher.update(&signature);
+    let expected_tx = format!("0x{}", to_hex(&tx_hasher.finalize()));
+    report.check(
+        "blockchain_tx",
+        certificate["blockchain_tx"] == expected_tx.as_str(),
+        "SHA-256 of payload and signature",
+        "does not match payload and signature",
+    );
+
+    // Evidence: leaves recomputed from the hashed photo records, root from the
+    // leaves. Photos without a recorded hash are listed but not attested.
+    let photos = bundle["evidence"]["photos"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let leaves: Vec<[u8; 32]> = photos
+        .iter()
+        .filter_map(|photo| {
+            Some(evidence_leaf(
+                json_u64(&photo["uploaded_at"]).unwrap_or_default(),
+                photo["content_hash"].as_str()?,
+                photo["photo_url"].as_str().unwrap_or_default(),
+            ))
+        })
+        .collect();
+    let listed_leaves: Vec<String> = bundle["evidence"]["merkle"]["leaves"]
+        .as_array()
+        .map(|leaves| {
+            leaves
+                .iter()
+                .filter_map(|leaf| leaf.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let leaves_match = listed_leaves == leaves.iter().map(|leaf| to_hex(leaf)).collect::<Vec<_>>();
+    let root_matches =
+        bundle["evidence"]["merkle"]["root"] == to_hex(&merkle_root(&leaves)).as_str();
+    report.check(
+        "evidence_merkle",
+        leaves_match && root_matches,
+        &format!(
+            "{} hashed evidence leaves of {} photos, root matches",
+            leaves.len(),
+            photos.len()
+        ),
+        "evidence leaves or Merkle root do not match the photo records",
+    );
+
+    // Bundle signature over everything else. JCS rounds numbers to doubles, so
+    // an integer above 2^53 could be altered without breaking the signature.
+    let mut unsigned = bundle.clone();
+    let bundle_signature = unsigned
+        .as_object_mut()
+        .and_then(|fields| fields.remove("bundle_signature"))
+        .and_then(|signature| signature["value"].as_str().and_then(from_hex))
+        .unwrap_or_default();
+    report.check(
+        "exact_integers",
+        !has_large_integers(&unsigned),
+        "every number is exactly covered by the bundle signature",
+        "contains integers above 2^53, which the bundle signature does not cover exactly",
+    );
+    report.check(
+        "bundle_signature",
+        verify_signature(
+            verifying_key.as_ref(),
+            canonical_json(&unsigned).as_bytes(),
+            &bundle_signature,
+        ),
+        "covers evidence, process log and NFT snapshot",
+        "invalid; bundle contents were modified",
+    );
+
+    // Status as of the time the bundle was generated
+    let generated_at = json_u64(&bundle["generated_at"]).unwrap_or_default();
+    let expiry_date = json_u64(&certificate["expiry_date"]).unwrap_or_default();
+    match certificate["certificate_status"].as_str() {
+        Some("Active") if expiry_date > generated_at => report.push(
+            "status",
+            CheckStatus::Pass,
+            "active when the bundle was generated",
+        ),
+        Some("Active") => report.push("status", CheckStatus::Fail, "expired"),
+        Some(status) => report.push("status", CheckStatus::Fail, status.to_lowercase()),
+        None => report.push("status", CheckStatus::Fail, "missing certificate status"),
+    }
+
+    Ok(report)
+}
+
+// The canister signs SHA-256(message) with ECDSA over secp256k1 (r || s)
+fn verify_signature(key: Option<&VerifyingKey>, message: &[u8], signature: &[u8]) -> bool {
+    let (Some(key), Ok(signature)) = (key, Signature::from_slice(signature)) else {
+        return false;
+    };
+    let signature = signature.normalize_s().unwrap_or(signature);
+    key.verify_prehash(&Sha256::digest(message), &signature)
+        .is_ok()
+}
+
+// Leaf = SHA-256(0x00 || "{uploaded_at}\n{content_hash}\n{photo_url}")
+pub fn evidence_leaf(uploaded_at: u64, content_hash: &str, photo_url: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(format!("{uploaded_at}\n{content_hash}\n{photo_url}"));
+    hasher.finalize().into()
+}
+
+// Node = SHA-256(0x01 || left || right); an odd node is carried up unchanged.
+// The root of an empty tree is SHA-256 of the empty string.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return Sha256::digest(b"").into();
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update([0x01]);
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+// Replace integers a double cannot hold exactly (such as nanosecond timestamps)
+// with decimal strings, so JCS canonicalization keeps every digit
+pub fn stringify_large_integers(value: &mut Value) {
+    match value {
+        Value::Number(number) if is_large_integer(number) => {
+            *value = Value::String(number.to_string());
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_large_integers),
+        Value::Object(fields) => fields.values_mut().for_each(stringify_large_integers),
+        _ => {}
+    }
+}
+
+fn has_large_integers(value: &Value) -> bool {
+    match value {
+        Value::Number(number) => is_large_integer(number),
+        Value::Array(items) => items.iter().any(has_large_integers),
+        Value::Object(fields) => fields.values().any(has_large_integers),
+        _ => false,
+    }
+}
+
+fn is_large_integer(number: &serde_json::Number) -> bool {
+    number
+        .as_u64()
+        .or_else(|| number.as_i64().map(i64::unsigned_abs))
+        .is_some_and(|value| value > MAX_SAFE_INTEGER)
+}
+
+// Integer stored either as a JSON number or, above 2^53, as a decimal string
+fn json_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|value| value.parse().ok()))
+}
+
+// RFC 8785 (JCS) canonical JSON
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            let members: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::String(key.clone()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", members.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::Number(number) => canonical_number(number),
+        other => other.to_string(),
+    }
+}
+
+// JCS numbers are the ECMAScript Number::toString of the IEEE 754 double, so
+// integers above 2^53 are rounded like any JSON parser in a browser would
+fn canonical_number(number: &serde_json::Number) -> String {
+    let value = number.as_f64().unwrap_or_default();
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    // Shortest round-trip digits and decimal exponent, e.g. "1.7e18"
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+    let formatted = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat(-n as usize))
+    } else {
+        let sign = if n > 0 { "+" } else { "-" };
+        let mantissa = if k == 1 {
+            digits
+        } else {
+            format!("{}.{}", &digits[..1], &digits[1..])
+        };
+        format!("{mantissa}e{sign}{}", (n - 1).abs())
+    };
+
+    if value < 0.0 {
+        format!("-{formatted}")
+    } else {
+        formatted
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,70 @@
+// originstamp-verify <bundle.json> [--public-key <hex>]
+//
+// Exit codes: 0 valid, 1 invalid, 2 usage or read error, 3 consistent but not
+// authenticated (no --public-key).
+
+use originstamp_verifier::{from_hex, verify_bundle, CheckStatus, Verdict};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: originstamp-verify <bundle.json> [--public-key <sec1 hex>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, public_key) = match args.as_slice() {
+        [path] => (path, None),
+        [path, flag, key] if flag == "--public-key" => match from_hex(key) {
+            Some(key) => (path, Some(key)),
+            None => {
+                eprintln!("--public-key must be hex encoded");
+                return ExitCode::from(2);
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let bundle = match std::fs::read_to_string(path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            eprintln!("Failed to read {path}: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = match verify_bundle(&bundle, public_key.as_deref()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(1);
+        }
+    };
+
+    println!("Certificate {}", report.certificate_id);
+    for check in &report.checks {
+        let label = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("[{label}] {}: {}", check.name, check.detail);
+    }
+
+    match report.verdict() {
+        Verdict::Valid => {
+            println!("Bundle is valid");
+            ExitCode::SUCCESS
+        }
+        Verdict::Unauthenticated => {
+            println!(
+                "Bundle is consistent but NOT authenticated; pin the issuer key with --public-key"
+            );
+            ExitCode::from(3)
+        }
+        Verdict::Invalid => {
+            println!("Bundle is NOT valid");
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{Signature, SigningKey};
+use originstamp_verifier::{
+    canonical_json, evidence_leaf, merkle_root, stringify_large_integers, to_hex, verify_bundle,
+    CheckStatus, Verdict, BUNDLE_FORMAT, BUNDLE_VERSION,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+const GENERATED_AT: u64 = 1_700_000_000_000_000_000;
+
+fn issuer_key() -> SigningKey {
+    SigningKey::from_slice(&[7u8; 32]).unwrap()
+}
+
+fn public_key(key: &SigningKey) -> Vec<u8> {
+    key.verifying_key()
+        .to_encoded_point(true)
+        .as_bytes()
+        .to_vec()
+}
+
+// Same scheme as the canister: ECDSA over SHA-256(message), r || s
+fn sign(key: &SigningKey, message: &[u8]) -> Vec<u8> {
+    let signature: Signature = key.sign_prehash(&Sha256::digest(message)).unwrap();
+    signature.to_bytes().to_vec()
+}
+
+// A bundle shaped like export_verification_bundle output, signed by `key`
+fn signed_bundle(key: &SigningKey) -> Value {
+    let payload = json!({
+        "certificate_id": "CERT-1",
+        "art_title": "Sunrise",
+        "issue_date": GENERATED_AT - 1,
+        "expiry_date": GENERATED_AT + 1_000,
+    })
+    .to_string();
+    let signature = sign(key, payload.as_bytes());
+    let mut tx_hasher = Sha256::new();
+    tx_hasher.update(payload.as_bytes());
+    tx_hasher.update(&signature);
+
+    let photos = vec![
+        json!({ "photo_url": "https://example.com/1.jpg", "content_hash": "ab".repeat(32), "uploaded_at": 10 }),
+        json!({ "photo_url": "https://example.com/2.jpg", "content_hash": null, "uploaded_at": 20 }),
+        json!({ "photo_url": "https://example.com/3.jpg", "content_hash": "cd".repeat(32), "uploaded_at": 30 }),
+    ];
+    // Only hashed evidence is attested by the Merkle root
+    let leaves: Vec<[u8; 32]> = photos
+        .iter()
+        .filter_map(|photo| {
+            Some(evidence_leaf(
+                photo["uploaded_at"].as_u64().unwrap(),
+                photo["content_hash"].as_str()?,
+                photo["photo_url"].as_str().unwrap(),
+            ))
+        })
+        .collect();
+
+    let mut bundle = json!({
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "generated_at": GENERATED_AT,
+        "certificate": {
+            "certificate_id": "CERT-1",
+            "art_title": "Sunrise",
+            "issue_date": GENERATED_AT - 1,
+            "expiry_date": GENERATED_AT + 1_000,
+            "certificate_status": "Active",
+            "verification_hash": format!("0x{}", to_hex(&Sha256::digest(payload.as_bytes()))),
+            "signature": to_hex(&signature),
+            "blockchain_tx": format!("0x{}", to_hex(&tx_hasher.finalize())),
+        },
+        "signing_payload": payload,
+        "public_key": { "sec1": to_hex(&public_key(key)) },
+        "evidence": {
+            "photos": photos,
+            "merkle": {
+                "algorithm": "sha256",
+                "leaves": leaves.iter().map(|leaf| to_hex(leaf)).collect::<Vec<_>>(),
+                "root": to_hex(&merkle_root(&leaves)),
+            },
+        },
+    });
+    resign(&mut bundle, key);
+    bundle
+}
+
+fn resign(bundle: &mut Value, key: &SigningKey) {
+    bundle.as_object_mut().unwrap().remove("bundle_signature");
+    stringify_large_integers(bundle);
+    let signature = sign(key, canonical_json(bundle).as_bytes());
+    bundle["bundle_signature"] = json!({ "value": to_hex(&signature) });
+}
+
+fn status_of(bundle: &Value, pinned: Option<&[u8]>, check: &str) -> CheckStatus {
+    let report = verify_bundle(&bundle.to_string(), pinned).unwrap();
+    report
+        .checks
+        .iter()
+        .find(|c| c.name == check)
+        .map(|c| c.status)
+        .unwrap()
+}
+
+#[test]
+fn round_trip_bundle_is_valid_with_pinned_key() {
+    let key = issuer_key();
+    let report = verify_bundle(&signed_bundle(&key).to_string(), Some(&public_key(&key))).unwrap();
+    assert_eq!(report.certificate_id, "CERT-1");
+    assert_eq!(report.verdict(), Verdict::Valid, "{:?}", report.checks);
+    assert!(report.is_valid());
+}
+
+#[test]
+fn unpinned_key_is_never_valid() {
+    // A forger signs with their own key and embeds it in the bundle
+    let forger = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let report = verify_bundle(&signed_bundle(&forger).to_string(), None).unwrap();
+    assert_eq!(report.verdict(), Verdict::Unauthenticated);
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn wrong_pinned_key_is_invalid() {
+    let key = issuer_key();
+    let other = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let bundle = signed_bundle(&key);
+    let report = verify_bundle(&bundle.to_string(), Some(&public_key(&other))).unwrap();
+    assert_eq!(report.verdict(), Verdict::Invalid);
+    assert_eq!(
+        status_of(&bundle, Some(&public_key(&other)), "public_key"),
+        CheckStatus::Fail
+    );
+    assert_eq!(
+        status_of(&bundle, Some(&public_key(&other)), "certificate_signature"),
+        CheckStatus::Fail
+    );
+}
+
+#[test]
+fn tampered_payload_is_invalid() {
+    let key = issuer_key();
+    let mut bundle = signed_bundle(&key);
+    // Change the payload and the certificate together, and re-sign the bundle,
+    // so only the certificate signature can catch it
+    let payload = bundle["signing_payload"]
+        .as_str()
+        .unwrap()
+        .replace("Sunrise", "Sunset");
+    bundle["signing_payload"] = json!(payload);
+    bundle["certificate"]["art_title"] = json!("Sunset");
+    bundle["certificate"]["verification_hash"] =
+        json!(format!("0x{}", to_hex(&Sha256::digest(payload.as_bytes()))));
+    resign(&mut bundle, &key);
+
+    let pinned = public_key(&key);
+    let report = verify_bundle(&bundle.to_string(), Some(&pinned)).unwrap();
+    assert_eq!(report.verdict(), Verdict::Invalid);
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "certificate_signature"),
+        CheckStatus::Fail
+    );
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "signing_payload"),
+        CheckStatus::Pass
+    );
+}
+
+#[test]
+fn tampered_evidence_leaf_is_invalid() {
+    let key = issuer_key();
+    let pinned = public_key(&key);
+    let mut bundle = signed_bundle(&key);
+    bundle["evidence"]["photos"][1]["content_hash"] = json!("ef".repeat(32));
+
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "evidence_merkle"),
+        CheckStatus::Fail
+    );
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "bundle_signature"),
+        CheckStatus::Fail
+    );
+}
+
+#[test]
+fn tampered_merkle_root_is_invalid() {
+    let key = issuer_key();
+    let pinned = public_key(&key);
+    let mut bundle = signed_bundle(&key);
+    bundle["evidence"]["merkle"]["root"] = json!("00".repeat(32));
+    // Even a bundle re-signed with the issuer key cannot hide a wrong root
+    resign(&mut bundle, &key);
+
+    let report = verify_bundle(&bundle.to_string(), Some(&pinned)).unwrap();
+    assert_eq!(report.verdict(), Verdict::Invalid);
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "evidence_merkle"),
+        CheckStatus::Fail
+    );
+}
+
+#[test]
+fn unhashed_photos_are_not_evidence_leaves() {
+    let key = issuer_key();
+    let bundle = signed_bundle(&key);
+    assert_eq!(
+        bundle["evidence"]["merkle"]["leaves"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let report = verify_bundle(&bundle.to_string(), Some(&public_key(&key))).unwrap();
+    let merkle = report
+        .checks
+        .iter()
+        .find(|c| c.name == "evidence_merkle")
+        .unwrap();
+    assert_eq!(merkle.status, CheckStatus::Pass);
+    assert_eq!(
+        merkle.detail,
+        "2 hashed evidence leaves of 3 photos, root matches"
+    );
+}
+
+#[test]
+fn large_integers_are_strings_in_the_signed_bundle() {
+    let key = issuer_key();
+    let pinned = public_key(&key);
+    let mut bundle = signed_bundle(&key);
+    assert_eq!(bundle["generated_at"], json!(GENERATED_AT.to_string()));
+
+    // Signed as a number, the low digits of a nanosecond timestamp fall outside
+    // the canonical form and can change without breaking the signature
+    bundle.as_object_mut().unwrap().remove("bundle_signature");
+    bundle["generated_at"] = json!(GENERATED_AT);
+    let signature = sign(&key, canonical_json(&bundle).as_bytes());
+    bundle["bundle_signature"] = json!({ "value": to_hex(&signature) });
+    bundle["generated_at"] = json!(GENERATED_AT + 1);
+
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "bundle_signature"),
+        CheckStatus::Pass
+    );
+    assert_eq!(
+        status_of(&bundle, Some(&pinned), "exact_integers"),
+        CheckStatus::Fail
+    );
+    assert_eq!(
+        verify_bundle(&bundle.to_string(), Some(&pinned))
+            .unwrap()
+            .verdict(),
+        Verdict::Invalid
+    );
+}
+
+// Vectors pinning the hashing the canister and the verifier share
+
+#[test]
+fn merkle_root_vectors() {
+    let leaf = |byte: u8| [byte; 32];
+    let node = |left: [u8; 32], right: [u8; 32]| -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([0x01]);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    };
+
+    let empty: [u8; 32] = Sha256::digest(b"").into();
+    assert_eq!(merkle_root(&[]), empty);
+    assert_eq!(merkle_root(&[leaf(1)]), leaf(1));
+    assert_eq!(
+        merkle_root(&[leaf(1), leaf(2), leaf(3)]),
+        node(node(leaf(1), leaf(2)), leaf(3))
+    );
+    assert_eq!(
+        to_hex(&evidence_leaf(10, "ab", "https://example.com/a.jpg")),
+        to_hex(&{
+            let mut hasher = Sha256::new();
+            hasher.update([0x00]);
+            hasher.update(b"10\nab\nhttps://example.com/a.jpg");
+            <[u8; 32]>::from(hasher.finalize())
+        })
+    );
+}
+
+#[test]
+fn canonical_json_vectors() {
+    assert_eq!(
+        canonical_json(&json!({ "b": 1, "a": [true, null, "x"], "c": { "z": "", "y": -2 } })),
+        r#"{"a":[true,null,"x"],"b":1,"c":{"y":-2,"z":""}}"#
+    );
+    // Keys sort by UTF-16 code units, so U+E000 sorts after U+1F600 (a surrogate pair)
+    assert_eq!(
+        canonical_json(&json!({ "\u{e000}": 1, "\u{1f600}": 2 })),
+        "{\"\u{1f600}\":2,\"\u{e000}\":1}"
+    );
+}
+
+#[test]
+fn canonical_json_formats_numbers_like_ecmascript() {
+    // 2^53 + 1 is not representable as a double and rounds down
+    assert_eq!(
+        canonical_json(
+            &json!({ "b": 9_007_199_254_740_993u64, "a": 1_700_000_000_000_000_001u64 })
+        ),
+        r#"{"a":1700000000000000000,"b":9007199254740992}"#
+    );
+    assert_eq!(
+        canonical_json(&json!([0, -0.0, 1.5, -42, 1e21, 1e-7, 0.000001, 123e-20])),
+        "[0,0,1.5,-42,1e+21,1e-7,0.000001,1.23e-18]"
+    );
+}
@@ -0,0 +1,90 @@
+import { describe, beforeAll, afterAll, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+import { IDL } from "@dfinity/candid";
+
+// Built by `npm run bench:backend` into a separate target directory so the
+// benchmark endpoint never ends up in the deployed Wasm
+const BENCH_WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "bench",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+interface BenchService {
+  bench_nft_ownership: (size: bigint) => Promise<Array<[string, bigint]>>;
+}
+
+const benchIdlFactory: IDL.InterfaceFactory = ({ IDL }) =>
+  IDL.Service({
+    bench_nft_ownership: IDL.Func(
+      [IDL.Nat64],
+      [IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64))],
+      [],
+    ),
+  });
+
+const SIZES = [1_000n, 10_000n, 100_000n];
+
+describe("NFT ownership query benchmark", () => {
+  let pic: PocketIc;
+  let actor: Actor<BenchService>;
+
+  beforeAll(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+    const fixture = await pic.setupCanister<BenchService>({
+      idlFactory: benchIdlFactory,
+      wasm: BENCH_WASM_PATH,
+    });
+    actor = fixture.actor;
+  });
+
+  afterAll(async () => {
+    await pic.tearDown();
+  });
+
+  it("keeps indexed queries flat as the collection grows", async () => {
+    const results = new Map<bigint, Map<string, bigint>>();
+    for (const size of SIZES) {
+      // Large collections are seeded over several calls
+      let measured: Array<[string, bigint]> = [];
+      do {
+        measured = await actor.bench_nft_ownership(size);
+      } while (!measured[0][0].endsWith(`@${size}`));
+      results.set(
+        size,
+        new Map(
+          measured.map(([label, instructions]) => [
+            label.split("@")[0],
+            instructions,
+          ]),
+        ),
+      );
+    }
+
+    console.table(
+      Object.fromEntries(
+        [...results].map(([size, counts]) => [
+          `${size} tokens`,
+          Object.fromEntries(counts),
+        ]),
+      ),
+    );
+
+    const smallest = results.get(SIZES[0])!;
+    const largest = results.get(SIZES[SIZES.length - 1])!;
+    for (const query of ["tokens", "balance_of", "tokens_of", "user_nfts"]) {
+      expect(largest.get(query)!).toBeLessThan(smallest.get(query)! * 2n);
+    }
+    // The full scan grows with the collection
+    expect(largest.get("balance_of_scan")!).toBeGreaterThan(
+      smallest.get("balance_of_scan")! * 50n,
+    );
+  });
+});
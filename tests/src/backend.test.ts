@@ -1,14 +1,26 @@
 import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
 import { resolve, dirname } from "path";
+import { readFileSync } from "fs";
 import { fileURLToPath } from "url";
-import { PocketIc, type Actor } from "@dfinity/pic";
+import { createHash } from "crypto";
+import {
+  PocketIc,
+  SubnetStateType,
+  createIdentity,
+  type Actor,
+} from "@dfinity/pic";
 import { Principal } from "@dfinity/principal";
 
 // Import generated types for your canister
 import {
   type _SERVICE,
+  type Value,
   idlFactory,
 } from "../../src/declarations/backend/backend.did.js";
+import {
+  type _SERVICE as _ARCHIVE_SERVICE,
+  idlFactory as archiveIdlFactory,
+} from "../../src/declarations/archive/archive.did.js";
 
 // Helper function to get S3 config from environment variables
 function getS3ConfigFromEnv(): {
@@ -35,6 +47,34 @@ function getS3ConfigFromEnv(): {
   return s3Config;
 }
 
+// ICRC-3 representation-independent hash of a block value
+function hashIcrc3Value(value: Value): Buffer {
+  const sha256 = (data: Uint8Array | string) =>
+    createHash("sha256").update(data).digest();
+  if ("Nat" in value) {
+    const bytes: number[] = [];
+    let n = value.Nat;
+    do {
+      const byte = Number(n & 0x7fn);
+      n >>= 7n;
+      bytes.push(n > 0n ? byte | 0x80 : byte);
+    } while (n > 0n);
+    return sha256(Uint8Array.from(bytes));
+  }
+  if ("Text" in value) return sha256(value.Text);
+  if ("Blob" in value) return sha256(Uint8Array.from(value.Blob));
+  if ("Array" in value) {
+    return sha256(Buffer.concat(value.Array.map(hashIcrc3Value)));
+  }
+  if ("Map" in value) {
+    const pairs = value.Map.map(([key, entry]) =>
+      Buffer.concat([sha256(key), hashIcrc3Value(entry)]),
+    ).sort(Buffer.compare);
+    return sha256(Buffer.concat(pairs));
+  }
+  throw new Error("Int values are not used in blocks");
+}
+
 // Define the path to your canister's WASM file
 export const WASM_PATH = resolve(
   dirname(fileURLToPath(import.meta.url)),
@@ -46,6 +86,17 @@ export const WASM_PATH = resolve(
   "backend.wasm",
 );
 
+// The archive canister the backend spawns for old ICRC-3 blocks
+export const ARCHIVE_WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "archive.wasm",
+);
+
 // The `describe` function is used to group tests together
 describe("OriginStamp Backend", () => {
   // Define variables to hold our PocketIC instance, canister ID,
@@ -57,8 +108,11 @@ describe("OriginStamp Backend", () => {
 
   // The `beforeEach` hook runs before each test.
   beforeEach(async () => {
-    // create a new PocketIC instance
-    pic = await PocketIc.create(inject("PIC_URL"));
+    // create a new PocketIC instance with a fiduciary subnet so the
+    // threshold ECDSA test keys are available for certificate signing
+    pic = await PocketIc.create(inject("PIC_URL"), {
+      fiduciary: { state: { type: SubnetStateType.New } },
+    });
 
     // Setup the canister and actor
     const fixture = await pic.setupCanister<_SERVICE>({
@@ -69,6 +123,9 @@ describe("OriginStamp Backend", () => {
     // Save the actor and canister ID for use in tests
     actor = fixture.actor;
     canisterId = fixture.canisterId;
+
+    // There is no default signing key; PocketIC provides the local test key
+    await actor.set_signing_key_name("dfx_test_key");
   });
 
   // The `afterEach` hook runs after each test.
@@ -1038,19 +1095,16 @@ describe("OriginStamp Backend", () => {
       photoUrl,
     );
 
-    // All should succeed (or consistently fail)
-    expect(uploadResult1).toBeDefined();
-    expect(uploadResult2).toBeDefined();
-    expect(uploadResult3).toBeDefined();
+    // Only the first upload is recorded
+    expect(uploadResult1).toEqual({ Ok: true });
+    expect(uploadResult2).toEqual({
+      Err: "Photo already uploaded to this session",
+    });
+    expect(uploadResult3).toEqual(uploadResult2);
 
-    // Verify photo handling (might deduplicate or allow duplicates)
     const sessionDetails = await actor.get_session_details(sessionId);
     expect(sessionDetails.length).toBe(1);
-    if (sessionDetails[0]) {
-      // The behavior depends on backend implementation
-      expect(sessionDetails[0].uploaded_photos.length).toBeGreaterThan(0);
-      expect(sessionDetails[0].uploaded_photos).toContain(photoUrl);
-    }
+    expect(sessionDetails[0]?.uploaded_photos).toEqual([photoUrl]);
   });
 
   it("should maintain data integrity across multiple operations", async () => {
@@ -1137,9 +1191,34 @@ describe("OriginStamp Backend", () => {
     it("should return collection metadata", async () => {
       const metadata = await actor.icrc7_collection_metadata();
 
-      expect(metadata.name).toBe("Origin Stamp Art NFTs");
-      expect(metadata.description).toBeDefined();
-      expect(metadata.total_supply).toBe(0n);
+      expect(metadata).toContainEqual([
+        "icrc7:name",
+        { Text: "Origin Stamp Art NFTs" },
+      ]);
+      expect(metadata).toContainEqual(["icrc7:symbol", { Text: "OSART" }]);
+      expect(metadata).toContainEqual(["icrc7:total_supply", { Nat: 0n }]);
+      expect(metadata).toContainEqual([
+        "icrc7:max_query_batch_size",
+        { Nat: 100n },
+      ]);
+      const keys = metadata.map(([key]) => key);
+      expect(keys).toContain("icrc7:description");
+    });
+
+    it("should report symbol, batch limits and supported standards", async () => {
+      expect(await actor.icrc7_symbol()).toBe("OSART");
+      expect(await actor.icrc7_logo()).toEqual([]);
+      expect(await actor.icrc7_max_query_batch_size()).toEqual([100n]);
+      expect(await actor.icrc7_max_update_batch_size()).toEqual([20n]);
+      expect(await actor.icrc7_default_take_value()).toEqual([100n]);
+      expect(await actor.icrc7_max_take_value()).toEqual([1000n]);
+      expect(await actor.icrc7_atomic_batch_transfers()).toEqual([false]);
+
+      const standards = await actor.icrc10_supported_standards();
+      expect(standards.map((standard) => standard.name)).toEqual([
+        "ICRC-7",
+        "ICRC-10",
+      ]);
     });
 
     it("should return collection name", async () => {
@@ -1202,6 +1281,7 @@ describe("OriginStamp Backend", () => {
         sessionId,
         recipient,
         [["custom_attribute", "test_value"]],
+        [],
       );
 
       expect("Ok" in mintResult).toBe(true);
@@ -1248,6 +1328,7 @@ describe("OriginStamp Backend", () => {
         sessionId,
         recipient,
         [["rarity", "rare"]],
+        [],
       );
       expect("Ok" in mintResult).toBe(true);
       if (!("Ok" in mintResult)) return;
@@ -1257,24 +1338,29 @@ describe("OriginStamp Backend", () => {
       // Get token metadata
       const metadata = await actor.icrc7_token_metadata([tokenId]);
       expect(metadata).toHaveLength(1);
-      if (metadata[0] && metadata[0].length > 0) {
-        const tokenMetadata = metadata[0][0];
-        if (tokenMetadata) {
-          expect(tokenMetadata.name).toContain("Metadata Test Art");
-          expect(tokenMetadata.description).toBeDefined();
-          expect(tokenMetadata.attributes).toContainEqual(["rarity", "rare"]);
-          expect(tokenMetadata.attributes).toContainEqual([
-            "session_id",
-            sessionId,
-          ]);
-          expect(tokenMetadata.attributes).toContainEqual([
-            "artist",
-            "metadata_artist",
-          ]);
-        }
-      } else {
-        console.log("Warning: No metadata found for token", tokenId);
-      }
+      expect(metadata[0]).toHaveLength(1);
+      const tokenMetadata = new Map(metadata[0][0]);
+      const name = tokenMetadata.get("name");
+      expect(name && "Text" in name && name.Text).toContain(
+        "Metadata Test Art",
+      );
+      expect(tokenMetadata.has("description")).toBe(true);
+
+      const attributes = tokenMetadata.get("attributes");
+      expect(attributes && "Map" in attributes).toBe(true);
+      if (!attributes || !("Map" in attributes)) return;
+      expect(attributes.Map).toContainEqual(["rarity", { Text: "rare" }]);
+      expect(attributes.Map).toContainEqual([
+        "session_id",
+        { Text: sessionId },
+      ]);
+      expect(attributes.Map).toContainEqual([
+        "artist",
+        { Text: "metadata_artist" },
+      ]);
+
+      // Unknown tokens have no metadata
+      expect(await actor.icrc7_token_metadata([999_999n])).toEqual([[]]);
     });
 
     it("should handle token balance queries", async () => {
@@ -1297,7 +1383,7 @@ describe("OriginStamp Backend", () => {
       expect("Ok" in sessionResult).toBe(true);
       if (!("Ok" in sessionResult)) return;
 
-      await actor.mint_nft_from_session(sessionResult.Ok, account, []);
+      await actor.mint_nft_from_session(sessionResult.Ok, account, [], []);
 
       // Check balance after minting
       const newBalance = await actor.icrc7_balance_of([account]);
@@ -1320,7 +1406,7 @@ describe("OriginStamp Backend", () => {
         );
         expect("Ok" in sessionResult).toBe(true);
         if ("Ok" in sessionResult) {
-          await actor.mint_nft_from_session(sessionResult.Ok, account, []);
+          await actor.mint_nft_from_session(sessionResult.Ok, account, [], []);
         }
       }
 
@@ -1345,7 +1431,7 @@ describe("OriginStamp Backend", () => {
       };
 
       // Mint NFT from session
-      await actor.mint_nft_from_session(sessionId, account, []);
+      await actor.mint_nft_from_session(sessionId, account, [], []);
 
       // Get NFTs for this session
       const sessionNFTs = await actor.get_session_nfts(sessionId);
@@ -1368,7 +1454,7 @@ describe("OriginStamp Backend", () => {
       );
       expect("Ok" in sessionResult).toBe(true);
       if ("Ok" in sessionResult) {
-        await actor.mint_nft_from_session(sessionResult.Ok, account, []);
+        await actor.mint_nft_from_session(sessionResult.Ok, account, [], []);
       }
 
       // Get user NFTs
@@ -1377,6 +1463,1008 @@ describe("OriginStamp Backend", () => {
       expect(userNFTs[0].owner.owner).toEqual(principal);
     });
 
+    it("should transfer a token from the caller's account", async () => {
+      const seller = createIdentity("icrc7_seller");
+      const buyer = createIdentity("icrc7_buyer");
+      const sessionResult = await actor.create_physical_art_session(
+        "transfer_artist",
+        "Transfer Art",
+        "Testing transfers",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const mintResult = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        { owner: seller.getPrincipal(), subaccount: [] },
+        [],
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+      const tokenId = mintResult.Ok;
+
+      const transferTo = (to: Principal, id: bigint) => ({
+        from_subaccount: [] as [],
+        to: { owner: to, subaccount: [] as [] },
+        token_id: id,
+        memo: [] as [],
+        created_at_time: [] as [],
+      });
+
+      // Only the owner can move the token
+      actor.setIdentity(buyer);
+      const stolen = await actor.icrc7_transfer([
+        transferTo(buyer.getPrincipal(), tokenId),
+      ]);
+      expect(stolen).toEqual([[{ Err: { Unauthorized: null } }]]);
+
+      actor.setIdentity(seller);
+      const results = await actor.icrc7_transfer([
+        transferTo(buyer.getPrincipal(), tokenId),
+        transferTo(buyer.getPrincipal(), 999_999n),
+      ]);
+      expect(results).toHaveLength(2);
+      expect(results[0][0] && "Ok" in results[0][0]).toBe(true);
+      expect(results[1]).toEqual([{ Err: { NonExistingTokenId: null } }]);
+
+      const owners = await actor.icrc7_owner_of([tokenId]);
+      expect(owners[0][0]?.owner).toEqual(buyer.getPrincipal());
+      expect(
+        await actor.icrc7_balance_of([
+          { owner: seller.getPrincipal(), subaccount: [] },
+          { owner: buyer.getPrincipal(), subaccount: [] },
+        ]),
+      ).toEqual([0n, 1n]);
+    });
+
+    it("should enforce soulbound, artist-approved and locked-up transfers", async () => {
+      const artist = createIdentity("policy_artist");
+      const collector = createIdentity("policy_collector");
+      const buyer = createIdentity("policy_buyer");
+      const collectorAccount = {
+        owner: collector.getPrincipal(),
+        subaccount: [] as [],
+      };
+      const buyerAccount = {
+        owner: buyer.getPrincipal(),
+        subaccount: [] as [],
+      };
+
+      actor.setIdentity(artist);
+      await actor.register_user("policy_artist", "password123");
+      const sessionResult = await actor.create_physical_art_session(
+        "policy_artist",
+        "Bound Art",
+        "Testing transfer policies",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const sessionId = sessionResult.Ok;
+
+      const lockedUntil =
+        BigInt(await pic.getTime()) * 1_000_000n + 3_600_000_000_000n;
+      expect(
+        await actor.mint_nft_from_session(sessionId, collectorAccount, [], [
+          { LockedUntil: BigInt(await pic.getTime()) * 1_000_000n },
+        ]),
+      ).toEqual({ Err: "Lock-up date must be in the future" });
+
+      const minted: bigint[] = [];
+      for (const policy of [
+        { Soulbound: null },
+        { ArtistApproval: null },
+        { LockedUntil: lockedUntil },
+      ]) {
+        const result = await actor.mint_nft_from_session(
+          sessionId,
+          collectorAccount,
+          [],
+          [policy],
+        );
+        expect("Ok" in result).toBe(true);
+        if (!("Ok" in result)) return;
+        minted.push(result.Ok);
+      }
+      const [soulbound, approvalOnly, locked] = minted;
+
+      const metadata = await actor.icrc7_token_metadata([soulbound, locked]);
+      expect(metadata[0][0]).toContainEqual([
+        "transfer_policy",
+        { Text: "soulbound" },
+      ]);
+      expect(metadata[1][0]).toContainEqual([
+        "transfer_locked_until",
+        { Nat: lockedUntil },
+      ]);
+
+      const transfer = (id: bigint) =>
+        actor.icrc7_transfer([
+          {
+            from_subaccount: [],
+            to: buyerAccount,
+            token_id: id,
+            memo: [],
+            created_at_time: [],
+          },
+        ]);
+      const restricted = (message: string) => [
+        [{ Err: { GenericError: { error_code: 6n, message } } }],
+      ];
+
+      actor.setIdentity(collector);
+      expect(await transfer(soulbound)).toEqual(
+        restricted(`Token ${soulbound} is soulbound and cannot be transferred`),
+      );
+      expect(await transfer(approvalOnly)).toEqual(
+        restricted(
+          `Token ${approvalOnly} can only be transferred to a recipient approved by the artist`,
+        ),
+      );
+      expect(
+        await actor.set_nft_transfer_approval(approvalOnly, [buyerAccount]),
+      ).toEqual({
+        Err: "Only the artist or admins can approve transfers of this NFT",
+      });
+      const lockedTransfer = await transfer(locked);
+      expect(lockedTransfer[0][0] && "Err" in lockedTransfer[0][0]).toBe(true);
+
+      // The artist approves one transfer to a named recipient
+      actor.setIdentity(artist);
+      expect(
+        await actor.set_nft_transfer_approval(approvalOnly, [buyerAccount]),
+      ).toEqual({ Ok: null });
+      actor.setIdentity(collector);
+      const approved = await transfer(approvalOnly);
+      expect(approved[0][0] && "Ok" in approved[0][0]).toBe(true);
+      expect(await actor.get_nft_transfer_approval(approvalOnly)).toEqual([]);
+
+      // Lock-ups end on their date
+      await pic.advanceTime(3_600_000);
+      await pic.tick();
+      const unlocked = await transfer(locked);
+      expect(unlocked[0][0] && "Ok" in unlocked[0][0]).toBe(true);
+
+      // Soulbound tokens can still be burned
+      const burned = await actor.icrc7_burn([
+        {
+          from_subaccount: [],
+          token_id: soulbound,
+          memo: [],
+          created_at_time: [],
+          reason: [],
+        },
+      ]);
+      expect(burned[0][0] && "Ok" in burned[0][0]).toBe(true);
+
+      // Burning drops a pending artist approval with the token
+      actor.setIdentity(artist);
+      await actor.set_nft_transfer_approval(approvalOnly, [collectorAccount]);
+      actor.setIdentity(buyer);
+      await actor.icrc7_burn([
+        {
+          from_subaccount: [],
+          token_id: approvalOnly,
+          memo: [],
+          created_at_time: [],
+          reason: [],
+        },
+      ]);
+      expect(await actor.get_nft_transfer_approval(approvalOnly)).toEqual([]);
+    });
+
+    it("should apply the collection transfer policy to mints without one", async () => {
+      actor.setIdentity(createIdentity("policy_stranger"));
+      expect(
+        await actor.set_collection_transfer_policy({ Soulbound: null }),
+      ).toEqual({ Err: "Only admins can perform this action" });
+
+      actor.setPrincipal(Principal.anonymous());
+      expect(
+        await actor.set_collection_transfer_policy({ Soulbound: null }),
+      ).toEqual({ Ok: null });
+      expect(await actor.get_collection_transfer_policy()).toEqual({
+        Soulbound: null,
+      });
+      expect(await actor.icrc7_collection_metadata()).toContainEqual([
+        "transfer_policy",
+        { Text: "soulbound" },
+      ]);
+      const history = await actor.get_collection_metadata_history();
+      expect(history[0].updated.transfer_policy).toEqual({ Soulbound: null });
+
+      const sessionResult = await actor.create_physical_art_session(
+        "default_policy_artist",
+        "Default Policy Art",
+        "Testing the collection transfer policy",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const recipient = { owner: Principal.anonymous(), subaccount: [] as [] };
+      const bound = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        recipient,
+        [],
+        [],
+      );
+      const free = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        recipient,
+        [],
+        [{ Transferable: null }],
+      );
+      expect("Ok" in bound && "Ok" in free).toBe(true);
+      if (!("Ok" in bound) || !("Ok" in free)) return;
+      const [boundToken] = await actor.get_token_details(bound.Ok);
+      expect(boundToken?.transfer_policy).toEqual({ Soulbound: null });
+      const [freeToken] = await actor.get_token_details(free.Ok);
+      expect(freeToken?.transfer_policy).toEqual({ Transferable: null });
+    });
+
+    it("should keep ownership queries in sync across subaccounts", async () => {
+      const holder = createIdentity("index_holder");
+      const vault = new Uint8Array(32).fill(1);
+      const main = { owner: holder.getPrincipal(), subaccount: [] as [] };
+      const vaultAccount = {
+        owner: holder.getPrincipal(),
+        subaccount: [vault] as [Uint8Array],
+      };
+      const sessionResult = await actor.create_physical_art_session(
+        "index_artist",
+        "Indexed Art",
+        "Testing the owner index",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const minted: bigint[] = [];
+      for (const recipient of [main, main, main, vaultAccount]) {
+        const mintResult = await actor.mint_nft_from_session(
+          sessionResult.Ok,
+          recipient,
+          [],
+          [],
+        );
+        if (!("Ok" in mintResult)) throw new Error(mintResult.Err);
+        minted.push(mintResult.Ok);
+      }
+
+      actor.setIdentity(holder);
+      const moved = await actor.icrc7_transfer([
+        {
+          from_subaccount: [],
+          to: vaultAccount,
+          token_id: minted[0],
+          memo: [],
+          created_at_time: [],
+        },
+      ]);
+      expect(moved[0][0] && "Ok" in moved[0][0]).toBe(true);
+      const burned = await actor.icrc7_burn([
+        {
+          from_subaccount: [],
+          token_id: minted[1],
+          memo: [],
+          created_at_time: [],
+          reason: [],
+        },
+      ]);
+      expect(burned[0][0] && "Ok" in burned[0][0]).toBe(true);
+
+      expect(await actor.icrc7_balance_of([main, vaultAccount])).toEqual([
+        1n,
+        2n,
+      ]);
+      expect(await actor.icrc7_tokens_of(main, [], [])).toEqual([minted[2]]);
+      expect(await actor.icrc7_tokens_of(vaultAccount, [], [1n])).toEqual([
+        minted[0],
+      ]);
+      expect(
+        await actor.icrc7_tokens_of(vaultAccount, [minted[0]], []),
+      ).toEqual([minted[3]]);
+      const userNfts = await actor.get_user_nfts(holder.getPrincipal());
+      expect(userNfts.map((token) => token.id)).toEqual([
+        minted[0],
+        minted[2],
+        minted[3],
+      ]);
+
+      expect(await actor.icrc7_tokens([minted[0]], [])).toEqual([
+        minted[2],
+        minted[3],
+      ]);
+      expect(await actor.icrc7_tokens([2n ** 64n], [])).toEqual([]);
+    });
+
+    it("should reject an empty transfer batch", async () => {
+      const results = await actor.icrc7_transfer([]);
+      expect(results).toHaveLength(1);
+      expect(results[0][0] && "Err" in results[0][0]).toBe(true);
+      if (results[0][0] && "Err" in results[0][0]) {
+        expect("GenericBatchError" in results[0][0].Err).toBe(true);
+      }
+    });
+
+    it("should validate, window and deduplicate transfers", async () => {
+      const seller = createIdentity("dedup_seller");
+      const buyer = createIdentity("dedup_buyer");
+      const sessionResult = await actor.create_physical_art_session(
+        "dedup_artist",
+        "Dedup Art",
+        "Testing transfer deduplication",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const mintResult = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        { owner: seller.getPrincipal(), subaccount: [] },
+        [],
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+
+      const now = BigInt(await pic.getTime()) * 1_000_000n;
+      const hour = 3_600_000_000_000n;
+      const transfer = (
+        to: Principal,
+        createdAt: bigint,
+        memo: Uint8Array = new Uint8Array([1, 2, 3]),
+      ) => ({
+        from_subaccount: [] as [],
+        to: { owner: to, subaccount: [] as [] },
+        token_id: mintResult.Ok,
+        memo: [memo] as [Uint8Array],
+        created_at_time: [createdAt] as [bigint],
+      });
+
+      actor.setIdentity(seller);
+      const rejected = await actor.icrc7_transfer([
+        transfer(seller.getPrincipal(), now),
+        transfer(buyer.getPrincipal(), now - 25n * hour),
+        transfer(buyer.getPrincipal(), now + hour),
+        transfer(buyer.getPrincipal(), now, new Uint8Array(33)),
+      ]);
+      expect(rejected[0]).toEqual([{ Err: { InvalidRecipient: null } }]);
+      expect(rejected[1]).toEqual([{ Err: { TooOld: null } }]);
+      expect(rejected[2][0] && "Err" in rejected[2][0]).toBe(true);
+      if (rejected[2][0] && "Err" in rejected[2][0]) {
+        expect("CreatedInFuture" in rejected[2][0].Err).toBe(true);
+      }
+      expect(rejected[3][0] && "Err" in rejected[3][0]).toBe(true);
+      if (rejected[3][0] && "Err" in rejected[3][0]) {
+        expect("GenericError" in rejected[3][0].Err).toBe(true);
+      }
+
+      // Resubmitting the same request is reported as a duplicate of the first
+      const first = await actor.icrc7_transfer([
+        transfer(buyer.getPrincipal(), now),
+      ]);
+      expect(first[0][0] && "Ok" in first[0][0]).toBe(true);
+      if (!first[0][0] || !("Ok" in first[0][0])) return;
+      const again = await actor.icrc7_transfer([
+        transfer(buyer.getPrincipal(), now),
+      ]);
+      expect(again).toEqual([
+        [{ Err: { Duplicate: { duplicate_of: first[0][0].Ok } } }],
+      ]);
+    });
+
+    it("should let only admins configure the transfer window", async () => {
+      expect(await actor.icrc7_tx_window()).toEqual([86_400n]);
+      expect(await actor.icrc7_permitted_drift()).toEqual([120n]);
+      expect(await actor.icrc7_max_memo_size()).toEqual([32n]);
+
+      actor.setIdentity(createIdentity("window_stranger"));
+      const denied = await actor.set_icrc7_transfer_window(3_600n, 60n);
+      expect("Err" in denied).toBe(true);
+
+      actor.setPrincipal(Principal.anonymous());
+      const invalid = await actor.set_icrc7_transfer_window(0n, 60n);
+      expect("Err" in invalid).toBe(true);
+      const updated = await actor.set_icrc7_transfer_window(3_600n, 60n);
+      expect("Ok" in updated).toBe(true);
+      expect(await actor.icrc7_tx_window()).toEqual([3_600n]);
+      expect(await actor.icrc7_permitted_drift()).toEqual([60n]);
+    });
+
+    describe("ICRC-37 approvals", () => {
+      const seller = createIdentity("approval_seller");
+      const buyer = createIdentity("approval_buyer");
+      const market = createIdentity("approval_market");
+      const account = (identity: typeof seller) => ({
+        owner: identity.getPrincipal(),
+        subaccount: [] as [],
+      });
+
+      async function mintTo(identity: typeof seller): Promise<bigint> {
+        const sessionResult = await actor.create_physical_art_session(
+          "approval_artist",
+          "Approval Art",
+          "Testing approvals",
+        );
+        if (!("Ok" in sessionResult)) throw new Error(sessionResult.Err);
+        const mintResult = await actor.mint_nft_from_session(
+          sessionResult.Ok,
+          account(identity),
+          [],
+          [],
+        );
+        if (!("Ok" in mintResult)) throw new Error(mintResult.Err);
+        return mintResult.Ok;
+      }
+
+      async function approvalInfo(expiresAt: [] | [bigint] = []) {
+        return {
+          spender: account(market),
+          from_subaccount: [] as [],
+          expires_at: expiresAt,
+          memo: [] as [],
+          created_at_time: BigInt(await pic.getTime()) * 1_000_000n,
+        };
+      }
+
+      const transferFrom = (tokenId: bigint) => ({
+        spender_subaccount: [] as [],
+        from: account(seller),
+        to: account(buyer),
+        token_id: tokenId,
+        memo: [] as [],
+        created_at_time: [] as [],
+      });
+
+      it("should let an approved spender transfer a token once", async () => {
+        const tokenId = await mintTo(seller);
+
+        // Without an approval the marketplace cannot move the token
+        actor.setIdentity(market);
+        expect(await actor.icrc37_transfer_from([transferFrom(tokenId)])).toEqual(
+          [[{ Err: { Unauthorized: null } }]],
+        );
+
+        actor.setIdentity(buyer);
+        const notOwner = await actor.icrc37_approve_tokens([
+          { token_id: tokenId, approval_info: await approvalInfo() },
+        ]);
+        expect(notOwner).toEqual([[{ Err: { Unauthorized: null } }]]);
+
+        actor.setIdentity(seller);
+        const approved = await actor.icrc37_approve_tokens([
+          { token_id: tokenId, approval_info: await approvalInfo() },
+        ]);
+        expect(approved[0][0] && "Ok" in approved[0][0]).toBe(true);
+        expect(
+          await actor.icrc37_is_approved([
+            { spender: account(market), from_subaccount: [], token_id: tokenId },
+          ]),
+        ).toEqual([true]);
+        const listed = await actor.icrc37_get_token_approvals(tokenId, [], []);
+        expect(listed).toHaveLength(1);
+        expect(listed[0].approval_info.spender.owner).toEqual(
+          market.getPrincipal(),
+        );
+
+        actor.setIdentity(market);
+        const sold = await actor.icrc37_transfer_from([transferFrom(tokenId)]);
+        expect(sold[0][0] && "Ok" in sold[0][0]).toBe(true);
+        const owners = await actor.icrc7_owner_of([tokenId]);
+        expect(owners[0][0]?.owner).toEqual(buyer.getPrincipal());
+
+        // The seller's approval does not survive the transfer
+        expect(
+          await actor.icrc37_is_approved([
+            { spender: account(market), from_subaccount: [], token_id: tokenId },
+          ]),
+        ).toEqual([false]);
+        expect(await actor.icrc37_get_token_approvals(tokenId, [], [])).toEqual(
+          [],
+        );
+      });
+
+      it("should honour collection approvals, expiry and revocation", async () => {
+        const first = await mintTo(seller);
+        const second = await mintTo(seller);
+
+        actor.setIdentity(seller);
+        const now = BigInt(await pic.getTime()) * 1_000_000n;
+        const approved = await actor.icrc37_approve_collection([
+          { approval_info: await approvalInfo([now + 60_000_000_000n]) },
+        ]);
+        expect(approved[0][0] && "Ok" in approved[0][0]).toBe(true);
+        const listed = await actor.icrc37_get_collection_approvals(
+          account(seller),
+          [],
+          [],
+        );
+        expect(listed).toHaveLength(1);
+
+        actor.setIdentity(market);
+        const sold = await actor.icrc37_transfer_from([transferFrom(first)]);
+        expect(sold[0][0] && "Ok" in sold[0][0]).toBe(true);
+
+        // Expired approvals no longer authorize transfers
+        await pic.advanceTime(120_000);
+        await pic.tick();
+        expect(await actor.icrc37_transfer_from([transferFrom(second)])).toEqual(
+          [[{ Err: { Unauthorized: null } }]],
+        );
+
+        actor.setIdentity(seller);
+        await actor.icrc37_approve_collection([
+          { approval_info: await approvalInfo() },
+        ]);
+        const revokeArg = {
+          spender: [account(market)] as [ReturnType<typeof account>],
+          from_subaccount: [] as [],
+          memo: [] as [],
+          created_at_time: [] as [],
+        };
+        const revoked = await actor.icrc37_revoke_collection_approvals([
+          revokeArg,
+        ]);
+        expect(revoked[0][0] && "Ok" in revoked[0][0]).toBe(true);
+        expect(
+          await actor.icrc37_revoke_collection_approvals([revokeArg]),
+        ).toEqual([[{ Err: { ApprovalDoesNotExist: null } }]]);
+
+        actor.setIdentity(market);
+        expect(await actor.icrc37_transfer_from([transferFrom(second)])).toEqual(
+          [[{ Err: { Unauthorized: null } }]],
+        );
+      });
+
+      it("should reject approving the holder as spender", async () => {
+        const tokenId = await mintTo(seller);
+        actor.setIdentity(seller);
+        const info = { ...(await approvalInfo()), spender: account(seller) };
+        expect(
+          await actor.icrc37_approve_tokens([
+            { token_id: tokenId, approval_info: info },
+          ]),
+        ).toEqual([[{ Err: { InvalidSpender: null } }]]);
+        expect(await actor.icrc37_max_approvals_per_token_or_collection()).toEqual(
+          [32n],
+        );
+      });
+    });
+
+    describe("ICRC-3 transaction log", () => {
+      const blockField = (block: Value, key: string) => {
+        if (!("Map" in block)) throw new Error("block is not a map");
+        return block.Map.find(([entryKey]) => entryKey === key)?.[1];
+      };
+
+      it("should log mints and transfers as a hash chain", async () => {
+        expect(await actor.icrc3_get_tip_certificate()).toEqual([]);
+
+        const seller = createIdentity("log_seller");
+        const buyer = createIdentity("log_buyer");
+        const sessionResult = await actor.create_physical_art_session(
+          "log_artist",
+          "Logged Art",
+          "Testing the transaction log",
+        );
+        expect("Ok" in sessionResult).toBe(true);
+        if (!("Ok" in sessionResult)) return;
+        const mintResult = await actor.mint_nft_from_session(
+          sessionResult.Ok,
+          { owner: seller.getPrincipal(), subaccount: [] },
+          [],
+          [],
+        );
+        expect("Ok" in mintResult).toBe(true);
+        if (!("Ok" in mintResult)) return;
+
+        actor.setIdentity(seller);
+        const transfer = await actor.icrc7_transfer([
+          {
+            from_subaccount: [],
+            to: { owner: buyer.getPrincipal(), subaccount: [] },
+            token_id: mintResult.Ok,
+            memo: [new Uint8Array([7])],
+            created_at_time: [],
+          },
+        ]);
+        expect(transfer).toEqual([[{ Ok: 1n }]]);
+
+        const result = await actor.icrc3_get_blocks([
+          { start: 0n, length: 10n },
+        ]);
+        expect(result.log_length).toBe(2n);
+        expect(result.archived_blocks).toEqual([]);
+        const [mint, xfer] = result.blocks.map((entry) => entry.block);
+        expect(blockField(mint, "btype")).toEqual({ Text: "7mint" });
+        expect(blockField(mint, "phash")).toBeUndefined();
+        expect(blockField(xfer, "btype")).toEqual({ Text: "7xfer" });
+
+        // Each block carries the hash of its predecessor
+        const phash = blockField(xfer, "phash");
+        expect(phash && "Blob" in phash).toBe(true);
+        if (!phash || !("Blob" in phash)) return;
+        expect(Buffer.from(phash.Blob)).toEqual(hashIcrc3Value(mint));
+
+        const tx = blockField(xfer, "tx");
+        expect(tx && "Map" in tx).toBe(true);
+        if (!tx || !("Map" in tx)) return;
+        expect(tx.Map).toContainEqual(["tid", { Nat: mintResult.Ok }]);
+        expect(tx.Map).toContainEqual([
+          "to",
+          { Array: [{ Blob: buyer.getPrincipal().toUint8Array() }] },
+        ]);
+
+        // The certified tip commits to the hash of the last block
+        const tip = await actor.icrc3_get_tip_certificate();
+        expect(tip).toHaveLength(1);
+        if (tip.length === 0) return;
+        expect(tip[0].certificate.length).toBeGreaterThan(0);
+        const hashTree = Buffer.from(tip[0].hash_tree);
+        expect(hashTree.includes(hashIcrc3Value(xfer))).toBe(true);
+        expect(hashTree.includes(Buffer.from("last_block_index"))).toBe(true);
+      });
+
+      it("should log approvals and list supported block types", async () => {
+        const seller = createIdentity("log_approver");
+        const sessionResult = await actor.create_physical_art_session(
+          "log_approval_artist",
+          "Approved Art",
+          "Testing approval blocks",
+        );
+        expect("Ok" in sessionResult).toBe(true);
+        if (!("Ok" in sessionResult)) return;
+        const mintResult = await actor.mint_nft_from_session(
+          sessionResult.Ok,
+          { owner: seller.getPrincipal(), subaccount: [] },
+          [],
+          [],
+        );
+        expect("Ok" in mintResult).toBe(true);
+        if (!("Ok" in mintResult)) return;
+
+        actor.setIdentity(seller);
+        const approved = await actor.icrc37_approve_tokens([
+          {
+            token_id: mintResult.Ok,
+            approval_info: {
+              spender: {
+                owner: createIdentity("log_market").getPrincipal(),
+                subaccount: [],
+              },
+              from_subaccount: [],
+              expires_at: [],
+              memo: [],
+              created_at_time: BigInt(await pic.getTime()) * 1_000_000n,
+            },
+          },
+        ]);
+        expect(approved).toEqual([[{ Ok: 1n }]]);
+
+        const result = await actor.icrc3_get_blocks([
+          { start: 1n, length: 1n },
+        ]);
+        expect(result.blocks).toHaveLength(1);
+        expect(blockField(result.blocks[0].block, "btype")).toEqual({
+          Text: "37approve",
+        });
+
+        const types = await actor.icrc3_supported_block_types();
+        expect(types.map((type) => type.block_type)).toEqual(
+          expect.arrayContaining(["7mint", "7xfer", "7burn", "37approve"]),
+        );
+        expect(await actor.icrc3_get_archives({ from: [] })).toEqual([]);
+      });
+
+      it("should move old blocks to spawned archive canisters", async () => {
+        // Archiving needs the archive Wasm and only starts past the threshold
+        actor.setIdentity(createIdentity("log_stranger"));
+        expect(
+          await actor.set_icrc3_archive_wasm(readFileSync(ARCHIVE_WASM_PATH)),
+        ).toEqual({ Err: "Only admins can perform this action" });
+        actor.setPrincipal(Principal.anonymous());
+        expect(
+          await actor.set_icrc3_archive_wasm(new Uint8Array([1, 2, 3])),
+        ).toEqual({
+          Err: "Archive module must be a Wasm or gzipped Wasm binary",
+        });
+        expect(
+          await actor.set_icrc3_archive_wasm(readFileSync(ARCHIVE_WASM_PATH)),
+        ).toEqual({ Ok: null });
+        expect(
+          await actor.set_icrc3_archive_config({
+            trigger_threshold: 3n,
+            num_blocks_to_archive: 2n,
+            max_blocks_per_archive: 4n,
+            cycles_for_archive_creation: 500_000_000_000n,
+          }),
+        ).toEqual({ Ok: null });
+
+        const owner = {
+          owner: createIdentity("log_holder").getPrincipal(),
+          subaccount: [] as [],
+        };
+        for (let i = 0; i < 8; i++) {
+          const sessionResult = await actor.create_physical_art_session(
+            "log_archive_artist",
+            `Archived Art ${i}`,
+            "Testing archives",
+          );
+          if (!("Ok" in sessionResult)) throw new Error(sessionResult.Err);
+          const mintResult = await actor.mint_nft_from_session(
+            sessionResult.Ok,
+            owner,
+            [],
+            [],
+          );
+          expect("Ok" in mintResult).toBe(true);
+          for (let round = 0; round < 5; round++) await pic.tick();
+        }
+
+        // Blocks 0..3 fill the first archive, 4..5 the second; 6..7 stay local
+        const archives = await actor.icrc3_get_archives({ from: [] });
+        expect(archives.map(({ start, end }) => [start, end])).toEqual([
+          [0n, 3n],
+          [4n, 5n],
+        ]);
+        expect(
+          await actor.icrc3_get_archives({ from: [archives[0].canister_id] }),
+        ).toEqual([archives[1]]);
+
+        const result = await actor.icrc3_get_blocks([
+          { start: 2n, length: 5n },
+        ]);
+        expect(result.log_length).toBe(8n);
+        expect(result.blocks.map((entry) => entry.id)).toEqual([6n]);
+        expect(result.archived_blocks).toHaveLength(2);
+        expect(result.archived_blocks[0].args).toEqual([
+          { start: 2n, length: 2n },
+        ]);
+        expect(result.archived_blocks[0].callback[0]).toEqual(
+          archives[0].canister_id,
+        );
+        expect(result.archived_blocks[0].callback[1]).toBe("icrc3_get_blocks");
+
+        // The archive serves the blocks through the advertised callback, chain intact
+        const archive = pic.createActor<_ARCHIVE_SERVICE>(
+          archiveIdlFactory,
+          archives[1].canister_id,
+        );
+        const archived = await archive.icrc3_get_blocks(
+          result.archived_blocks[1].args,
+        );
+        expect(archived.blocks.map((entry) => entry.id)).toEqual([4n, 5n]);
+        const phash = blockField(result.blocks[0].block, "phash");
+        expect(phash && "Blob" in phash && Buffer.from(phash.Blob)).toEqual(
+          hashIcrc3Value(archived.blocks[1].block),
+        );
+        // Ranges entirely before the archive's first block return nothing
+        const below = await archive.icrc3_get_blocks([
+          { start: 0n, length: 3n },
+        ]);
+        expect(below.blocks).toEqual([]);
+        expect(await archive.append_blocks(6n, [])).toEqual({
+          Err: "Only the ledger canister can append blocks",
+        });
+        expect(await actor.get_icrc3_archive_failure()).toEqual({ Ok: [] });
+      });
+    });
+
+    it("should burn tokens by their owner with a reason", async () => {
+      const holder = createIdentity("burn_holder");
+      const sessionResult = await actor.create_physical_art_session(
+        "burn_artist",
+        "Burned Art",
+        "Testing burns",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const mintResult = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        { owner: holder.getPrincipal(), subaccount: [] },
+        [],
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+      const burnArg = (reason: string) => ({
+        from_subaccount: [] as [],
+        token_id: mintResult.Ok,
+        memo: [] as [],
+        created_at_time: [] as [],
+        reason: [reason] as [string],
+      });
+
+      actor.setIdentity(createIdentity("burn_stranger"));
+      expect(await actor.icrc7_burn([burnArg("not mine")])).toEqual([
+        [{ Err: { Unauthorized: null } }],
+      ]);
+
+      actor.setIdentity(holder);
+      const tooLong = await actor.icrc7_burn([burnArg("x".repeat(257))]);
+      expect(tooLong[0][0] && "Err" in tooLong[0][0]).toBe(true);
+      expect(await actor.icrc7_burn([burnArg("minted in error")])).toEqual([
+        [{ Ok: 1n }],
+      ]);
+
+      expect(await actor.icrc7_owner_of([mintResult.Ok])).toEqual([[]]);
+      expect(await actor.icrc7_total_supply()).toBe(0n);
+      expect(await actor.icrc7_burn([burnArg("again")])).toEqual([
+        [{ Err: { NonExistingTokenId: null } }],
+      ]);
+
+      const log = await actor.icrc3_get_blocks([{ start: 1n, length: 1n }]);
+      const block = log.blocks[0].block;
+      expect("Map" in block && block.Map).toContainEqual([
+        "btype",
+        { Text: "7burn" },
+      ]);
+      const tx = "Map" in block && block.Map.find(([key]) => key === "tx");
+      expect(tx && tx[1]).toEqual({
+        Map: expect.arrayContaining([
+          ["tid", { Nat: mintResult.Ok }],
+          ["reason", { Text: "minted in error" }],
+        ]),
+      });
+    });
+
+    it("should restrict minting to owners, minters and admins", async () => {
+      const artist = createIdentity("mint_owner");
+      const minter = createIdentity("mint_service");
+      const stranger = createIdentity("mint_stranger");
+      const recipient = { owner: artist.getPrincipal(), subaccount: [] as [] };
+
+      actor.setIdentity(artist);
+      await actor.register_user("mint_owner", "password123");
+      const sessionResult = await actor.create_physical_art_session(
+        "mint_owner",
+        "Owned Art",
+        "Testing mint authorization",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const sessionId = sessionResult.Ok;
+
+      actor.setIdentity(stranger);
+      expect(
+        await actor.mint_nft_from_session(sessionId, recipient, [], []),
+      ).toEqual({ Err: "Only the owner, minters or admins can mint this NFT" });
+      expect(await actor.set_nft_minter(minter.getPrincipal(), true)).toEqual({
+        Err: "Only admins can perform this action",
+      });
+
+      // The session owner can mint their own work
+      actor.setIdentity(artist);
+      const ownMint = await actor.mint_nft_from_session(
+        sessionId,
+        recipient,
+        [],
+        [],
+      );
+      expect("Ok" in ownMint).toBe(true);
+
+      actor.setPrincipal(Principal.anonymous());
+      expect(await actor.set_nft_minter(minter.getPrincipal(), true)).toEqual({
+        Ok: null,
+      });
+      expect(await actor.get_nft_minters()).toEqual([minter.getPrincipal()]);
+
+      actor.setIdentity(minter);
+      const minterMint = await actor.mint_nft_from_session(
+        sessionId,
+        recipient,
+        [],
+        [],
+      );
+      expect("Ok" in minterMint).toBe(true);
+    });
+
+    it("should let only admins change collection metadata and cap supply", async () => {
+      actor.setIdentity(createIdentity("metadata_stranger"));
+      expect(
+        await actor.update_collection_metadata("Renamed", [], [], [1n]),
+      ).toEqual({ Err: "Only admins can perform this action" });
+      expect(await actor.get_collection_metadata_history()).toEqual([]);
+
+      actor.setPrincipal(Principal.anonymous());
+      const sessionResult = await actor.create_physical_art_session(
+        "capped_artist",
+        "Capped Art",
+        "Testing the supply cap",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const recipient = { owner: Principal.anonymous(), subaccount: [] as [] };
+      await actor.mint_nft_from_session(sessionResult.Ok, recipient, [], []);
+
+      expect(
+        await actor.update_collection_metadata("Capped", [], [], [0n]),
+      ).toEqual({ Err: "Max supply cannot be below the current supply of 1" });
+      expect(
+        await actor.update_collection_metadata(
+          "Capped",
+          ["One of one"],
+          [],
+          [1n],
+        ),
+      ).toEqual({ Ok: true });
+      expect(await actor.icrc7_supply_cap()).toEqual([1n]);
+      expect(
+        await actor.mint_nft_from_session(sessionResult.Ok, recipient, [], []),
+      ).toEqual({
+        Err: "Supply cap of 1 tokens reached",
+      });
+
+      const history = await actor.get_collection_metadata_history();
+      expect(history).toHaveLength(1);
+      expect(history[0].changed_by).toBe(Principal.anonymous().toText());
+      expect(history[0].previous.name).toBe("Origin Stamp Art NFTs");
+      expect(history[0].previous.max_supply).toEqual([]);
+      expect(history[0].updated.name).toBe("Capped");
+      expect(history[0].updated.max_supply).toEqual([1n]);
+    });
+
+    it("should serve token and collection metadata at the token URI", async () => {
+      const get = (url: string) =>
+        actor.http_request({
+          method: "GET",
+          url,
+          headers: [],
+          body: new Uint8Array(),
+          certificate_version: [],
+        });
+      const decode = (body: Uint8Array | number[]) =>
+        JSON.parse(new TextDecoder().decode(new Uint8Array(body)));
+
+      const sessionResult = await actor.create_physical_art_session(
+        "metadata_artist",
+        "Indexed Art",
+        "Served to marketplaces",
+      );
+      expect("Ok" in sessionResult).toBe(true);
+      if (!("Ok" in sessionResult)) return;
+      const photoUrl = "https://example.com/indexed-art.jpg";
+      await actor.upload_photo_to_session(sessionResult.Ok, photoUrl);
+      const mintResult = await actor.mint_nft_from_session(
+        sessionResult.Ok,
+        { owner: Principal.anonymous(), subaccount: [] },
+        [],
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+      const tokenId = mintResult.Ok;
+
+      const metadata = await get(`/nft/${tokenId}/metadata`);
+      expect(metadata.status_code).toBe(200);
+      expect(metadata.headers.map(([name]) => name)).toContain(
+        "IC-Certificate",
+      );
+      const document = decode(metadata.body);
+      expect(document.name).toBe(`Indexed Art - #${tokenId}`);
+      expect(document.description).toBe("Served to marketplaces");
+      expect(document.image).toBe(photoUrl);
+      expect(document.attributes).toContainEqual({
+        trait_type: "artist",
+        value: "metadata_artist",
+      });
+
+      // The image URL is user-supplied and only listed, never redirected to
+      expect((await get(`/nft/${tokenId}/image`)).status_code).toBe(404);
+
+      const collection = decode((await get("/collection.json")).body);
+      expect(collection.name).toBe("Origin Stamp Art NFTs");
+      expect(collection.symbol).toBe("OSART");
+      expect(collection.total_supply).toBe(1);
+
+      await actor.icrc7_burn([
+        {
+          from_subaccount: [],
+          token_id: tokenId,
+          memo: [],
+          created_at_time: [],
+          reason: [],
+        },
+      ]);
+      expect((await get(`/nft/${tokenId}/metadata`)).status_code).toBe(404);
+      expect(decode((await get("/collection.json")).body).total_supply).toBe(0);
+    });
+
     it("should fail to mint NFT from non-existent session", async () => {
       const account = {
         owner: Principal.fromText("euqfo-6ybai-bqibi-ga4ea-scq"),
@@ -1387,6 +2475,7 @@ describe("OriginStamp Backend", () => {
         "nonexistent-session",
         account,
         [],
+        [],
       );
 
       expect("Ok" in mintResult).toBe(false);
@@ -1399,6 +2488,46 @@ describe("OriginStamp Backend", () => {
 
   // Certificate Tests
   describe("Certificate Functionality", () => {
+    // Create an active session with one photo and issue a certificate for it
+    async function issueCertificate(
+      username: string,
+      typeDetails: Parameters<
+        typeof actor.generate_certificate
+      >[0]["type_details"] = [],
+    ): Promise<string> {
+      const sessionResult = await actor.create_physical_art_session(
+        username,
+        "Issued Art",
+        "Certificate fixture",
+      );
+      if (!("Ok" in sessionResult)) throw new Error(sessionResult.Err);
+
+      await actor.upload_photo_to_session(
+        sessionResult.Ok,
+        "https://example.com/issued.jpg",
+      );
+      await actor.update_session_status(sessionResult.Ok, "active");
+
+      const certResult = await actor.generate_certificate({
+        session_id: sessionResult.Ok,
+        username,
+        art_title: "Issued Art",
+        description: "Certificate fixture",
+        photo_count: 1,
+        creation_duration: 60,
+        file_format: "PNG",
+        creation_tools: ["Camera"],
+        file_sizes: [1024n],
+        type_details: typeDetails,
+      });
+      if (!("Ok" in certResult)) throw new Error(certResult.Err);
+
+      // Pages, documents and QR codes are rendered in a follow-up message
+      await pic.tick();
+      await pic.tick();
+      return certResult.Ok.certificate_id;
+    }
+
     it("should generate certificate successfully", async () => {
       // Create session first
       const sessionResult = await actor.create_physical_art_session(
@@ -1443,7 +2572,7 @@ describe("OriginStamp Backend", () => {
         expect(cert.art_title).toBe("Certificate Art");
         expect(cert.verification_hash).toContain("0x");
         expect(cert.blockchain_tx).toContain("0x");
-        expect(cert.certificate_status).toBe("active");
+        expect(cert.certificate_status).toEqual({ Active: null });
         expect(cert.verification_score).toBeGreaterThan(0);
         expect(cert.authenticity_rating).toBeGreaterThan(0);
       }
@@ -1552,8 +2681,10 @@ describe("OriginStamp Backend", () => {
       expect("Ok" in verifyResult).toBe(true);
       if ("Ok" in verifyResult) {
         expect(verifyResult.Ok.valid).toBe(true);
-        expect(verifyResult.Ok.score).toBeGreaterThan(0);
-        expect(verifyResult.Ok.details).toContain("verified");
+        expect(verifyResult.Ok.status).toEqual({ Active: null });
+        expect(verifyResult.Ok.certificate_id).toBe(certificateId);
+        expect(verifyResult.Ok.scores.verification_score).toBeGreaterThan(0);
+        expect(verifyResult.Ok.revocation).toEqual([]);
       }
     });
 
@@ -1623,6 +2754,939 @@ describe("OriginStamp Backend", () => {
       }
     });
 
+    it("should sign certificates with the canister threshold key", async () => {
+      const certificateId = await issueCertificate("signed_artist");
+
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      expect(certificate[0]?.signature).toMatch(/^[0-9a-f]{128}$/);
+
+      const payload =
+        await actor.get_certificate_signing_payload(certificateId);
+      expect(payload.length).toBe(1);
+      const payloadHash = createHash("sha256")
+        .update(payload[0] ?? "")
+        .digest("hex");
+      expect(certificate[0]?.verification_hash).toBe(`0x${payloadHash}`);
+
+      const publicKey = await actor.get_certificate_public_key();
+      expect(publicKey.length).toBe(1);
+      expect(publicKey[0]?.key_name).toBe("dfx_test_key");
+      expect(publicKey[0]?.public_key).toMatch(/^0[23][0-9a-f]{64}$/);
+      expect(certificate[0]?.signing_key_id).toBe(publicKey[0]?.key_id);
+    });
+
+    it("should keep retired signing keys verifiable", async () => {
+      // The key is fetched when configured, before anything is signed
+      const initialKey = await actor.get_certificate_public_key();
+      expect(initialKey.length).toBe(1);
+      expect(initialKey[0]?.active).toBe(true);
+
+      const certificateId = await issueCertificate("rotation_artist");
+      const credential = await actor.export_certificate_vc(certificateId);
+      expect("Ok" in credential).toBe(true);
+      if (!("Ok" in credential)) return;
+
+      actor.setIdentity(createIdentity("rotation_stranger"));
+      expect(await actor.set_signing_key_name("test_key_1")).toEqual({
+        Err: "Only admins can perform this action",
+      });
+      actor.setPrincipal(Principal.anonymous());
+      expect(await actor.set_signing_key_name("local_key")).toEqual({
+        Err: "Invalid key name: must be one of dfx_test_key, test_key_1, key_1",
+      });
+
+      const rotated = await actor.set_signing_key_name("test_key_1");
+      expect("Ok" in rotated).toBe(true);
+      if (!("Ok" in rotated)) return;
+      expect(rotated.Ok.key_id).not.toBe(initialKey[0]?.key_id);
+
+      const keys = await actor.get_certificate_signing_keys();
+      expect(keys.map((key) => key.key_id).sort()).toEqual(
+        [initialKey[0]?.key_id ?? "", rotated.Ok.key_id].sort(),
+      );
+      expect(
+        keys.find((key) => key.key_id === initialKey[0]?.key_id)?.active,
+      ).toBe(false);
+
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      expect(certificate[0]?.signing_key_id).toBe(initialKey[0]?.key_id);
+
+      const verified = await actor.verify_certificate_vc(credential.Ok);
+      expect("Ok" in verified).toBe(true);
+      if ("Ok" in verified) {
+        expect(verified.Ok.valid).toBe(true);
+      }
+    });
+
+    it("should return certified certificate data from a query", async () => {
+      const certificateId = await issueCertificate("certified_artist");
+
+      const certifiedResult =
+        await actor.get_certificate_certified(certificateId);
+      expect("Ok" in certifiedResult).toBe(true);
+      if (!("Ok" in certifiedResult)) return;
+
+      const certified = certifiedResult.Ok;
+      expect(certified.certificate[0]?.certificate_id).toBe(certificateId);
+      expect(certified.certificate_bytes.length).toBeGreaterThan(0);
+      expect(certified.data_certificate.length).toBeGreaterThan(0);
+      expect(certified.witness.length).toBeGreaterThan(0);
+
+      const missing = await actor.get_certificate_certified("missing-cert");
+      expect("Ok" in missing).toBe(true);
+      if ("Ok" in missing) {
+        expect(missing.Ok.certificate).toEqual([]);
+        expect(missing.Ok.witness.length).toBeGreaterThan(0);
+      }
+    });
+
+    it("should serve certified verification pages over HTTP", async () => {
+      const certificateId = await issueCertificate("http_artist");
+      const get = (url: string) =>
+        actor.http_request({
+          method: "GET",
+          url,
+          headers: [],
+          body: new Uint8Array(),
+          certificate_version: [],
+        });
+
+      const page = await get(`/verify/${certificateId}`);
+      expect(page.status_code).toBe(200);
+      const headerNames = page.headers.map(([name]) => name);
+      expect(headerNames).toContain("IC-Certificate");
+      expect(new TextDecoder().decode(new Uint8Array(page.body))).toContain(
+        certificateId,
+      );
+
+      const json = await get(`/verify/${certificateId}.json`);
+      expect(json.status_code).toBe(200);
+      const document = JSON.parse(
+        new TextDecoder().decode(new Uint8Array(json.body)),
+      );
+      expect(document.certificate_id).toBe(certificateId);
+      expect(document.status).toBe("active");
+      expect(document.evidence.timeline.length).toBeGreaterThan(0);
+
+      const missing = await get("/verify/missing-cert");
+      expect(missing.status_code).toBe(404);
+    });
+
+    it("should serve HEAD with the certified body and track evidence changes", async () => {
+      const certificateId = await issueCertificate("http_head_artist");
+      const request = (method: string) =>
+        actor.http_request({
+          method,
+          url: `/verify/${certificateId}`,
+          headers: [],
+          body: new Uint8Array(),
+          certificate_version: [],
+        });
+
+      const page = await request("GET");
+      const head = await request("HEAD");
+      expect(head.status_code).toBe(200);
+      expect(head.body).toEqual(page.body);
+      const html = new TextDecoder().decode(new Uint8Array(page.body));
+      expect(html).toContain("Valid until");
+      expect(html).toContain("Expired on");
+
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      if (!certificate[0]) throw new Error("certificate missing");
+      await actor.upload_photo_to_session(
+        certificate[0].session_id,
+        "https://example.com/late-evidence.jpg",
+      );
+      await pic.tick();
+      await pic.tick();
+      const updated = await request("GET");
+      expect(new TextDecoder().decode(new Uint8Array(updated.body))).toContain(
+        "https://example.com/late-evidence.jpg",
+      );
+    });
+
+    it("should generate certificate QR codes as SVG and PNG", async () => {
+      const certificateId = await issueCertificate("qr_artist");
+
+      const svgResult = await actor.get_certificate_qr(
+        certificateId,
+        { Svg: null },
+        [],
+      );
+      expect("Ok" in svgResult).toBe(true);
+      if (!("Ok" in svgResult)) return;
+      expect(svgResult.Ok.content_type).toBe("image/svg+xml");
+      expect(svgResult.Ok.encoded_url).toContain(`/verify/${certificateId}`);
+      expect(
+        new TextDecoder().decode(new Uint8Array(svgResult.Ok.data)),
+      ).toContain("<svg");
+
+      const pngResult = await actor.get_certificate_qr(
+        certificateId,
+        { Png: null },
+        [{ error_correction: [{ High: null }], size: [512], quiet_zone: [] }],
+      );
+      expect("Ok" in pngResult).toBe(true);
+      if (!("Ok" in pngResult)) return;
+      expect(pngResult.Ok.content_type).toBe("image/png");
+      expect(pngResult.Ok.size).toBeLessThanOrEqual(512);
+      expect(Array.from(pngResult.Ok.data.slice(0, 4))).toEqual([
+        0x89, 0x50, 0x4e, 0x47,
+      ]);
+
+      const tooSmall = await actor.get_certificate_qr(
+        certificateId,
+        { Png: null },
+        [{ error_correction: [], size: [8], quiet_zone: [] }],
+      );
+      expect("Err" in tooSmall).toBe(true);
+
+      const served = await actor.http_request({
+        method: "GET",
+        url: `/qr/${certificateId}.svg`,
+        headers: [],
+        body: new Uint8Array(),
+        certificate_version: [],
+      });
+      expect(served.status_code).toBe(200);
+      expect(served.headers).toContainEqual(["Content-Type", "image/svg+xml"]);
+    });
+
+    it("should render printable certificates as SVG and PDF", async () => {
+      const certificateId = await issueCertificate("printable_artist");
+
+      const svgResult = await actor.get_certificate_document(certificateId, {
+        Svg: null,
+      });
+      expect("Ok" in svgResult).toBe(true);
+      if (!("Ok" in svgResult)) return;
+      const svg = new TextDecoder().decode(new Uint8Array(svgResult.Ok.data));
+      expect(svg).toContain("Certificate of Authenticity");
+      expect(svg).toContain(certificateId);
+
+      const pdfResult = await actor.get_certificate_document(certificateId, {
+        Pdf: null,
+      });
+      expect("Ok" in pdfResult).toBe(true);
+      if (!("Ok" in pdfResult)) return;
+      expect(pdfResult.Ok.content_type).toBe("application/pdf");
+      const pdf = new TextDecoder().decode(new Uint8Array(pdfResult.Ok.data));
+      expect(pdf.startsWith("%PDF-1.4")).toBe(true);
+      expect(pdf.trimEnd().endsWith("%%EOF")).toBe(true);
+
+      const served = await actor.http_request({
+        method: "GET",
+        url: `/certificate/${certificateId}.pdf`,
+        headers: [],
+        body: new Uint8Array(),
+        certificate_version: [],
+      });
+      expect(served.status_code).toBe(200);
+    });
+
+    it("should only let admins configure certificate templates", async () => {
+      const certificateId = await issueCertificate("template_artist");
+      const template = {
+        certificate_type: { Standard: null },
+        heading: "Gallery Certificate",
+        accent_color: "#7C2D12",
+        background_color: "#FFFFFF",
+        footer_text: "Printed for the gallery archive",
+        show_scores: false,
+      };
+
+      actor.setIdentity(createIdentity("template_stranger"));
+      const rejected = await actor.set_certificate_template(template);
+      expect("Err" in rejected).toBe(true);
+
+      // The anonymous principal deployed the canister, so it is a controller
+      actor.setPrincipal(Principal.anonymous());
+      const invalid = await actor.set_certificate_template({
+        ...template,
+        accent_color: "red",
+      });
+      expect("Err" in invalid).toBe(true);
+
+      const accepted = await actor.set_certificate_template(template);
+      expect(accepted).toEqual({ Ok: true });
+      const stored = await actor.get_certificate_template({ Standard: null });
+      expect(stored.heading).toBe("Gallery Certificate");
+
+      // Published documents are re-rendered in batches after the update returns
+      await pic.tick();
+      await pic.tick();
+      const served = await actor.http_request({
+        method: "GET",
+        url: `/certificate/${certificateId}.svg`,
+        headers: [],
+        body: new Uint8Array(),
+        certificate_version: [],
+      });
+      expect(new TextDecoder().decode(new Uint8Array(served.body))).toContain(
+        "Gallery Certificate",
+      );
+      expect(await actor.render_pending_certificate_assets()).toEqual({
+        Ok: 0n,
+      });
+
+      actor.setIdentity(createIdentity("render_stranger"));
+      expect(await actor.render_pending_certificate_assets()).toEqual({
+        Err: "Only admins can perform this action",
+      });
+    });
+
+    it("should export and verify certificates as verifiable credentials", async () => {
+      const certificateId = await issueCertificate("vc_artist");
+
+      const exportResult = await actor.export_certificate_vc(certificateId);
+      expect("Ok" in exportResult).toBe(true);
+      if (!("Ok" in exportResult)) return;
+
+      const credential = JSON.parse(exportResult.Ok);
+      expect(credential["@context"][0]).toBe(
+        "https://www.w3.org/ns/credentials/v2",
+      );
+      expect(credential.issuer.id).toMatch(/^did:web:.+\.icp0\.io$/);
+      expect(credential.credentialSubject.certificateId).toBe(certificateId);
+      expect(credential.proof.cryptosuite).toBe(
+        "originstamp-secp256k1-jcs-2025",
+      );
+      expect(credential.proof.proofValue).toMatch(/^z/);
+
+      const verified = await actor.verify_certificate_vc(exportResult.Ok);
+      expect("Ok" in verified).toBe(true);
+      if ("Ok" in verified) {
+        expect(verified.Ok.valid).toBe(true);
+        expect(verified.Ok.errors).toEqual([]);
+      }
+
+      credential.credentialSubject.title = "Forged title";
+      const tampered = await actor.verify_certificate_vc(
+        JSON.stringify(credential),
+      );
+      expect("Ok" in tampered).toBe(true);
+      if ("Ok" in tampered) {
+        expect(tampered.Ok.signature_valid).toBe(false);
+        expect(tampered.Ok.valid).toBe(false);
+      }
+
+      const didDocument = await actor.http_request({
+        method: "GET",
+        url: "/.well-known/did.json",
+        headers: [],
+        body: new Uint8Array(),
+        certificate_version: [],
+      });
+      expect(didDocument.status_code).toBe(200);
+      const did = JSON.parse(
+        new TextDecoder().decode(new Uint8Array(didDocument.body)),
+      );
+      expect(did.id).toBe(credential.issuer.id);
+    });
+
+    it("should export a signed provenance manifest with evidence ingredients", async () => {
+      const certificateId = await issueCertificate("provenance_artist");
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      const sessionId = certificate[0]?.session_id ?? "";
+
+      const evidence = await actor.get_session_evidence(sessionId);
+      expect(evidence.length).toBe(1);
+      const contentHash = createHash("sha256").update("photo").digest("hex");
+      const recorded = await actor.record_photo_hash(
+        sessionId,
+        evidence[0]?.photo_url ?? "",
+        contentHash,
+      );
+      expect(recorded).toEqual({ Ok: true });
+      const conflicting = await actor.record_photo_hash(
+        sessionId,
+        evidence[0]?.photo_url ?? "",
+        "0".repeat(64),
+      );
+      expect("Err" in conflicting).toBe(true);
+
+      // Only the session owner or an admin may record or correct hashes
+      actor.setIdentity(createIdentity("provenance_stranger"));
+      expect(
+        await actor.correct_photo_hash(
+          sessionId,
+          evidence[0]?.photo_url ?? "",
+          "0".repeat(64),
+        ),
+      ).toEqual({
+        Err: "Only the session owner or admins can record photo hashes",
+      });
+      // Nor can strangers add or remove the photos the evidence is built from
+      expect(
+        await actor.upload_photo_to_session(
+          sessionId,
+          "https://example.com/injected.jpg",
+        ),
+      ).toEqual({
+        Err: "Only the session owner or admins can change session photos",
+      });
+      expect(
+        await actor.remove_photo_from_session(
+          sessionId,
+          evidence[0]?.photo_url ?? "",
+        ),
+      ).toEqual({
+        Err: "Only the session owner or admins can change session photos",
+      });
+      actor.setPrincipal(Principal.anonymous());
+      expect(
+        await actor.correct_photo_hash(
+          sessionId,
+          evidence[0]?.photo_url ?? "",
+          "0".repeat(64),
+        ),
+      ).toEqual({ Ok: true });
+      expect(
+        await actor.correct_photo_hash(
+          sessionId,
+          evidence[0]?.photo_url ?? "",
+          contentHash,
+        ),
+      ).toEqual({ Ok: true });
+
+      const exportResult = await actor.export_certificate_provenance(
+        certificateId,
+      );
+      expect("Ok" in exportResult).toBe(true);
+      if (!("Ok" in exportResult)) return;
+
+      const manifest = exportResult.Ok;
+      expect(manifest.file_name).toBe(`${certificateId}.provenance.jumbf`);
+      expect(manifest.manifest_label).toMatch(/^urn:uuid:/);
+      const data = new Uint8Array(manifest.data);
+      // Outer JUMBF superbox followed by its description box
+      expect(new TextDecoder().decode(data.slice(4, 8))).toBe("jumb");
+      expect(new TextDecoder().decode(data.slice(12, 16))).toBe("jumd");
+      expect(new TextDecoder().decode(data.slice(16, 20))).toBe("c2pa");
+
+      const served = await actor.http_request({
+        method: "GET",
+        url: `/certificate/${certificateId}.provenance.jumbf`,
+        headers: [],
+        body: new Uint8Array(),
+        certificate_version: [],
+      });
+      expect(served.status_code).toBe(200);
+      expect(served.headers).toContainEqual([
+        "Content-Type",
+        "application/octet-stream",
+      ]);
+    });
+
+    it("should export a signed offline verification bundle", async () => {
+      const certificateId = await issueCertificate("bundle_artist");
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      const sessionId = certificate[0]?.session_id ?? "";
+
+      // Photos without a recorded hash are listed but not Merkle leaves
+      const unhashed = await actor.export_verification_bundle(certificateId);
+      expect("Ok" in unhashed).toBe(true);
+      if (!("Ok" in unhashed)) return;
+      const unhashedBundle = JSON.parse(
+        new TextDecoder().decode(new Uint8Array(unhashed.Ok.data)),
+      );
+      expect(unhashedBundle.evidence.photos).toHaveLength(1);
+      expect(unhashedBundle.evidence.merkle.leaves).toEqual([]);
+      expect(unhashedBundle.evidence.merkle.root).toBe(
+        createHash("sha256").update("").digest("hex"),
+      );
+
+      expect(
+        await actor.record_photo_hash(
+          sessionId,
+          "https://example.com/issued.jpg",
+          createHash("sha256").update("photo").digest("hex"),
+        ),
+      ).toEqual({ Ok: true });
+
+      const exportResult = await actor.export_verification_bundle(certificateId);
+      expect("Ok" in exportResult).toBe(true);
+      if (!("Ok" in exportResult)) return;
+
+      expect(exportResult.Ok.file_name).toBe(`${certificateId}.osvb.json`);
+      const bundle = JSON.parse(
+        new TextDecoder().decode(new Uint8Array(exportResult.Ok.data)),
+      );
+      expect(bundle.format).toBe("originstamp-verification-bundle");
+      expect(bundle.version).toBe(2);
+      expect(bundle.certificate.certificate_id).toBe(certificateId);
+      // Nanosecond timestamps exceed 2^53 and are carried as strings
+      expect(typeof bundle.generated_at).toBe("string");
+      expect(bundle.certificate.issue_date).toBe(
+        String(certificate[0]?.issue_date),
+      );
+      expect(
+        createHash("sha256").update(bundle.signing_payload).digest("hex"),
+      ).toBe(bundle.certificate.verification_hash.slice(2));
+      expect(bundle.evidence.photos).toHaveLength(1);
+      expect(bundle.evidence.merkle.leaves).toHaveLength(1);
+      // A single leaf is its own root
+      expect(bundle.evidence.merkle.root).toBe(bundle.evidence.merkle.leaves[0]);
+      expect(bundle.process_log.length).toBeGreaterThan(0);
+      expect(bundle.bundle_signature.algorithm).toBe("ecdsa-secp256k1-sha256");
+      expect(bundle.bundle_signature.value).toMatch(/^[0-9a-f]{128}$/);
+
+      // Unchanged certificates reuse the signed bundle
+      const again = await actor.export_verification_bundle(certificateId);
+      expect(again).toEqual(exportResult);
+
+      const missing = await actor.export_verification_bundle("missing");
+      expect(missing).toEqual({ Err: "Certificate not found" });
+    });
+
+    it("should verify certificates in batches and by hash or token", async () => {
+      const first = await issueCertificate("batch_artist_one");
+      const second = await issueCertificate("batch_artist_two");
+
+      const batch = await actor.verify_certificates([first, "missing", second]);
+      expect("Ok" in batch).toBe(true);
+      if (!("Ok" in batch)) return;
+      expect(batch.Ok.map((entry) => entry.certificate_id)).toEqual([
+        first,
+        "missing",
+        second,
+      ]);
+      expect("Ok" in batch.Ok[0].result).toBe(true);
+      expect(batch.Ok[1].result).toEqual({ Err: "Certificate not found" });
+
+      const oversized = await actor.verify_certificates(
+        Array.from({ length: 201 }, (_, i) => `certificate_${i}`),
+      );
+      expect("Err" in oversized).toBe(true);
+
+      const certificate = await actor.get_certificate_by_id(first);
+      const hash = certificate[0]?.verification_hash ?? "";
+      const byHash = await actor.verify_certificate_by_hash(
+        hash.slice(2).toUpperCase(),
+      );
+      expect("Ok" in byHash && byHash.Ok.certificate_id).toBe(first);
+
+      await actor.set_user_subscription("batch_artist_one", { Basic: null });
+      const mintResult = await actor.mint_certificate_nft(
+        first,
+        {
+          owner: Principal.anonymous(),
+          subaccount: [],
+        },
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+
+      const byToken = await actor.verify_certificate_by_token(mintResult.Ok);
+      expect("Ok" in byToken && byToken.Ok.certificate_id).toBe(first);
+      const unknownToken = await actor.verify_certificate_by_token(999_999n);
+      expect("Err" in unknownToken).toBe(true);
+    });
+
+    it("should unlink a burned certificate NFT so it can be re-minted", async () => {
+      const certificateId = await issueCertificate("burn_cert_artist");
+      await actor.set_user_subscription("burn_cert_artist", { Basic: null });
+      const wrongRecipient = createIdentity("wrong_recipient").getPrincipal();
+      const mintResult = await actor.mint_certificate_nft(
+        certificateId,
+        {
+          owner: wrongRecipient,
+          subaccount: [],
+        },
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+
+      // Admins can burn tokens they do not own
+      const burned = await actor.icrc7_burn([
+        {
+          from_subaccount: [],
+          token_id: mintResult.Ok,
+          memo: [],
+          created_at_time: [],
+          reason: ["wrong recipient"],
+        },
+      ]);
+      expect(burned[0][0] && "Ok" in burned[0][0]).toBe(true);
+
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      expect(certificate[0]?.nft_generated).toBe(false);
+      expect(certificate[0]?.nft_id).toEqual([]);
+      expect(certificate[0]?.token_uri).toEqual([]);
+      expect(
+        "Err" in (await actor.verify_certificate_by_token(mintResult.Ok)),
+      ).toBe(true);
+
+      const corrected = createIdentity("right_recipient").getPrincipal();
+      const reminted = await actor.mint_certificate_nft(
+        certificateId,
+        {
+          owner: corrected,
+          subaccount: [],
+        },
+        [],
+      );
+      expect("Ok" in reminted).toBe(true);
+      if (!("Ok" in reminted)) return;
+      expect(reminted.Ok).not.toBe(mintResult.Ok);
+      const owners = await actor.icrc7_owner_of([reminted.Ok]);
+      expect(owners[0][0]?.owner).toEqual(corrected);
+    });
+
+    it("should log verifications and report counts to the owner", async () => {
+      const certificateId = await issueCertificate("analytics_artist");
+
+      const webCheck = await actor.record_certificate_verification(
+        certificateId,
+        { Web: null },
+      );
+      expect("Ok" in webCheck && webCheck.Ok.valid).toBe(true);
+
+      actor.setIdentity(createIdentity("collector"));
+      await actor.record_certificate_verification(certificateId, { Qr: null });
+      await actor.record_certificate_verification(certificateId, { Qr: null });
+      const missing = await actor.record_certificate_verification("missing", {
+        Api: null,
+      });
+      expect(missing).toEqual({ Err: "Certificate not found" });
+
+      // Only the owner and staff can read the log
+      const denied = await actor.get_certificate_verification_events(
+        certificateId,
+        [],
+      );
+      expect("Err" in denied).toBe(true);
+
+      actor.setPrincipal(Principal.anonymous());
+      const events = await actor.get_certificate_verification_events(
+        certificateId,
+        [],
+      );
+      expect("Ok" in events).toBe(true);
+      if (!("Ok" in events)) return;
+      expect(events.Ok).toHaveLength(3);
+      // Newest first; the anonymous web check has no caller hash
+      expect(events.Ok[0].channel).toEqual({ Qr: null });
+      expect(events.Ok[0].caller_hash[0]).toMatch(/^[0-9a-f]{16}$/);
+      expect(events.Ok[0].caller_hash).toEqual(events.Ok[1].caller_hash);
+      expect(events.Ok[2].caller_hash).toEqual([]);
+
+      const stats = await actor.get_certificate_verification_stats(
+        certificateId,
+        [],
+      );
+      expect("Ok" in stats).toBe(true);
+      if (!("Ok" in stats)) return;
+      expect(stats.Ok.total).toEqual({ qr: 2n, api: 0n, web: 1n, total: 3n });
+      expect(stats.Ok.daily.length).toBeGreaterThan(0);
+      expect(stats.Ok.last_verified_at).toEqual([events.Ok[0].timestamp]);
+
+      const metrics = await actor.get_dashboard_metrics();
+      expect(metrics.total_verifications).toBe(3n);
+      expect(metrics.verifications_last_30_days).toBe(3n);
+    });
+
+    it("should issue typed certificates with type-specific validation", async () => {
+      const standardId = await issueCertificate("typed_artist");
+      const standard = await actor.get_certificate_by_id(standardId);
+      expect(standard[0]?.certificate_type).toEqual({ Standard: null });
+      expect(standard[0]?.token_standard).toBe("ICRC-7");
+      expect(standard[0]?.issuer).toBe("OriginStamp");
+
+      const editionId = await issueCertificate("typed_artist", [
+        { LimitedEdition: { edition_number: 1, edition_size: 10 } },
+      ]);
+      const edition = await actor.get_certificate_by_id(editionId);
+      expect(edition[0]?.certificate_type).toEqual({ LimitedEdition: null });
+      const payload = await actor.get_certificate_signing_payload(editionId);
+      expect(JSON.parse(payload[0] ?? "{}").type_details).toEqual({
+        LimitedEdition: { edition_number: 1, edition_size: 10 },
+      });
+
+      // The same edition of a work can only be certified once
+      await expect(
+        issueCertificate("typed_artist", [
+          { LimitedEdition: { edition_number: 1, edition_size: 10 } },
+        ]),
+      ).rejects.toThrow("already certified");
+      await expect(
+        issueCertificate("typed_artist", [
+          { LimitedEdition: { edition_number: 11, edition_size: 10 } },
+        ]),
+      ).rejects.toThrow("edition_number");
+
+      // Restorations need before and after photos; digital works need photo hashes
+      await expect(
+        issueCertificate("typed_artist", [
+          {
+            Restoration: {
+              original_artist: "Unknown",
+              original_year: [1890],
+              scope: "Varnish removal",
+            },
+          },
+        ]),
+      ).rejects.toThrow("at least 2 process photos");
+      await expect(
+        issueCertificate("typed_artist", [
+          { Digital: { master_file_hash: "ab".repeat(32) } },
+        ]),
+      ).rejects.toThrow("content hash");
+
+      actor.setIdentity(createIdentity("issuer_stranger"));
+      expect("Err" in (await actor.set_certificate_issuer("Rogue"))).toBe(
+        true,
+      );
+      actor.setPrincipal(Principal.anonymous());
+      expect(await actor.set_certificate_issuer("Gallery Nusantara")).toEqual({
+        Ok: true,
+      });
+      const galleryId = await issueCertificate("typed_artist_two");
+      const gallery = await actor.get_certificate_by_id(galleryId);
+      expect(gallery[0]?.issuer).toBe("Gallery Nusantara");
+      // Certificates keep the issuer they were signed with
+      expect(
+        (await actor.get_certificate_by_id(standardId))[0]?.issuer,
+      ).toBe("OriginStamp");
+    });
+
+    it("should search certificates with ranking, filters and pagination", async () => {
+      const painterId = await issueCertificate("search_painter");
+      const sculptorId = await issueCertificate("search_sculptor");
+      const search = (query: string, overrides = {}) =>
+        actor.search_certificates({
+          query,
+          status: [],
+          certificate_type: [],
+          issued_after: [],
+          issued_before: [],
+          nft_minted: [],
+          offset: [],
+          limit: [],
+          ...overrides,
+        });
+
+      const byArtist = await search("Painter");
+      expect("Ok" in byArtist).toBe(true);
+      if (!("Ok" in byArtist)) return;
+      expect(byArtist.Ok.total).toBe(1n);
+      expect(byArtist.Ok.hits[0].certificate.certificate_id).toBe(painterId);
+
+      // The last term also matches as a prefix
+      const prefix = await search("issued paint");
+      expect("Ok" in prefix && prefix.Ok.hits.length).toBe(1);
+
+      const page = await search("camera", { limit: [1] });
+      expect("Ok" in page).toBe(true);
+      if (!("Ok" in page)) return;
+      expect(page.Ok.total).toBe(2n);
+      expect(page.Ok.hits).toHaveLength(1);
+      const nextPage = await search("camera", { limit: [1], offset: [1] });
+      expect("Ok" in nextPage && nextPage.Ok.hits).toHaveLength(1);
+
+      await actor.revoke_certificate(sculptorId, { Fraudulent: null });
+      const revoked = await search("", { status: [{ Revoked: null }] });
+      expect(
+        "Ok" in revoked &&
+          revoked.Ok.hits.map((hit) => hit.certificate.certificate_id),
+      ).toEqual([sculptorId]);
+
+      const editions = await search("issued", {
+        certificate_type: [{ LimitedEdition: null }],
+      });
+      expect("Ok" in editions && editions.Ok.total).toBe(0n);
+      const unminted = await search("issued", { nft_minted: [false] });
+      expect("Ok" in unminted && unminted.Ok.total).toBe(2n);
+
+      const invalidRange = await search("", {
+        issued_after: [2n],
+        issued_before: [1n],
+      });
+      expect("Err" in invalidRange).toBe(true);
+    });
+
+    it("should revoke certificate and report revocation", async () => {
+      const certificateId = await issueCertificate("revoke_artist");
+
+      const revokeResult = await actor.revoke_certificate(certificateId, {
+        Fraudulent: null,
+      });
+      expect("Ok" in revokeResult).toBe(true);
+      if ("Ok" in revokeResult) {
+        expect(revokeResult.Ok.certificate_id).toBe(certificateId);
+        expect(revokeResult.Ok.reason).toEqual({ Fraudulent: null });
+      }
+
+      const certificate = await actor.get_certificate_by_id(certificateId);
+      expect(certificate[0]?.certificate_status).toEqual({ Revoked: null });
+
+      const verifyResult = await actor.verify_certificate(certificateId);
+      expect("Ok" in verifyResult).toBe(true);
+      if ("Ok" in verifyResult) {
+        expect(verifyResult.Ok.valid).toBe(false);
+        expect(verifyResult.Ok.status).toEqual({ Revoked: null });
+        expect(verifyResult.Ok.revocation[0]?.reason).toEqual({
+          Fraudulent: null,
+        });
+      }
+
+      const revocations = await actor.get_revocation_list([]);
+      expect(revocations.map((entry) => entry.certificate_id)).toContain(
+        certificateId,
+      );
+
+      const secondRevoke = await actor.revoke_certificate(certificateId, {
+        OwnerRequest: null,
+      });
+      expect(secondRevoke).toEqual({ Err: "Certificate is already revoked" });
+    });
+
+    it("should reject revocation by someone other than the owner", async () => {
+      const certificateId = await issueCertificate("protected_artist");
+
+      actor.setIdentity(createIdentity("stranger"));
+      const revokeResult = await actor.revoke_certificate(certificateId, {
+        Other: "Not my certificate",
+      });
+
+      expect("Err" in revokeResult).toBe(true);
+      const revocations = await actor.get_revocation_list([]);
+      expect(revocations).toHaveLength(0);
+    });
+
+    it("should renew certificate and track version lineage", async () => {
+      const certificateId = await issueCertificate("renew_artist");
+
+      const renewResult = await actor.renew_certificate(certificateId, [5]);
+      expect("Ok" in renewResult).toBe(true);
+      if (!("Ok" in renewResult)) return;
+
+      const renewed = renewResult.Ok;
+      expect(renewed.certificate_id).not.toBe(certificateId);
+      expect(renewed.version).toBe(2);
+      expect(renewed.supersedes).toEqual([certificateId]);
+
+      const previous = await actor.get_certificate_by_id(certificateId);
+      expect(previous[0]?.certificate_status).toEqual({ Superseded: null });
+      expect(previous[0]?.superseded_by).toEqual([renewed.certificate_id]);
+
+      const history = await actor.get_certificate_history(certificateId);
+      expect(history.map((cert) => cert.certificate_id)).toEqual([
+        certificateId,
+        renewed.certificate_id,
+      ]);
+
+      const renewAgain = await actor.renew_certificate(certificateId, []);
+      expect("Err" in renewAgain).toBe(true);
+
+      const revokeSuperseded = await actor.revoke_certificate(certificateId, {
+        OwnerRequest: null,
+      });
+      expect(revokeSuperseded).toEqual({
+        Err: `Certificate has been superseded by ${renewed.certificate_id}; revoke the current version`,
+      });
+    });
+
+    it("should move the NFT link to the renewed certificate", async () => {
+      const certificateId = await issueCertificate("renew_nft_artist");
+      await actor.set_user_subscription("renew_nft_artist", { Basic: null });
+      const mintResult = await actor.mint_certificate_nft(
+        certificateId,
+        {
+          owner: Principal.anonymous(),
+          subaccount: [],
+        },
+        [],
+      );
+      expect("Ok" in mintResult).toBe(true);
+      if (!("Ok" in mintResult)) return;
+
+      const renewResult = await actor.renew_certificate(certificateId, []);
+      expect("Ok" in renewResult).toBe(true);
+      if (!("Ok" in renewResult)) return;
+      const renewed = renewResult.Ok;
+      expect(renewed.nft_id).toEqual([mintResult.Ok.toString()]);
+
+      const previous = await actor.get_certificate_by_id(certificateId);
+      expect(previous[0]?.nft_generated).toBe(false);
+      expect(previous[0]?.nft_id).toEqual([]);
+      expect(previous[0]?.token_uri).toEqual([]);
+
+      const byToken = await actor.verify_certificate_by_token(mintResult.Ok);
+      expect("Ok" in byToken && byToken.Ok.certificate_id).toBe(
+        renewed.certificate_id,
+      );
+    });
+
+    it("should reissue certificate with new session evidence", async () => {
+      const certificateId = await issueCertificate("reissue_artist");
+      const original = await actor.get_certificate_by_id(certificateId);
+      if (!original[0]) throw new Error("certificate missing");
+
+      await actor.upload_photo_to_session(
+        original[0].session_id,
+        "https://example.com/extra-evidence.jpg",
+      );
+
+      const reissueResult = await actor.reissue_certificate(certificateId);
+      expect("Ok" in reissueResult).toBe(true);
+      if ("Ok" in reissueResult) {
+        expect(reissueResult.Ok.metadata.total_actions).toBe(2);
+        expect(reissueResult.Ok.verification_score).toBeGreaterThan(
+          original[0].verification_score,
+        );
+        expect(reissueResult.Ok.supersedes).toEqual([certificateId]);
+      }
+    });
+
+    it("should require the certificate type's evidence on reissue", async () => {
+      const sessionResult = await actor.create_physical_art_session(
+        "reissue_digital_artist",
+        "Digital Art",
+        "Hashed evidence",
+      );
+      if (!("Ok" in sessionResult)) throw new Error(sessionResult.Err);
+      const sessionId = sessionResult.Ok;
+      await actor.upload_photo_to_session(
+        sessionId,
+        "https://example.com/digital.jpg",
+      );
+      await actor.record_photo_hash(
+        sessionId,
+        "https://example.com/digital.jpg",
+        "cd".repeat(32),
+      );
+      await actor.update_session_status(sessionId, "active");
+
+      const certResult = await actor.generate_certificate({
+        session_id: sessionId,
+        username: "reissue_digital_artist",
+        art_title: "Digital Art",
+        description: "Hashed evidence",
+        photo_count: 1,
+        creation_duration: 60,
+        file_format: "PNG",
+        creation_tools: ["Tablet"],
+        file_sizes: [1024n],
+        type_details: [{ Digital: { master_file_hash: "ab".repeat(32) } }],
+      });
+      if (!("Ok" in certResult)) throw new Error(certResult.Err);
+
+      // A new photo without a recorded hash no longer meets the Digital rules
+      await actor.upload_photo_to_session(
+        sessionId,
+        "https://example.com/digital-unhashed.jpg",
+      );
+      const reissueResult = await actor.reissue_certificate(
+        certResult.Ok.certificate_id,
+      );
+      expect(reissueResult).toEqual({
+        Err: "Digital certificates require a recorded content hash for every photo",
+      });
+    });
+
     it("should handle certificate with various creation tools", async () => {
       const sessionResult = await actor.create_physical_art_session(
         "tools_artist",
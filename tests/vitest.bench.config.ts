@@ -0,0 +1,13 @@
+import { defineConfig } from "vitest/config";
+import { resolve } from "path";
+
+// Benchmarks run against a backend built with `--features bench`
+export default defineConfig({
+  test: {
+    root: ".",
+    globalSetup: resolve(__dirname, "backend-test-setup.ts"),
+    testTimeout: 600_000,
+    hookTimeout: 60_000,
+    include: ["bench/**/*.test.ts"],
+  },
+});